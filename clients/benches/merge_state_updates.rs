@@ -0,0 +1,45 @@
+#[macro_use]
+extern crate criterion;
+
+use criterion::Criterion;
+use ethereum_types::Address;
+use ovm::types::{Integer, Property, StateUpdate};
+use plasma_clients::plasma::plasma_block::PlasmaBlock;
+use plasma_core::data_structure::Range;
+
+/// State updates of width 1 whose owner changes every `run_length` coins like a batch payment.
+fn create_state_updates(size: u64, run_length: u64) -> Vec<StateUpdate> {
+    (0..size)
+        .map(|i| {
+            StateUpdate::new(
+                Integer::new(1),
+                Address::zero(),
+                Range::new(i, i + 1),
+                Property::new(Address::from_low_u64_be(i / run_length), vec![]),
+            )
+        })
+        .collect()
+}
+
+fn merge_state_updates_benchmark(c: &mut Criterion) {
+    for size in [100, 1000].iter() {
+        let mut block = PlasmaBlock::new(1, create_state_updates(*size, 10), vec![]);
+        block.merge_state_updates();
+        assert_eq!(block.get_state_updates().len() as u64, size / 10);
+    }
+    c.bench_function_over_inputs(
+        "merge_state_updates",
+        |b, &&size| {
+            let state_updates = create_state_updates(size, 10);
+            b.iter(|| {
+                let mut block = PlasmaBlock::new(1, state_updates.clone(), vec![]);
+                block.merge_state_updates();
+                block.merkelize()
+            })
+        },
+        &[100, 1000],
+    );
+}
+
+criterion_group!(benches, merge_state_updates_benchmark);
+criterion_main!(benches);
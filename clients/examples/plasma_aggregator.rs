@@ -4,28 +4,43 @@ extern crate futures;
 use abi_utils::Decodable;
 use abi_utils::Encodable;
 use bincode::serialize;
+use contract_wrapper::plasma_contract_adaptor::PlasmaContractAdaptor;
+use ethabi::Contract as ContractABI;
 use ethereum_types::Address;
 use futures::{future, Async, Future, Poll, Stream};
+use plasma_clients::plasma::pubsub_auth::{AddressAuthenticator, AGGREGATOR_SERVER_ID};
 use plasma_clients::plasma::{
-    utils::string_to_address, Command, FetchBlockRequest, PlasmaAggregator,
+    utils::string_to_address, Command, ExclusionProofRequest, FetchBlockRequest,
+    InclusionProofRequest, PingRequest, PlasmaAggregator,
 };
 use plasma_core::data_structure::Transaction;
 use plasma_db::impls::kvs::CoreDbMemoryImpl;
-use pubsub_messaging::{spawn_server, Message, Sender, ServerHandler, WsMessage};
+use pubsub_messaging::{
+    spawn_server_with_auth, CloseCode, Message, Sender, ServerHandler, WsMessage,
+};
+use std::fs::File;
+use std::io::BufReader;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::timer::Interval;
 
 struct Handle {
     plasma_aggregator: Arc<Mutex<PlasmaAggregator<CoreDbMemoryImpl>>>,
+    /// Plasma contract whose pending exits are monitored. None if its ABI isn't found.
+    plasma_contract: Option<Arc<Mutex<PlasmaContractAdaptor>>>,
     interval: Interval,
     interval_sec: u64,
 }
 
 impl Handle {
-    fn new(plasma_aggregator: PlasmaAggregator<CoreDbMemoryImpl>, interval_sec: u64) -> Self {
+    fn new(
+        plasma_aggregator: PlasmaAggregator<CoreDbMemoryImpl>,
+        plasma_contract: Option<PlasmaContractAdaptor>,
+        interval_sec: u64,
+    ) -> Self {
         Self {
             plasma_aggregator: Arc::new(Mutex::new(plasma_aggregator)),
+            plasma_contract: plasma_contract.map(|c| Arc::new(Mutex::new(c))),
             interval: Interval::new_interval(Duration::from_secs(interval_sec)),
             interval_sec,
         }
@@ -36,6 +51,7 @@ impl Clone for Handle {
     fn clone(&self) -> Self {
         Self {
             plasma_aggregator: self.plasma_aggregator.clone(),
+            plasma_contract: self.plasma_contract.clone(),
             interval: Interval::new_interval(Duration::from_secs(self.interval_sec)),
             interval_sec: self.interval_sec,
         }
@@ -48,6 +64,11 @@ impl Stream for Handle {
     fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
         try_ready!(self.interval.poll().map_err(|_| ()));
         let mut agg = self.plasma_aggregator.lock().unwrap();
+        if let Some(plasma_contract) = &self.plasma_contract {
+            if let Err(e) = agg.update_pending_exits_on_chain(&plasma_contract.lock().unwrap()) {
+                println!("failed to get pending exits {:?}", e);
+            }
+        }
         if agg.submit_next_block().is_ok() {
             println!("succeeded to submit");
             Ok(Async::Ready(Some(())))
@@ -63,7 +84,13 @@ impl ServerHandler for Handle {
         let command = Command::from_abi(&msg.message).unwrap();
         if command.command_type.0 == 0 {
             let tx = Transaction::from_abi(&command.body).unwrap();
-            let ingest_result = agg.ingest_transaction(tx).unwrap();
+            let ingest_result = match agg.ingest_transaction(tx) {
+                Ok(ingest_result) => ingest_result,
+                Err(e) => {
+                    println!("rejected transaction: {}", e);
+                    return;
+                }
+            };
             let message = Message::new(
                 "BROADCAST".to_owned(),
                 Command::create_new_tx_event(ingest_result)
@@ -85,6 +112,54 @@ impl ServerHandler for Handle {
                 let msg = WsMessage::Binary(serialize(&message).unwrap());
                 let _ = sender.broadcast(msg);
             }
+        } else if command.command_type.0 == 5 {
+            let ping_request = PingRequest::from_abi(&command.body).unwrap();
+            let commitment_contract_address = agg.get_commitment_contract_address();
+            if ping_request.commitment_contract_address != commitment_contract_address {
+                let _ = sender.close(CloseCode::Policy);
+            } else {
+                let message = Message::new(
+                    "BROADCAST".to_owned(),
+                    Command::create_ping_response(commitment_contract_address)
+                        .to_abi()
+                        .to_vec(),
+                );
+                let msg = WsMessage::Binary(serialize(&message).unwrap());
+                let _ = sender.send(msg);
+            }
+        } else if command.command_type.0 == 8 {
+            let pong = agg.handle_latency_ping(&command.body).unwrap();
+            let message = Message::new("BROADCAST".to_owned(), pong.to_abi().to_vec());
+            let msg = WsMessage::Binary(serialize(&message).unwrap());
+            let _ = sender.send(msg);
+        } else if command.command_type.0 == 6 {
+            let request = InclusionProofRequest::from_abi(&command.body).unwrap();
+            if let Ok(response) = agg.get_inclusion_proof(request.block_number, request.range) {
+                let message = Message::new(
+                    "BROADCAST".to_owned(),
+                    Command::create_inclusion_proof_response(response)
+                        .to_abi()
+                        .to_vec(),
+                );
+                let msg = WsMessage::Binary(serialize(&message).unwrap());
+                let _ = sender.send(msg);
+            }
+        } else if command.command_type.0 == 10 {
+            let request = ExclusionProofRequest::from_abi(&command.body).unwrap();
+            if let Ok(response) = agg.get_exclusion_proof(
+                request.block_number,
+                request.deposit_contract_address,
+                request.range,
+            ) {
+                let message = Message::new(
+                    "BROADCAST".to_owned(),
+                    Command::create_exclusion_proof_response(response)
+                        .to_abi()
+                        .to_vec(),
+                );
+                let msg = WsMessage::Binary(serialize(&message).unwrap());
+                let _ = sender.send(msg);
+            }
         } else {
             println!("undefined command type {:?}", command.command_type.0);
         }
@@ -101,7 +176,18 @@ fn main() {
     aggregator.insert_test_ranges();
 
     let interval_second = 5;
-    let handle = Handle::new(aggregator, interval_second);
+    let plasma_contract = File::open("PlasmaContract.json")
+        .ok()
+        .and_then(|f| ContractABI::load(BufReader::new(f)).ok())
+        .and_then(|abi| {
+            PlasmaContractAdaptor::new(
+                "http://127.0.0.1:9545",
+                "0000000000000000000000000000000000000000",
+                abi,
+            )
+            .ok()
+        });
+    let handle = Handle::new(aggregator, plasma_contract, interval_second);
 
     handle
         .plasma_aggregator
@@ -111,7 +197,13 @@ fn main() {
     let h = handle.clone();
     tokio::run(future::lazy(move || {
         tokio::spawn(Worker { handle: h });
-        if let Ok(server) = spawn_server("127.0.0.1:8080".to_owned(), handle.clone()) {
+        // clients must sign the challenge with their ethereum key
+        if let Ok(server) = spawn_server_with_auth(
+            "127.0.0.1:8080".to_owned(),
+            AGGREGATOR_SERVER_ID.to_owned(),
+            handle.clone(),
+            AddressAuthenticator::default(),
+        ) {
             let _ = server.handle.join();
         }
         Ok(())
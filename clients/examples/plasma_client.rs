@@ -6,16 +6,19 @@ fn main() {
     let mut shell = PlasmaClientShell::new(
         "127.0.0.1:8080".to_string(),
         string_to_address("9FBDa871d559710256a2502A2517b794B482Db40"),
+        string_to_address("627306090abab3a6e1400e9345bc60c78a8bef57"),
     );
     tokio::run(future::lazy(move || {
-        shell.connect();
+        shell.connect().unwrap();
         println!("{:?}", shell.get_balance(&Bytes::from("")));
         let session = &Bytes::from("");
         let (property, metadata) = shell.ownership_property(
             session,
             string_to_address("2932b7a2355d6fecc4b5c0b6bd44cc31df247a2e"),
         );
-        shell.send_transaction(session, None, 0, 10, property, metadata);
+        if let Err(e) = shell.send_transaction(session, None, 0, 10, property, metadata) {
+            println!("failed to send transaction: {:?}", e);
+        }
         Ok(())
     }));
 }
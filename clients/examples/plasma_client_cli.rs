@@ -80,13 +80,14 @@ fn main() {
     let mut shell = PlasmaClientShell::new(
         "127.0.0.1:8080".to_string(),
         string_to_address("9FBDa871d559710256a2502A2517b794B482Db40"),
+        string_to_address("627306090abab3a6e1400e9345bc60c78a8bef57"),
     );
 
     if matches.subcommand_matches("balance").is_some() {
         let eth_address = Address::zero();
         let dai_address = string_to_address("0000000000000000000000000000000000000001");
         tokio::run(future::lazy(move || {
-            shell.connect();
+            shell.connect().unwrap();
             println!(
                 "Balance\n\t{:?} ETH\n\t{:?} DAI",
                 shell
@@ -102,14 +103,14 @@ fn main() {
         }));
     } else if matches.subcommand_matches("init").is_some() {
         tokio::run(future::lazy(move || {
-            shell.connect();
+            shell.connect().unwrap();
             shell.initialize();
             Ok(())
         }));
     } else if let Some(matches) = matches.subcommand_matches("import") {
         let secret_key = value_t!(matches, "secret_key", String).unwrap();
         tokio::run(future::lazy(move || {
-            shell.connect();
+            shell.connect().unwrap();
             let (session, _key) = shell.import_account(&secret_key);
             println!("session: {}", hex::encode(session.to_vec()));
             Ok(())
@@ -126,11 +127,14 @@ fn main() {
             start, end, token_address_opt, to_address
         );
         tokio::run(future::lazy(move || {
-            shell.connect();
+            shell.connect().unwrap();
             let session = &decode_session(session_str).unwrap();
             let (property, metadata) = shell.ownership_property(session, to_address);
-            shell.send_transaction(session, token_address_opt, start, end, property, metadata);
-            println!("Sent!!!");
+            match shell.send_transaction(session, token_address_opt, start, end, property, metadata)
+            {
+                Ok(()) => println!("Sent!!!"),
+                Err(e) => println!("failed to send transaction: {:?}", e),
+            }
             Ok(())
         }));
     }
@@ -143,14 +143,16 @@ fn send_payment(
         println!("Range: {:?}", range);
         let session = decode_session(body.session.clone()).unwrap();
         let (property, metadata) = plasma_client.ownership_property(&session, body.to);
-        plasma_client.send_transaction(
-            &session,
-            Some(body.token_address),
-            range.get_start(),
-            range.get_start() + body.amount,
-            property,
-            metadata,
-        );
+        plasma_client
+            .send_transaction(
+                &session,
+                Some(body.token_address),
+                range.get_start(),
+                range.get_start() + body.amount,
+                property,
+                metadata,
+            )
+            .map_err(error::ErrorBadRequest)?;
         return Ok(HttpResponse::Ok().json(SendPayment {
             token_address: body.token_address,
             from: body.from,
@@ -291,8 +293,9 @@ pub fn main() {
         let mut client = PlasmaClientShell::new(
             "127.0.0.1:8080".to_owned(),
             string_to_address("9FBDa871d559710256a2502A2517b794B482Db40"),
+            string_to_address("627306090abab3a6e1400e9345bc60c78a8bef57"),
         );
-        client.connect();
+        client.connect().unwrap();
 
         let data = web::Data::new(client);
         App::new()
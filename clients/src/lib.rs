@@ -1,3 +1,7 @@
+#[cfg(feature = "metrics")]
+#[macro_use]
+extern crate lazy_static;
+
 pub mod plasma;
 pub mod state_channel;
 
@@ -1,10 +1,14 @@
 pub mod block_db;
 pub mod block_manager;
+#[cfg(feature = "metrics")]
+pub mod block_manager_metrics;
 pub mod command;
 pub mod error;
+pub mod exit_db;
 pub mod plasma_aggregator;
 pub mod plasma_block;
 pub mod plasma_client;
+pub mod pubsub_auth;
 pub mod state_db;
 pub mod state_manager;
 pub mod token;
@@ -12,6 +16,9 @@ pub mod utils;
 pub mod wallet_db;
 pub mod wallet_manager;
 
-pub use command::{Command, FetchBlockRequest, NewTransactionEvent};
+pub use command::{
+    Command, ExclusionProofRequest, FetchBlockRequest, InclusionProofRequest,
+    InclusionProofResponse, LatencyPing, NewTransactionEvent, PingRequest,
+};
 pub use plasma_aggregator::PlasmaAggregator;
-pub use plasma_client::{PlasmaClient, PlasmaClientController, PlasmaClientShell};
+pub use plasma_client::{HealthStatus, PlasmaClient, PlasmaClientController, PlasmaClientShell};
@@ -3,9 +3,11 @@ use super::error::{Error, ErrorKind};
 use super::plasma_block::PlasmaBlock;
 use abi_utils::{Decodable, Encodable};
 use bytes::Bytes;
+use ethereum_types::Address;
 use ovm::types::{Integer, StateUpdate};
+use plasma_core::data_structure::Nonce;
 use plasma_db::{
-    traits::{kvs::KeyValueStore, rangestore::RangeStore},
+    traits::{db::DatabaseTrait, kvs::KeyValueStore, rangestore::RangeStore},
     RangeDbImpl,
 };
 
@@ -16,7 +18,7 @@ pub struct BlockDb<'a, KVS: KeyValueStore> {
     db: &'a RangeDbImpl<KVS>,
 }
 
-impl<'a, KVS: KeyValueStore> BlockDb<'a, KVS> {
+impl<'a, KVS: KeyValueStore + DatabaseTrait> BlockDb<'a, KVS> {
     pub fn from(range_db: &'a RangeDbImpl<KVS>) -> Self {
         BlockDb { db: range_db }
     }
@@ -50,8 +52,7 @@ impl<'a, KVS: KeyValueStore> BlockDb<'a, KVS> {
     pub fn get_pending_state_updates(&self) -> Result<Vec<StateUpdate>, Error> {
         let res = self
             .db
-            .bucket(&Bytes::from(&"queued_state_updates"[..]))
-            .get(MIN_RANGE, MAX_RANGE)?
+            .get_all_in_bucket(b"queued_state_updates")?
             .iter()
             .map(|range| StateUpdate::from_abi(range.get_value()).unwrap())
             .collect();
@@ -59,10 +60,34 @@ impl<'a, KVS: KeyValueStore> BlockDb<'a, KVS> {
     }
 
     pub fn delete_all_queued_state_updates(&self) -> Result<(), Error> {
-        let _ = self
-            .db
-            .bucket(&Bytes::from(&"queued_state_updates"[..]))
-            .del_batch(MIN_RANGE, MAX_RANGE)?;
+        self.db.get_db().transaction(|_| {
+            let _ = self
+                .db
+                .bucket(&Bytes::from(&"queued_state_updates"[..]))
+                .del_batch(MIN_RANGE, MAX_RANGE)?;
+            Ok(())
+        })
+    }
+
+    /// Deletes queued state updates contained in `state_updates`, which may be merged ones.
+    /// Queued state updates which only overlap with them are kept.
+    pub fn delete_queued_state_updates(&self, state_updates: &[StateUpdate]) -> Result<(), Error> {
+        let bucket = self.db.bucket(&Bytes::from(&"queued_state_updates"[..]));
+        for state_update in state_updates.iter() {
+            let range = state_update.get_range();
+            for queued in bucket.get(range.get_start(), range.get_end())?.iter() {
+                let queued_state_update = StateUpdate::from_abi(queued.get_value())?;
+                if queued_state_update.get_deposit_contract_address()
+                    == state_update.get_deposit_contract_address()
+                    && queued_state_update.get_block_number() == state_update.get_block_number()
+                    && queued_state_update.get_property() == state_update.get_property()
+                    && range.is_subrange(&queued_state_update.get_range())
+                {
+                    // queued ranges don't overlap each other, so only this one is deleted
+                    bucket.del_batch(queued.get_start(), queued.get_end())?;
+                }
+            }
+        }
         Ok(())
     }
 
@@ -79,8 +104,7 @@ impl<'a, KVS: KeyValueStore> BlockDb<'a, KVS> {
     pub fn get_pending_txs(&self) -> Result<Vec<NewTransactionEvent>, Error> {
         let res = self
             .db
-            .bucket(&Bytes::from(&"queued_txs"[..]))
-            .get(MIN_RANGE, MAX_RANGE)?
+            .get_all_in_bucket(b"queued_txs")?
             .iter()
             .map(|range| NewTransactionEvent::from_abi(range.get_value()).unwrap())
             .collect();
@@ -95,6 +119,22 @@ impl<'a, KVS: KeyValueStore> BlockDb<'a, KVS> {
         Ok(())
     }
 
+    /// Deletes queued transactions equal to `txs`.
+    /// Queued transactions which only overlap with them are kept.
+    pub fn delete_queued_txs(&self, txs: &[NewTransactionEvent]) -> Result<(), Error> {
+        let bucket = self.db.bucket(&Bytes::from(&"queued_txs"[..]));
+        for tx in txs.iter() {
+            let range = tx.transaction.get_range();
+            let encoded = tx.to_abi();
+            for queued in bucket.get(range.get_start(), range.get_end())?.iter() {
+                if queued.get_value() == encoded.as_slice() {
+                    bucket.del_batch(queued.get_start(), queued.get_end())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub fn get_block(&self, block_number: Integer) -> Result<PlasmaBlock, Error> {
         let plasma_block_opt = self
             .db
@@ -103,21 +143,180 @@ impl<'a, KVS: KeyValueStore> BlockDb<'a, KVS> {
             .bucket(&Bytes::from("blocks").into())
             .get(&block_number.0.into())
             .map_err::<Error, _>(Into::into)?;
-        if let Some(plasma_block) = plasma_block_opt {
-            PlasmaBlock::from_abi(&plasma_block).map_err(Into::into)
+        let block = if let Some(plasma_block) = plasma_block_opt {
+            PlasmaBlock::from_abi(&plasma_block)?
         } else {
-            Err(Error::from(ErrorKind::PlasmaDbError))
+            return Err(Error::from(ErrorKind::PlasmaDbError));
+        };
+        self.verify_checksums(&block)?;
+        Ok(block)
+    }
+
+    /// Compares checksums of state updates in `block` with the stored ones.
+    /// Blocks saved without checksums aren't verified.
+    fn verify_checksums(&self, block: &PlasmaBlock) -> Result<(), Error> {
+        let checksums_opt = self
+            .db
+            .get_db()
+            .bucket(&Bytes::from("plasma_block_db").into())
+            .bucket(&Bytes::from("checksums").into())
+            .get(&block.get_block_number().into())
+            .map_err::<Error, _>(Into::into)?;
+        if let Some(checksums) = checksums_opt {
+            let state_updates = block.get_state_updates();
+            if checksums.len() != state_updates.len() * 4
+                || checksums
+                    .chunks(4)
+                    .zip(state_updates.iter())
+                    .any(|(checksum, s)| checksum != s.checksum().to_be_bytes())
+            {
+                return Err(Error::from(ErrorKind::StorageIntegrityError));
+            }
         }
+        Ok(())
     }
 
-    pub fn save_block(&self, block: &PlasmaBlock) -> Result<(), Error> {
-        let index = block.get_block_number();
-        self.db
+    /// Returns all stored block numbers in ascending order.
+    /// Block numbers are stored as big-endian keys, so prefix scan returns them sorted.
+    pub fn get_block_numbers(&self) -> Result<Vec<u64>, Error> {
+        let block_numbers = self
+            .db
             .get_db()
             .bucket(&Bytes::from("plasma_block_db").into())
             .bucket(&Bytes::from("blocks").into())
-            .put(&index.into(), &block.to_abi())?;
-        Ok(())
+            .iter_all(&Bytes::new().into(), Box::new(|_k, _v| true))
+            .iter()
+            .filter(|kv| kv.get_key().as_bytes().len() == 8)
+            .map(|kv| {
+                let mut buf = [0u8; 8];
+                buf.copy_from_slice(kv.get_key().as_bytes());
+                u64::from_be_bytes(buf)
+            })
+            .collect();
+        Ok(block_numbers)
+    }
+
+    /// Returns `(block_number, tx_count)` of stored blocks from `from_block` to `to_block` inclusive.
+    /// Only the outer ABI of blocks is decoded, so transactions aren't deserialized.
+    pub fn get_transaction_count_per_block(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<(u64, usize)>, Error> {
+        let blocks = self
+            .db
+            .get_db()
+            .bucket(&Bytes::from("plasma_block_db").into())
+            .bucket(&Bytes::from("blocks").into());
+        let mut counts = vec![];
+        for block_number in self.get_block_numbers()? {
+            if block_number < from_block || block_number > to_block {
+                continue;
+            }
+            let encoded = blocks
+                .get(&block_number.into())
+                .map_err::<Error, _>(Into::into)?
+                .ok_or_else(|| Error::from(ErrorKind::PlasmaDbError))?;
+            let tuple = ethabi::decode(&PlasmaBlock::get_param_types(), &encoded)?;
+            let tx_count = tuple[2]
+                .clone()
+                .to_array()
+                .ok_or_else(|| Error::from(ErrorKind::AbiDecode))?
+                .len();
+            counts.push((block_number, tx_count));
+        }
+        Ok(counts)
+    }
+
+    /// Records that `block_number` was finalized at ethereum block `finalized_at`.
+    pub fn mark_block_finalized(&self, block_number: u64, finalized_at: u64) -> Result<(), Error> {
+        self.db
+            .get_db()
+            .bucket(&Bytes::from("plasma_block_db").into())
+            .bucket(&Bytes::from("finalized_blocks").into())
+            .put(&block_number.into(), &finalized_at.to_be_bytes())
+            .map_err::<Error, _>(Into::into)
+    }
+
+    /// Returns `(plasma_block_number, finalized_at_eth_block)` pairs sorted by plasma block number.
+    pub fn get_finalized_blocks(&self) -> Result<Vec<(u64, u64)>, Error> {
+        let finalized_blocks = self
+            .db
+            .get_db()
+            .bucket(&Bytes::from("plasma_block_db").into())
+            .bucket(&Bytes::from("finalized_blocks").into())
+            .iter_all(&Bytes::new().into(), Box::new(|_k, _v| true))
+            .iter()
+            .filter(|kv| kv.get_key().as_bytes().len() == 8 && kv.get_value().len() == 8)
+            .map(|kv| {
+                let mut block_number = [0u8; 8];
+                block_number.copy_from_slice(kv.get_key().as_bytes());
+                let mut finalized_at = [0u8; 8];
+                finalized_at.copy_from_slice(kv.get_value());
+                (
+                    u64::from_be_bytes(block_number),
+                    u64::from_be_bytes(finalized_at),
+                )
+            })
+            .collect();
+        Ok(finalized_blocks)
+    }
+
+    /// Returns the last nonce applied for `sender`.
+    pub fn get_last_nonce(&self, sender: Address) -> Result<Option<Nonce>, Error> {
+        let nonce_opt = self
+            .db
+            .get_db()
+            .bucket(&Bytes::from("plasma_block_db").into())
+            .bucket(&Bytes::from("nonces").into())
+            .get(&sender.as_bytes().into())
+            .map_err::<Error, _>(Into::into)?;
+        match nonce_opt {
+            Some(nonce) if nonce.len() == 8 => {
+                let mut buf = [0u8; 8];
+                buf.copy_from_slice(&nonce);
+                Ok(Some(Nonce(u64::from_be_bytes(buf))))
+            }
+            Some(_) => Err(Error::from(ErrorKind::StorageIntegrityError)),
+            None => Ok(None),
+        }
+    }
+
+    /// Stores `nonce` as the last nonce applied for `sender`.
+    pub fn set_last_nonce(&self, sender: Address, nonce: Nonce) -> Result<(), Error> {
+        self.db
+            .get_db()
+            .bucket(&Bytes::from("plasma_block_db").into())
+            .bucket(&Bytes::from("nonces").into())
+            .put(&sender.as_bytes().into(), &nonce.0.to_be_bytes())
+            .map_err::<Error, _>(Into::into)
+    }
+
+    /// Saves `block` and deletes its state updates and transactions from queue atomically.
+    pub fn commit_block(&self, block: &PlasmaBlock) -> Result<(), Error> {
+        self.db.get_db().transaction(|_| {
+            self.save_block(block)?;
+            self.delete_queued_state_updates(block.get_state_updates())?;
+            self.delete_queued_txs(block.get_transactions())
+        })
+    }
+
+    pub fn save_block(&self, block: &PlasmaBlock) -> Result<(), Error> {
+        let index = block.get_block_number();
+        self.db.get_db().transaction(|db| {
+            db.bucket(&Bytes::from("plasma_block_db").into())
+                .bucket(&Bytes::from("blocks").into())
+                .put(&index.into(), &block.to_abi())?;
+            let checksums: Vec<u8> = block
+                .get_state_updates()
+                .iter()
+                .flat_map(|s| s.checksum().to_be_bytes().to_vec())
+                .collect();
+            db.bucket(&Bytes::from("plasma_block_db").into())
+                .bucket(&Bytes::from("checksums").into())
+                .put(&index.into(), &checksums)?;
+            Ok(())
+        })
     }
 }
 
@@ -165,6 +364,129 @@ mod tests {
         assert_eq!(block.get_state_updates().len(), 1);
     }
 
+    #[test]
+    fn test_commit_block() {
+        let db = CoreDbMemoryImpl::open("test");
+        let range_db = RangeDbImpl::from(db);
+        let block_db = BlockDb::from(&range_db);
+        let state_update = StateUpdate::new(
+            Integer::new(1),
+            Address::zero(),
+            Range::new(0, 5),
+            Property::new(Address::zero(), vec![]),
+        );
+        let tx = NewTransactionEvent::new(
+            vec![Integer::new(0)],
+            Transaction::new(
+                Address::zero(),
+                Range::new(0, 5),
+                Bytes::default(),
+                Bytes::default(),
+                Metadata::default(),
+            ),
+        );
+        assert!(block_db.enqueue_state_update(&state_update).is_ok());
+        assert!(block_db.enqueue_tx(tx.clone()).is_ok());
+        let plasma_block = PlasmaBlock::new(1, vec![state_update], vec![tx]);
+        assert!(block_db.commit_block(&plasma_block).is_ok());
+        assert!(block_db.get_block(Integer::new(1)).is_ok());
+        assert!(block_db.get_pending_state_updates().unwrap().is_empty());
+        assert!(block_db.get_pending_txs().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_get_block_with_corrupted_state_update() {
+        let db = CoreDbMemoryImpl::open("test");
+        let range_db = RangeDbImpl::from(db);
+        let block_db = BlockDb::from(&range_db);
+        let create_block = |end: u64| {
+            PlasmaBlock::new(
+                1,
+                vec![StateUpdate::new(
+                    Integer::new(1),
+                    Address::zero(),
+                    Range::new(0, end),
+                    Property::new(Address::zero(), vec![]),
+                )],
+                vec![],
+            )
+        };
+        assert!(block_db.save_block(&create_block(5)).is_ok());
+        assert!(block_db.get_block(Integer::new(1)).is_ok());
+        assert!(range_db
+            .get_db()
+            .bucket(&Bytes::from("plasma_block_db").into())
+            .bucket(&Bytes::from("blocks").into())
+            .put(&1u64.into(), &create_block(6).to_abi())
+            .is_ok());
+        match block_db.get_block(Integer::new(1)) {
+            Err(e) => match e.kind() {
+                ErrorKind::StorageIntegrityError => {}
+                kind => panic!("unexpected error {:?}", kind),
+            },
+            Ok(_) => panic!("corrupted block must not be loaded"),
+        }
+    }
+
+    #[test]
+    fn test_get_block_numbers() {
+        let db = CoreDbMemoryImpl::open("test");
+        let range_db = RangeDbImpl::from(db);
+        let block_db = BlockDb::from(&range_db);
+        for block_number in [3, 1, 256].iter() {
+            let plasma_block = PlasmaBlock::new(*block_number, vec![], vec![]);
+            assert!(block_db.save_block(&plasma_block).is_ok());
+        }
+        assert_eq!(block_db.get_block_numbers().unwrap(), vec![1, 3, 256]);
+    }
+
+    #[test]
+    fn test_get_transaction_count_per_block() {
+        let db = CoreDbMemoryImpl::open("test");
+        let range_db = RangeDbImpl::from(db);
+        let block_db = BlockDb::from(&range_db);
+        let tx = NewTransactionEvent::new(
+            vec![Integer::new(0)],
+            Transaction::new(
+                Address::zero(),
+                Range::new(0, 5),
+                Bytes::default(),
+                Bytes::default(),
+                Metadata::default(),
+            ),
+        );
+        for block_number in 1..4 {
+            let plasma_block = PlasmaBlock::new(
+                block_number,
+                vec![],
+                vec![tx.clone(); block_number as usize],
+            );
+            assert!(block_db.save_block(&plasma_block).is_ok());
+        }
+        assert_eq!(
+            block_db.get_transaction_count_per_block(2, 10).unwrap(),
+            vec![(2, 2), (3, 3)]
+        );
+        assert!(block_db
+            .get_transaction_count_per_block(4, 10)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_finalized_blocks() {
+        let db = CoreDbMemoryImpl::open("test");
+        let range_db = RangeDbImpl::from(db);
+        let block_db = BlockDb::from(&range_db);
+        assert!(block_db.get_finalized_blocks().unwrap().is_empty());
+        assert!(block_db.mark_block_finalized(2, 120).is_ok());
+        assert!(block_db.mark_block_finalized(1, 100).is_ok());
+        assert_eq!(
+            block_db.get_finalized_blocks().unwrap(),
+            vec![(1, 100), (2, 120)]
+        );
+    }
+
     #[test]
     fn test_abi_plasma_block() {
         let plasma_block = PlasmaBlock::new(
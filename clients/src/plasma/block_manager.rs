@@ -1,22 +1,57 @@
 use super::block_db::BlockDb;
+#[cfg(feature = "metrics")]
+use super::block_manager_metrics::BlockManagerMetrics;
 use super::command::NewTransactionEvent;
-use super::error::Error;
+use super::error::{Error, ErrorKind, StateUpdateError};
 use super::plasma_block::PlasmaBlock;
-use contract_wrapper::commitment_contract_adaptor::CommitmentContractAdaptor;
+use abi_utils::{Decodable, Encodable};
+use bytes::Bytes;
+use contract_wrapper::commitment_contract_adaptor::{
+    CommitmentContractAdaptor, DEFAULT_CONFIRMATION_TIMEOUT,
+};
 use ethabi::Contract as ContractABI;
-use ethereum_types::Address;
-use ovm::types::{Integer, StateUpdate};
+use ethereum_types::{Address, H256};
+use ovm::deciders::SignVerifier;
+use ovm::types::{Integer, Property, StateUpdate};
+use plasma_core::data_structure::{Nonce, Range, Transaction};
 use plasma_db::traits::db::DatabaseTrait;
 use plasma_db::traits::kvs::KeyValueStore;
-use plasma_db::RangeDbImpl;
+use plasma_db::{DbDump, DbStats, RangeDbImpl};
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::BufReader;
 
+/// Number of confirmations to wait for on testnet.
+pub const TESTNET_CONFIRMATION_THRESHOLD: u32 = 1;
+/// Number of confirmations to wait for on mainnet.
+pub const MAINNET_CONFIRMATION_THRESHOLD: u32 = 12;
+
+/// Statistics of BlockManager for monitoring.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockManagerStats {
+    pub current_block_number: u64,
+    pub queued_state_updates: usize,
+    pub db: DbStats,
+    /// Number of pending exits on chain at the last `set_pending_exits_on_chain`.
+    pub pending_exits_on_chain: u64,
+}
+
 pub struct BlockManager<KVS: KeyValueStore> {
     db: RangeDbImpl<KVS>,
     commitment_contract_address: Address,
     aggregator_address: Address,
     current_block_number: u64,
+    /// Number of confirmations of block submission before the block is saved.
+    confirmation_threshold: u32,
+    /// Total deposited range of each deposit contract.
+    total_ranges: HashMap<Address, Range>,
+    /// Maximum number of state updates included in a block.
+    max_state_updates_per_block: usize,
+    /// Maximum sum of ABI encoded size of state updates included in a block.
+    max_block_size_bytes: usize,
+    pending_exits_on_chain: u64,
+    #[cfg(feature = "metrics")]
+    metrics: BlockManagerMetrics,
 }
 
 impl<KVS: KeyValueStore + DatabaseTrait> BlockManager<KVS> {
@@ -24,12 +59,85 @@ impl<KVS: KeyValueStore + DatabaseTrait> BlockManager<KVS> {
         let db = KVS::open("plasma_aggregator_db");
         let db = RangeDbImpl::from(db);
 
-        BlockManager {
+        let block_manager = BlockManager {
             aggregator_address,
             commitment_contract_address,
             db,
             current_block_number: 1,
+            confirmation_threshold: TESTNET_CONFIRMATION_THRESHOLD,
+            total_ranges: HashMap::new(),
+            max_state_updates_per_block: std::usize::MAX,
+            max_block_size_bytes: std::usize::MAX,
+            pending_exits_on_chain: 0,
+            #[cfg(feature = "metrics")]
+            metrics: BlockManagerMetrics::global(),
+        };
+        // transactions can be left queued if the aggregator crashed before submitting a block
+        match block_manager.get_orphaned_transactions() {
+            Ok(orphaned) if !orphaned.is_empty() => {
+                println!("{} orphaned transactions are queued", orphaned.len())
+            }
+            Ok(_) => {}
+            Err(e) => println!("failed to check orphaned transactions: {}", e),
         }
+        block_manager
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics(
+        aggregator_address: Address,
+        commitment_contract_address: Address,
+        metrics: BlockManagerMetrics,
+    ) -> Self {
+        let mut block_manager = Self::new(aggregator_address, commitment_contract_address);
+        block_manager.metrics = metrics;
+        block_manager
+    }
+
+    pub fn set_confirmation_threshold(&mut self, confirmation_threshold: u32) {
+        self.confirmation_threshold = confirmation_threshold;
+    }
+
+    pub fn get_confirmation_threshold(&self) -> u32 {
+        self.confirmation_threshold
+    }
+
+    pub fn set_max_state_updates_per_block(&mut self, max_state_updates_per_block: usize) {
+        self.max_state_updates_per_block = max_state_updates_per_block;
+    }
+
+    pub fn set_max_block_size_bytes(&mut self, max_block_size_bytes: usize) {
+        self.max_block_size_bytes = max_block_size_bytes;
+    }
+
+    /// Takes state updates from the head while both limits are kept.
+    /// The first state update is always taken so that a large one doesn't block the queue.
+    fn take_within_limit(&self, state_updates: Vec<StateUpdate>) -> Vec<StateUpdate> {
+        let mut size = 0;
+        let mut taken = vec![];
+        for state_update in state_updates.into_iter() {
+            size += state_update.to_abi().len();
+            if !taken.is_empty()
+                && (taken.len() >= self.max_state_updates_per_block
+                    || size > self.max_block_size_bytes)
+            {
+                break;
+            }
+            taken.push(state_update);
+        }
+        taken
+    }
+
+    /// Returns true if some pending state updates don't fit in the next block.
+    pub fn has_pending_updates_after_limit(&self) -> bool {
+        let pending = self.get_queued_state_updates();
+        let pending_count = pending.len();
+        self.take_within_limit(pending).len() < pending_count
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn get_metrics(&self) -> &BlockManagerMetrics {
+        &self.metrics
     }
 
     pub fn get_queued_state_updates(&self) -> Vec<StateUpdate> {
@@ -44,21 +152,206 @@ impl<KVS: KeyValueStore + DatabaseTrait> BlockManager<KVS> {
             .map_err::<Error, _>(Into::into)
     }
 
+    /// Sets total deposited range of `deposit_contract_address` used by `apply_state_update`.
+    pub fn set_total_range(&mut self, deposit_contract_address: Address, total_range: Range) {
+        self.total_ranges
+            .insert(deposit_contract_address, total_range);
+    }
+
+    /// Extends total deposited range of `deposit_contract_address` to cover `range`.
+    pub fn add_deposited_range(&mut self, deposit_contract_address: Address, range: Range) {
+        let total_range = match self.total_ranges.get(&deposit_contract_address) {
+            Some(total_range) => Range::new(
+                total_range.get_start().min(range.get_start()),
+                total_range.get_end().max(range.get_end()),
+            ),
+            None => range,
+        };
+        self.set_total_range(deposit_contract_address, total_range);
+    }
+
+    /// Returns the last nonce applied for `sender`.
+    pub fn get_last_nonce(&self, sender: Address) -> Result<Option<Nonce>, Error> {
+        BlockDb::from(&self.db).get_last_nonce(sender)
+    }
+
+    /// Verifies that `tx` is signed by the sender in its metadata and its nonce follows
+    /// the last nonce of the sender. The first nonce is 0.
+    pub fn verify_nonce(&self, tx: &Transaction) -> Result<(), StateUpdateError> {
+        let sender = tx.get_metadata().get_from();
+        let body = Bytes::from(tx.to_body_abi());
+        if !SignVerifier::verify(&body, tx.get_signature(), sender) {
+            return Err(StateUpdateError::InvalidSignature);
+        }
+        let expected = self
+            .get_last_nonce(sender)
+            .map_err(|_| StateUpdateError::PlasmaDbError)?
+            .map_or_else(Nonce::default, Nonce::next);
+        if tx.get_nonce() != expected {
+            return Err(StateUpdateError::InvalidNonce {
+                expected,
+                got: tx.get_nonce(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Stores nonce of `tx` as the last nonce of its sender.
+    pub fn set_last_nonce(&self, tx: &Transaction) -> Result<(), Error> {
+        BlockDb::from(&self.db).set_last_nonce(tx.get_metadata().get_from(), tx.get_nonce())
+    }
+
+    /// Validates state update made by `tx` and enqueues it.
+    /// The range and deposit contract must be the ones of `tx`.
+    /// The range must be non-empty and deposited, the block number must be current block number
+    /// and no pending state update may overlap with it.
+    /// `tx` must pass `verify_nonce`.
+    pub fn apply_state_update(
+        &self,
+        update: StateUpdate,
+        tx: &Transaction,
+    ) -> Result<(), StateUpdateError> {
+        let range = update.get_range();
+        if range != tx.get_range()
+            || update.get_deposit_contract_address() != tx.get_deposit_contract_address()
+        {
+            return Err(StateUpdateError::TransactionMismatch);
+        }
+        if range.is_empty() {
+            return Err(StateUpdateError::EmptyRange);
+        }
+        match self
+            .total_ranges
+            .get(&update.get_deposit_contract_address())
+        {
+            Some(total_range) if total_range.is_subrange(&range) => {}
+            _ => return Err(StateUpdateError::OutOfDepositRange),
+        }
+        if update.get_block_number() != Integer::new(self.current_block_number) {
+            return Err(StateUpdateError::InvalidBlockNumber);
+        }
+        self.verify_nonce(tx)?;
+        let block_db = BlockDb::from(&self.db);
+        let pending = block_db
+            .get_queued_state_updates(range.get_start(), range.get_end())
+            .map_err(|_| StateUpdateError::PlasmaDbError)?;
+        if !pending.is_empty() {
+            return Err(StateUpdateError::ConflictingUpdate);
+        }
+        block_db
+            .enqueue_state_update(&update)
+            .map_err(|_| StateUpdateError::PlasmaDbError)?;
+        self.set_last_nonce(tx)
+            .map_err(|_| StateUpdateError::PlasmaDbError)
+    }
+
     pub fn enqueue_tx(&self, tx: NewTransactionEvent) -> Result<(), Error> {
         let block_db = BlockDb::from(&self.db);
-        block_db.enqueue_tx(tx).map_err::<Error, _>(Into::into)
+        block_db.enqueue_tx(tx).map_err::<Error, _>(Into::into)?;
+        #[cfg(feature = "metrics")]
+        self.metrics.pending_transactions.inc();
+        Ok(())
+    }
+
+    /// Applies state updates made by transactions of a batch and enqueues the transactions
+    /// all together. `state_updates[i]` must be made by `txs[i]`.
+    /// Rolls back the queue and nonces if any state update fails `apply_state_update`
+    /// or writing fails.
+    pub fn enqueue_batch(
+        &self,
+        state_updates: &[StateUpdate],
+        txs: &[NewTransactionEvent],
+    ) -> Result<(), Error> {
+        if state_updates.len() != txs.len() {
+            return Err(Error::from(ErrorKind::InvalidParameter));
+        }
+        let block_db = BlockDb::from(&self.db);
+        // state updates overlapping each other and non-consecutive nonces of the same sender
+        // are rejected because the earlier ones are already applied
+        self.db.get_db().transaction(|_| {
+            state_updates
+                .iter()
+                .zip(txs.iter())
+                .try_for_each(|(state_update, tx)| {
+                    self.apply_state_update(state_update.clone(), &tx.transaction)?;
+                    block_db.enqueue_tx(tx.clone())
+                })
+        })?;
+        #[cfg(feature = "metrics")]
+        self.metrics.pending_transactions.add(txs.len() as i64);
+        Ok(())
+    }
+
+    /// Returns queued transactions which aren't included in any stored block
+    /// and never will be because no queued state update covers them.
+    pub fn get_orphaned_transactions(&self) -> Result<Vec<Transaction>, Error> {
+        let block_db = BlockDb::from(&self.db);
+        let mut included: HashSet<Vec<u8>> = HashSet::new();
+        for block_number in block_db.get_block_numbers()? {
+            let block = block_db.get_block(Integer::new(block_number))?;
+            included.extend(
+                block
+                    .get_transactions()
+                    .iter()
+                    .map(|tx| tx.transaction.to_abi()),
+            );
+        }
+        let state_updates = block_db.get_pending_state_updates()?;
+        Ok(block_db
+            .get_pending_txs()?
+            .into_iter()
+            .map(|tx| tx.transaction)
+            .filter(|tx| {
+                !included.contains(&tx.to_abi())
+                    && !state_updates.iter().any(|s| {
+                        s.get_deposit_contract_address() == tx.get_deposit_contract_address()
+                            && s.get_range().is_subrange(&tx.get_range())
+                    })
+            })
+            .collect())
     }
 
-    /// generate block from queued state updates
-    /// save block in block_db, submit to CommitmentContract
-    /// return generated block
-    pub fn submit_next_block(&mut self) -> Result<(), Error> {
+    /// Builds next block from queued state updates and transactions without modifying the db.
+    fn build_next_block(&self) -> Result<PlasmaBlock, Error> {
         let block_db = BlockDb::from(&self.db);
-        let state_updates = block_db
-            .get_pending_state_updates()
-            .map_err::<Error, _>(Into::into)?;
-        let transactions = block_db.get_pending_txs().map_err::<Error, _>(Into::into)?;
+        let state_updates = self.take_within_limit(
+            block_db
+                .get_pending_state_updates()
+                .map_err::<Error, _>(Into::into)?,
+        );
+        // transactions are included with the state updates they made
+        let transactions: Vec<NewTransactionEvent> = block_db
+            .get_pending_txs()
+            .map_err::<Error, _>(Into::into)?
+            .into_iter()
+            .filter(|tx| {
+                state_updates.iter().any(|s| {
+                    s.get_deposit_contract_address()
+                        == tx.transaction.get_deposit_contract_address()
+                        && s.get_range().is_subrange(&tx.transaction.get_range())
+                })
+            })
+            .collect();
         let mut block = PlasmaBlock::new(self.current_block_number, state_updates, transactions);
+        block.reorder_transactions();
+        block.merge_state_updates();
+        Ok(block)
+    }
+
+    /// Returns the root of the block which would be submitted next.
+    /// The root may differ from the submitted one if state updates are enqueued before submission.
+    pub fn estimate_next_block_root(&self) -> Result<H256, Error> {
+        let root = self.build_next_block()?.compute_state_root()?;
+        Ok(H256::from_slice(&root))
+    }
+
+    /// Submits root of the next block and saves the block after confirmations.
+    /// Returns the saved block.
+    pub fn submit_next_block(&mut self) -> Result<PlasmaBlock, Error> {
+        #[cfg(feature = "metrics")]
+        let timer = self.metrics.block_submission_latency_seconds.start_timer();
+        let block_db = BlockDb::from(&self.db);
+        let mut block = self.build_next_block()?;
 
         let root = block.merkelize()?;
 
@@ -72,12 +365,91 @@ impl<KVS: KeyValueStore + DatabaseTrait> BlockManager<KVS> {
             contract_abi,
         )
         .unwrap();
-        let _ = contract.submit_block(self.aggregator_address, block.get_block_number(), root)?;
+        let result = contract.submit_block(self.aggregator_address, block.get_block_number(), root);
+        #[cfg(feature = "metrics")]
+        {
+            if result.is_err() {
+                self.metrics.block_submission_failures.inc();
+            } else {
+                self.metrics.blocks_submitted.inc();
+                self.metrics
+                    .block_tx_count
+                    .observe(block.get_transactions().len() as f64);
+                timer.observe_duration();
+            }
+        }
+        let tx_hash = result?;
+        contract.wait_for_confirmations(
+            tx_hash,
+            self.confirmation_threshold,
+            DEFAULT_CONFIRMATION_TIMEOUT,
+        )?;
 
-        let _ = block_db.save_block(&block);
-        let _ = block_db.delete_all_queued_state_updates();
-        let _ = block_db.delete_all_queued_txs();
+        block_db.commit_block(&block)?;
+        // transactions enqueued during submission or beyond the block limit are still pending
+        #[cfg(feature = "metrics")]
+        self.metrics
+            .pending_transactions
+            .set(block_db.get_pending_txs()?.len() as i64);
         self.save_next_block_number(self.get_next_block_number());
+        Ok(block)
+    }
+
+    /// Re-applies a stored copy of a historical block, e.g. after the state db is corrupted.
+    /// State updates are re-derived from the transactions of the block and their root must match
+    /// the root of `block`. The block already saved with the same root is skipped, and
+    /// a different saved one returns `ConflictingBlock` error unless it is corrupted.
+    pub fn replay_block(&mut self, block: &PlasmaBlock) -> Result<(), Error> {
+        let root = match block.get_root() {
+            Some(root) => root,
+            None => block.compute_state_root()?,
+        };
+        let block_db = BlockDb::from(&self.db);
+        let block_number = block.get_block_number();
+        if block_db.get_block_numbers()?.contains(&block_number) {
+            match block_db.get_block(Integer::new(block_number)) {
+                Ok(saved) => {
+                    if saved.compute_state_root().ok() == Some(root) {
+                        return Ok(());
+                    }
+                    return Err(Error::from(ErrorKind::ConflictingBlock));
+                }
+                // corrupted copy is overwritten
+                Err(e) => match e.kind() {
+                    ErrorKind::StorageIntegrityError | ErrorKind::AbiError => {}
+                    _ => return Err(e),
+                },
+            }
+        }
+        // next state of each transaction as `OwnershipDecider::execute_state_transition` makes
+        let state_updates = block
+            .get_transactions()
+            .iter()
+            .map(|tx| {
+                Property::from_abi(tx.transaction.get_parameters()).map(|property| {
+                    StateUpdate::new(
+                        Integer::new(block_number),
+                        tx.transaction.get_deposit_contract_address(),
+                        tx.transaction.get_range(),
+                        property,
+                    )
+                })
+            })
+            .collect::<Result<Vec<StateUpdate>, _>>()?;
+        let mut replayed = PlasmaBlock::new(
+            block_number,
+            state_updates,
+            block.get_transactions().to_vec(),
+        );
+        replayed.reorder_transactions();
+        replayed.merge_state_updates();
+        if replayed.merkelize().ok() != Some(root) {
+            return Err(Error::from(ErrorKind::StateRootMismatch));
+        }
+        block_db.commit_block(block)?;
+        if block_number >= self.current_block_number {
+            self.save_next_block_number(block_number + 1);
+        }
         Ok(())
     }
 
@@ -86,6 +458,71 @@ impl<KVS: KeyValueStore + DatabaseTrait> BlockManager<KVS> {
         block_db.get_block(block_number)
     }
 
+    /// Returns the latest state update of each range over all submitted blocks.
+    /// State updates of newer blocks override overlapping parts of older ones.
+    pub fn get_current_state(&self) -> Result<HashMap<Range, StateUpdate>, Error> {
+        let block_db = BlockDb::from(&self.db);
+        let mut state: HashMap<Range, StateUpdate> = HashMap::new();
+        for block_number in 1..self.current_block_number {
+            let block = block_db.get_block(Integer::new(block_number))?;
+            for state_update in block.get_state_updates().iter() {
+                let range = state_update.get_range();
+                let overlapped: Vec<Range> = state
+                    .iter()
+                    .filter(|(r, s)| {
+                        s.get_deposit_contract_address()
+                            == state_update.get_deposit_contract_address()
+                            && r.get_start() < range.get_end()
+                            && range.get_start() < r.get_end()
+                    })
+                    .map(|(r, _)| *r)
+                    .collect();
+                for r in overlapped.iter() {
+                    let old = state.remove(r).unwrap();
+                    // keep the parts of older state update which aren't overridden
+                    let remains = vec![
+                        Range::new(r.get_start(), range.get_start()),
+                        Range::new(range.get_end(), r.get_end()),
+                    ];
+                    for remain in remains
+                        .into_iter()
+                        .filter(|remain| remain.get_start() < remain.get_end())
+                    {
+                        let mut s = old.clone();
+                        s.set_range(remain);
+                        state.insert(remain, s);
+                    }
+                }
+                state.insert(range, state_update.clone());
+            }
+        }
+        Ok(state)
+    }
+
+    /// Takes snapshot of the aggregator database for backup.
+    pub fn snapshot(&self) -> Result<DbDump, Error> {
+        self.db.dump().map_err::<Error, _>(Into::into)
+    }
+
+    /// Restores the aggregator database from snapshot.
+    pub fn restore(&self, dump: &DbDump) -> Result<(), Error> {
+        self.db.restore(dump).map_err::<Error, _>(Into::into)
+    }
+
+    pub fn stats(&self) -> BlockManagerStats {
+        BlockManagerStats {
+            current_block_number: self.current_block_number,
+            queued_state_updates: self.get_queued_state_updates().len(),
+            db: self.db.stats(),
+            pending_exits_on_chain: self.pending_exits_on_chain,
+        }
+    }
+
+    /// Sets the number of pending exits on chain reported by `stats`.
+    pub fn set_pending_exits_on_chain(&mut self, pending_exits_on_chain: u64) {
+        self.pending_exits_on_chain = pending_exits_on_chain;
+    }
+
     pub fn get_current_block_number(&self) -> u64 {
         self.current_block_number
     }
@@ -98,3 +535,407 @@ impl<KVS: KeyValueStore + DatabaseTrait> BlockManager<KVS> {
         self.current_block_number = block_number;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethsign::SecretKey;
+    use plasma_core::data_structure::{Metadata, TransactionParams};
+    use plasma_db::impls::kvs::CoreDbMemoryImpl;
+
+    fn create_state_update(block_number: u64, start: u64, end: u64) -> StateUpdate {
+        StateUpdate::new(
+            Integer::new(block_number),
+            Address::zero(),
+            Range::new(start, end),
+            Property::new(Address::zero(), vec![]),
+        )
+    }
+
+    fn create_signed_tx(secret_key: &SecretKey, start: u64, end: u64, nonce: u64) -> Transaction {
+        let sender = Address::from(secret_key.public().address());
+        let params = TransactionParams::new(Address::zero(), Range::new(start, end), Bytes::new())
+            .with_nonce(Nonce(nonce));
+        let signature = SignVerifier::sign(secret_key, &Bytes::from(params.to_abi()));
+        Transaction::from_params(params, signature, Metadata::new(sender, Address::zero()))
+    }
+
+    #[test]
+    fn test_apply_state_update() {
+        let mut block_manager: BlockManager<CoreDbMemoryImpl> =
+            BlockManager::new(Address::zero(), Address::zero());
+        let secret_key = SecretKey::from_raw(&[1u8; 32]).unwrap();
+        assert_eq!(
+            block_manager.apply_state_update(
+                create_state_update(1, 0, 10),
+                &create_signed_tx(&secret_key, 0, 10, 0)
+            ),
+            Err(StateUpdateError::OutOfDepositRange)
+        );
+        block_manager.add_deposited_range(Address::zero(), Range::new(0, 50));
+        block_manager.add_deposited_range(Address::zero(), Range::new(50, 100));
+        assert_eq!(
+            block_manager.apply_state_update(
+                create_state_update(1, 0, 20),
+                &create_signed_tx(&secret_key, 0, 10, 0)
+            ),
+            Err(StateUpdateError::TransactionMismatch)
+        );
+        assert_eq!(
+            block_manager.apply_state_update(
+                create_state_update(1, 10, 10),
+                &create_signed_tx(&secret_key, 10, 10, 0)
+            ),
+            Err(StateUpdateError::EmptyRange)
+        );
+        assert_eq!(
+            block_manager.apply_state_update(
+                create_state_update(1, 90, 110),
+                &create_signed_tx(&secret_key, 90, 110, 0)
+            ),
+            Err(StateUpdateError::OutOfDepositRange)
+        );
+        let tx = create_signed_tx(&secret_key, 0, 10, 0);
+        assert_eq!(
+            block_manager.apply_state_update(create_state_update(2, 0, 10), &tx),
+            Err(StateUpdateError::InvalidBlockNumber)
+        );
+        assert!(block_manager
+            .apply_state_update(create_state_update(1, 0, 10), &tx)
+            .is_ok());
+        assert_eq!(
+            block_manager.apply_state_update(
+                create_state_update(1, 5, 20),
+                &create_signed_tx(&secret_key, 5, 20, 1)
+            ),
+            Err(StateUpdateError::ConflictingUpdate)
+        );
+        assert_eq!(block_manager.get_queued_state_updates().len(), 1);
+    }
+
+    #[test]
+    fn test_apply_state_update_with_nonce() {
+        let mut block_manager: BlockManager<CoreDbMemoryImpl> =
+            BlockManager::new(Address::zero(), Address::zero());
+        block_manager.set_total_range(Address::zero(), Range::new(0, 100));
+        let secret_key = SecretKey::from_raw(&[1u8; 32]).unwrap();
+        let sender = Address::from(secret_key.public().address());
+        assert_eq!(
+            block_manager.apply_state_update(
+                create_state_update(1, 0, 10),
+                &create_signed_tx(&secret_key, 0, 10, 1)
+            ),
+            Err(StateUpdateError::InvalidNonce {
+                expected: Nonce(0),
+                got: Nonce(1)
+            })
+        );
+        assert!(block_manager
+            .apply_state_update(
+                create_state_update(1, 0, 10),
+                &create_signed_tx(&secret_key, 0, 10, 0)
+            )
+            .is_ok());
+        assert_eq!(
+            block_manager.apply_state_update(
+                create_state_update(1, 10, 20),
+                &create_signed_tx(&secret_key, 10, 20, 0)
+            ),
+            Err(StateUpdateError::InvalidNonce {
+                expected: Nonce(1),
+                got: Nonce(0)
+            })
+        );
+        // nonce is a part of signed body
+        let tampered = create_signed_tx(&secret_key, 10, 20, 0).with_nonce(Nonce(1));
+        assert_eq!(
+            block_manager.apply_state_update(create_state_update(1, 10, 20), &tampered),
+            Err(StateUpdateError::InvalidSignature)
+        );
+        assert!(block_manager
+            .apply_state_update(
+                create_state_update(1, 10, 20),
+                &create_signed_tx(&secret_key, 10, 20, 1)
+            )
+            .is_ok());
+        assert_eq!(
+            block_manager.get_last_nonce(sender).unwrap(),
+            Some(Nonce(1))
+        );
+        assert_eq!(block_manager.get_last_nonce(Address::zero()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_has_pending_updates_after_limit() {
+        let mut block_manager: BlockManager<CoreDbMemoryImpl> =
+            BlockManager::new(Address::zero(), Address::zero());
+        for i in 0..3 {
+            assert!(block_manager
+                .enqueue_state_update(&create_state_update(1, i * 10, (i + 1) * 10))
+                .is_ok());
+        }
+        assert!(!block_manager.has_pending_updates_after_limit());
+        block_manager.set_max_state_updates_per_block(2);
+        assert!(block_manager.has_pending_updates_after_limit());
+        block_manager.set_max_state_updates_per_block(3);
+        assert!(!block_manager.has_pending_updates_after_limit());
+        block_manager.set_max_block_size_bytes(1);
+        assert!(block_manager.has_pending_updates_after_limit());
+        assert_eq!(
+            block_manager
+                .take_within_limit(block_manager.get_queued_state_updates())
+                .len(),
+            1
+        );
+    }
+
+    fn create_tx_event(
+        secret_key: &SecretKey,
+        start: u64,
+        end: u64,
+        nonce: u64,
+    ) -> NewTransactionEvent {
+        NewTransactionEvent::new(
+            vec![Integer::new(0)],
+            create_signed_tx(secret_key, start, end, nonce),
+        )
+    }
+
+    #[test]
+    fn test_enqueue_batch() {
+        let mut block_manager: BlockManager<CoreDbMemoryImpl> =
+            BlockManager::new(Address::zero(), Address::zero());
+        block_manager.set_total_range(Address::zero(), Range::new(0, 100));
+        let secret_key = SecretKey::from_raw(&[1u8; 32]).unwrap();
+        assert!(block_manager
+            .enqueue_batch(
+                &[create_state_update(1, 0, 10), create_state_update(1, 5, 20)],
+                &[
+                    create_tx_event(&secret_key, 0, 10, 0),
+                    create_tx_event(&secret_key, 5, 20, 1)
+                ]
+            )
+            .is_err());
+        assert!(block_manager.get_queued_state_updates().is_empty());
+        assert!(block_manager
+            .enqueue_batch(
+                &[
+                    create_state_update(1, 0, 10),
+                    create_state_update(1, 10, 20)
+                ],
+                &[
+                    create_tx_event(&secret_key, 0, 10, 0),
+                    create_tx_event(&secret_key, 10, 20, 1)
+                ]
+            )
+            .is_ok());
+        assert_eq!(block_manager.get_queued_state_updates().len(), 2);
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut block_manager: BlockManager<CoreDbMemoryImpl> =
+            BlockManager::new(Address::zero(), Address::zero());
+        assert!(block_manager
+            .enqueue_state_update(&create_state_update(1, 0, 100))
+            .is_ok());
+        block_manager.set_pending_exits_on_chain(3);
+        let stats = block_manager.stats();
+        assert_eq!(stats.current_block_number, 1);
+        assert_eq!(stats.queued_state_updates, 1);
+        assert_eq!(stats.pending_exits_on_chain, 3);
+    }
+
+    #[test]
+    fn test_enqueue_batch_rolls_back_on_failure() {
+        let mut block_manager: BlockManager<CoreDbMemoryImpl> =
+            BlockManager::new(Address::zero(), Address::zero());
+        block_manager.set_total_range(Address::zero(), Range::new(0, 100));
+        let secret_key = SecretKey::from_raw(&[1u8; 32]).unwrap();
+        let sender = Address::from(secret_key.public().address());
+        assert!(block_manager
+            .enqueue_state_update(&create_state_update(1, 0, 10))
+            .is_ok());
+        // the second state update is out of deposited range and fails after the first one is applied
+        let result = block_manager.enqueue_batch(
+            &[
+                create_state_update(1, 20, 30),
+                create_state_update(1, 90, 110),
+            ],
+            &[
+                create_tx_event(&secret_key, 20, 30, 0),
+                create_tx_event(&secret_key, 90, 110, 1),
+            ],
+        );
+        assert!(result.is_err());
+        let queued = block_manager.get_queued_state_updates();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].get_range(), Range::new(0, 10));
+        assert_eq!(block_manager.get_last_nonce(sender).unwrap(), None);
+    }
+
+    #[test]
+    fn test_estimate_next_block_root() {
+        let block_manager: BlockManager<CoreDbMemoryImpl> =
+            BlockManager::new(Address::zero(), Address::zero());
+        assert!(block_manager.estimate_next_block_root().is_err());
+        assert!(block_manager
+            .enqueue_state_update(&create_state_update(1, 0, 10))
+            .is_ok());
+        let root = block_manager.estimate_next_block_root().unwrap();
+        assert_eq!(block_manager.estimate_next_block_root().unwrap(), root);
+        assert_eq!(block_manager.get_queued_state_updates().len(), 1);
+        assert!(block_manager
+            .enqueue_state_update(&create_state_update(1, 20, 30))
+            .is_ok());
+        assert_ne!(block_manager.estimate_next_block_root().unwrap(), root);
+    }
+
+    #[test]
+    fn test_get_current_state() {
+        let mut block_manager: BlockManager<CoreDbMemoryImpl> =
+            BlockManager::new(Address::zero(), Address::zero());
+        {
+            let block_db = BlockDb::from(&block_manager.db);
+            let block1 = PlasmaBlock::new(1, vec![create_state_update(1, 0, 100)], vec![]);
+            let block2 = PlasmaBlock::new(2, vec![create_state_update(2, 20, 50)], vec![]);
+            assert!(block_db.save_block(&block1).is_ok());
+            assert!(block_db.save_block(&block2).is_ok());
+        }
+        block_manager.save_next_block_number(3);
+
+        let state = block_manager.get_current_state().unwrap();
+        assert_eq!(state.len(), 3);
+        assert_eq!(
+            state.get(&Range::new(0, 20)).unwrap().get_block_number(),
+            Integer::new(1)
+        );
+        assert_eq!(
+            state.get(&Range::new(20, 50)).unwrap().get_block_number(),
+            Integer::new(2)
+        );
+        assert_eq!(
+            state.get(&Range::new(50, 100)).unwrap().get_range(),
+            Range::new(50, 100)
+        );
+    }
+
+    #[test]
+    fn test_replay_block() {
+        use bytes::Bytes;
+        use plasma_core::data_structure::Metadata;
+        let create_tx = |start: u64, end: u64| {
+            NewTransactionEvent::new(
+                vec![Integer::new(0)],
+                Transaction::new(
+                    Address::zero(),
+                    Range::new(start, end),
+                    Bytes::from(Property::new(Address::zero(), vec![]).to_abi()),
+                    Bytes::default(),
+                    Metadata::default(),
+                ),
+            )
+        };
+        let assert_error = |result: Result<(), Error>, expected: ErrorKind| match result {
+            Err(e) => assert_eq!(format!("{}", e.kind()), format!("{}", expected)),
+            Ok(_) => panic!("block must not be replayed"),
+        };
+        let mut block_manager: BlockManager<CoreDbMemoryImpl> =
+            BlockManager::new(Address::zero(), Address::zero());
+        let mut block = PlasmaBlock::new(
+            1,
+            vec![
+                create_state_update(1, 0, 10),
+                create_state_update(1, 20, 30),
+            ],
+            vec![create_tx(0, 10), create_tx(20, 30)],
+        );
+        assert!(block.merkelize().is_ok());
+        // queued state update which overlaps with the block but isn't in it
+        let other = StateUpdate::new(
+            Integer::new(1),
+            Address::zero(),
+            Range::new(0, 5),
+            Property::new(Address::from_low_u64_be(1), vec![]),
+        );
+        assert!(block_manager.enqueue_state_update(&other).is_ok());
+        assert!(block_manager
+            .enqueue_state_update(&create_state_update(1, 20, 30))
+            .is_ok());
+        assert!(block_manager.replay_block(&block).is_ok());
+        assert_eq!(block_manager.get_current_block_number(), 2);
+        assert_eq!(block_manager.get_queued_state_updates(), vec![other]);
+        assert_eq!(
+            block_manager
+                .get_block_range(Integer::new(1))
+                .unwrap()
+                .get_state_updates()
+                .len(),
+            2
+        );
+        // replaying the same block again is no-op
+        assert!(block_manager.replay_block(&block).is_ok());
+        assert_eq!(block_manager.get_current_block_number(), 2);
+
+        // different block of the same number
+        let conflicting = PlasmaBlock::new(
+            1,
+            vec![create_state_update(1, 0, 10)],
+            vec![create_tx(0, 10)],
+        );
+        assert_error(
+            block_manager.replay_block(&conflicting),
+            ErrorKind::ConflictingBlock,
+        );
+
+        // state updates aren't made by the transactions
+        let mismatched = PlasmaBlock::new(
+            2,
+            vec![create_state_update(2, 0, 10)],
+            vec![create_tx(0, 20)],
+        );
+        assert_error(
+            block_manager.replay_block(&mismatched),
+            ErrorKind::StateRootMismatch,
+        );
+        assert_eq!(block_manager.get_current_block_number(), 2);
+    }
+
+    #[test]
+    fn test_get_orphaned_transactions() {
+        use bytes::Bytes;
+        use plasma_core::data_structure::Metadata;
+        let create_tx = |start: u64, end: u64| {
+            NewTransactionEvent::new(
+                vec![Integer::new(0)],
+                Transaction::new(
+                    Address::zero(),
+                    Range::new(start, end),
+                    Bytes::default(),
+                    Bytes::default(),
+                    Metadata::default(),
+                ),
+            )
+        };
+        let block_manager: BlockManager<CoreDbMemoryImpl> =
+            BlockManager::new(Address::zero(), Address::zero());
+        {
+            let block_db = BlockDb::from(&block_manager.db);
+            let block = PlasmaBlock::new(
+                1,
+                vec![create_state_update(1, 0, 10)],
+                vec![create_tx(0, 10)],
+            );
+            assert!(block_db.save_block(&block).is_ok());
+        }
+        assert!(block_manager.enqueue_tx(create_tx(0, 10)).is_ok());
+        assert!(block_manager
+            .enqueue_state_update(&create_state_update(1, 10, 20))
+            .is_ok());
+        assert!(block_manager.enqueue_tx(create_tx(10, 20)).is_ok());
+        assert!(block_manager.enqueue_tx(create_tx(30, 40)).is_ok());
+        let orphaned = block_manager.get_orphaned_transactions().unwrap();
+        assert_eq!(orphaned.len(), 1);
+        assert_eq!(orphaned[0].get_range(), Range::new(30, 40));
+    }
+}
@@ -0,0 +1,70 @@
+use prometheus::{Histogram, HistogramOpts, IntCounter, IntGauge, Registry};
+
+lazy_static! {
+    static ref GLOBAL_METRICS: BlockManagerMetrics =
+        BlockManagerMetrics::register(prometheus::default_registry()).unwrap();
+}
+
+/// Prometheus metrics of BlockManager.
+#[derive(Clone)]
+pub struct BlockManagerMetrics {
+    pub blocks_submitted: IntCounter,
+    pub pending_transactions: IntGauge,
+    pub block_submission_latency_seconds: Histogram,
+    pub block_submission_failures: IntCounter,
+    pub block_tx_count: Histogram,
+}
+
+impl BlockManagerMetrics {
+    /// Creates metrics and registers them with `registry`.
+    pub fn register(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let metrics = BlockManagerMetrics {
+            blocks_submitted: IntCounter::new(
+                "blocks_submitted",
+                "Number of blocks submitted to commitment contract",
+            )?,
+            pending_transactions: IntGauge::new(
+                "pending_transactions",
+                "Number of transactions waiting for next block",
+            )?,
+            block_submission_latency_seconds: Histogram::with_opts(HistogramOpts::new(
+                "block_submission_latency_seconds",
+                "Latency of block submission in seconds",
+            ))?,
+            block_submission_failures: IntCounter::new(
+                "block_submission_failures",
+                "Number of failed block submissions",
+            )?,
+            block_tx_count: Histogram::with_opts(HistogramOpts::new(
+                "block_tx_count",
+                "Number of transactions in submitted blocks",
+            ))?,
+        };
+        registry.register(Box::new(metrics.blocks_submitted.clone()))?;
+        registry.register(Box::new(metrics.pending_transactions.clone()))?;
+        registry.register(Box::new(metrics.block_submission_latency_seconds.clone()))?;
+        registry.register(Box::new(metrics.block_submission_failures.clone()))?;
+        registry.register(Box::new(metrics.block_tx_count.clone()))?;
+        Ok(metrics)
+    }
+
+    /// Returns metrics registered with the global registry.
+    pub fn global() -> Self {
+        GLOBAL_METRICS.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlockManagerMetrics;
+    use prometheus::Registry;
+
+    #[test]
+    fn test_register() {
+        let registry = Registry::new();
+        let metrics = BlockManagerMetrics::register(&registry).unwrap();
+        metrics.blocks_submitted.inc();
+        assert_eq!(registry.gather().len(), 5);
+        assert!(BlockManagerMetrics::register(&registry).is_err());
+    }
+}
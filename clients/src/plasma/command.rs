@@ -3,8 +3,9 @@ use abi_derive::{AbiDecodable, AbiEncodable};
 use abi_utils::{Encodable, Integer};
 use bytes::Bytes;
 use ethabi::{ParamType, Token};
-use ovm::types::StateUpdateList;
-use plasma_core::data_structure::Transaction;
+use ethereum_types::Address;
+use ovm::types::{PlasmaDataBlock, StateUpdateList};
+use plasma_core::data_structure::{Range, Transaction};
 
 #[derive(Clone, Debug, AbiDecodable, AbiEncodable)]
 pub struct Command {
@@ -40,6 +41,147 @@ impl Command {
             body: Bytes::from(new_tx_event.to_abi()),
         }
     }
+    pub fn create_ping_request(commitment_contract_address: Address) -> Self {
+        Command {
+            command_type: Integer(5),
+            body: Bytes::from(PingRequest::new(commitment_contract_address).to_abi()),
+        }
+    }
+    pub fn create_inclusion_proof_request(block_number: Integer, range: Range) -> Self {
+        Command {
+            command_type: Integer(6),
+            body: Bytes::from(InclusionProofRequest::new(block_number, range).to_abi()),
+        }
+    }
+    pub fn create_inclusion_proof_response(response: InclusionProofResponse) -> Self {
+        Command {
+            command_type: Integer(7),
+            body: Bytes::from(response.to_abi()),
+        }
+    }
+    pub fn create_latency_ping(timestamp: Integer) -> Self {
+        Command {
+            command_type: Integer(8),
+            body: Bytes::from(LatencyPing::new(timestamp).to_abi()),
+        }
+    }
+    pub fn create_latency_pong(timestamp: Integer) -> Self {
+        Command {
+            command_type: Integer(9),
+            body: Bytes::from(LatencyPing::new(timestamp).to_abi()),
+        }
+    }
+    pub fn create_exclusion_proof_request(
+        block_number: Integer,
+        deposit_contract_address: Address,
+        range: Range,
+    ) -> Self {
+        Command {
+            command_type: Integer(10),
+            body: Bytes::from(
+                ExclusionProofRequest::new(block_number, deposit_contract_address, range).to_abi(),
+            ),
+        }
+    }
+    pub fn create_ping_response(commitment_contract_address: Address) -> Self {
+        Command {
+            command_type: Integer(12),
+            body: Bytes::from(PingRequest::new(commitment_contract_address).to_abi()),
+        }
+    }
+    pub fn create_exclusion_proof_response(response: InclusionProofResponse) -> Self {
+        Command {
+            command_type: Integer(11),
+            body: Bytes::from(response.to_abi()),
+        }
+    }
+}
+
+/// Aggregator closes connection if it isn't on the same commitment contract,
+/// and otherwise responds with its commitment contract address in the same body.
+#[derive(Clone, Debug, AbiDecodable, AbiEncodable)]
+pub struct PingRequest {
+    pub commitment_contract_address: Address,
+}
+
+impl PingRequest {
+    pub fn new(commitment_contract_address: Address) -> Self {
+        Self {
+            commitment_contract_address,
+        }
+    }
+}
+
+/// Body of latency ping and pong. The aggregator reflects `timestamp` of ping in pong.
+#[derive(Clone, Debug, AbiDecodable, AbiEncodable)]
+pub struct LatencyPing {
+    pub timestamp: Integer,
+}
+
+impl LatencyPing {
+    pub fn new(timestamp: Integer) -> Self {
+        Self { timestamp }
+    }
+}
+
+/// Requests inclusion proof of the state update which is in `range` at `block_number`.
+#[derive(Clone, Debug, AbiDecodable, AbiEncodable)]
+pub struct InclusionProofRequest {
+    pub block_number: Integer,
+    pub range: Range,
+}
+
+impl InclusionProofRequest {
+    pub fn new(block_number: Integer, range: Range) -> Self {
+        Self {
+            block_number,
+            range,
+        }
+    }
+}
+
+/// `block_number` and `range` are the same as the request.
+#[derive(Clone, Debug, AbiDecodable, AbiEncodable)]
+pub struct InclusionProofResponse {
+    pub block_number: Integer,
+    pub range: Range,
+    pub inclusion_proof: Bytes,
+    pub plasma_data_block: PlasmaDataBlock,
+}
+
+impl InclusionProofResponse {
+    pub fn new(
+        block_number: Integer,
+        range: Range,
+        inclusion_proof: Bytes,
+        plasma_data_block: PlasmaDataBlock,
+    ) -> Self {
+        Self {
+            block_number,
+            range,
+            inclusion_proof,
+            plasma_data_block,
+        }
+    }
+}
+
+/// Requests exclusion proof of `range` at `block_number`.
+/// The response is the inclusion proof of the first state update after `range`.
+#[derive(Clone, Debug, AbiDecodable, AbiEncodable)]
+pub struct ExclusionProofRequest {
+    pub block_number: Integer,
+    pub deposit_contract_address: Address,
+    pub range: Range,
+}
+
+impl ExclusionProofRequest {
+    pub fn new(block_number: Integer, deposit_contract_address: Address, range: Range) -> Self {
+        Self {
+            block_number,
+            deposit_contract_address,
+            range,
+        }
+    }
 }
 
 #[derive(Clone, Debug, AbiDecodable, AbiEncodable)]
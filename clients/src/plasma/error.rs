@@ -3,10 +3,12 @@ use contract_wrapper::error::Error as ContractError;
 use ethabi::Error as AbiDecodeError;
 use failure::{Backtrace, Context, Fail};
 use plasma_core::data_structure::error::Error as PlasmaCoreError;
+use plasma_core::data_structure::Nonce;
 use plasma_db::error::Error as PlasmaDbError;
 use std::fmt;
 use std::fmt::Display;
 use std::io::Error as IoError;
+use std::time::Duration;
 
 #[derive(Fail, Debug)]
 pub enum ErrorKind {
@@ -28,6 +30,76 @@ pub enum ErrorKind {
     MerkelizingError,
     #[fail(display = "Invalid Parameter")]
     InvalidParameter,
+    #[fail(display = "Connection Error")]
+    ConnectionError,
+    #[fail(display = "Storage Integrity Error")]
+    StorageIntegrityError,
+    #[fail(display = "State Root Mismatch")]
+    StateRootMismatch,
+    #[fail(display = "Conflicting Block")]
+    ConflictingBlock,
+    #[fail(display = "Commitment Contract Mismatch")]
+    CommitmentContractMismatch,
+    #[fail(display = "Invalid State Update")]
+    InvalidStateUpdate,
+}
+
+#[derive(Fail, Debug, PartialEq)]
+pub enum ProofError {
+    #[fail(display = "State update is not in block")]
+    StateUpdateNotInBlock,
+    #[fail(display = "Block has no state updates")]
+    EmptyBlock,
+    #[fail(display = "Transaction is not in block")]
+    TransactionNotInBlock,
+    #[fail(display = "Range is included in block")]
+    RangeIncluded,
+}
+
+#[derive(Fail, Debug, PartialEq)]
+pub enum StateUpdateError {
+    #[fail(display = "Range of state update is empty")]
+    EmptyRange,
+    #[fail(display = "Range is out of total range of deposit contract")]
+    OutOfDepositRange,
+    #[fail(display = "Block number isn't current block number")]
+    InvalidBlockNumber,
+    #[fail(display = "Conflicting state update is pending")]
+    ConflictingUpdate,
+    #[fail(display = "State update isn't made by the transaction")]
+    TransactionMismatch,
+    #[fail(display = "Invalid nonce: expected {}, got {}", expected, got)]
+    InvalidNonce { expected: Nonce, got: Nonce },
+    #[fail(display = "Transaction isn't signed by the sender")]
+    InvalidSignature,
+    #[fail(display = "Db Error")]
+    PlasmaDbError,
+}
+
+#[derive(Fail, Debug, PartialEq)]
+pub enum PingError {
+    #[fail(display = "Aggregator is unreachable")]
+    Unreachable,
+    #[fail(display = "Aggregator didn't respond in {:?}", _0)]
+    Timeout(Duration),
+    #[fail(display = "Unexpected response")]
+    UnexpectedResponse,
+}
+
+#[derive(Fail, Debug, PartialEq)]
+pub enum HealthError {
+    #[fail(display = "Plasma contract ABI is not found")]
+    ContractAbiNotFound,
+    #[fail(display = "Invalid endpoint")]
+    InvalidEndpoint,
+    #[fail(display = "Ethereum node is unreachable")]
+    EthNodeUnreachable,
+    #[fail(display = "Aggregator is unreachable")]
+    AggregatorUnreachable,
+    #[fail(display = "Plasma contract is not deployed")]
+    ContractNotDeployed,
+    #[fail(display = "Plasma chain ID is not detected")]
+    PlasmaChainIdNotDetected,
 }
 
 #[derive(Debug)]
@@ -115,6 +187,14 @@ impl From<PlasmaDbError> for Error {
     }
 }
 
+impl From<StateUpdateError> for Error {
+    fn from(error: StateUpdateError) -> Error {
+        Error {
+            inner: error.context(ErrorKind::InvalidStateUpdate),
+        }
+    }
+}
+
 impl From<ContractError> for Error {
     fn from(error: ContractError) -> Error {
         Error {
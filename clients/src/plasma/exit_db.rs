@@ -0,0 +1,175 @@
+use super::error::Error;
+use abi_derive::{AbiDecodable, AbiEncodable};
+use abi_utils::{Decodable, Encodable, Error as AbiError, ErrorKind as AbiErrorKind, Integer};
+use bytes::Bytes;
+use ethabi::{ParamType, Token};
+use ethereum_types::Address;
+use plasma_core::data_structure::Range;
+use plasma_db::traits::kvs::{BaseDbKey, KeyValueStore};
+
+/// Status of exit updated by exit events of plasma contract.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExitStatus {
+    Pending,
+    Challenged,
+    Finalized,
+}
+
+impl Encodable for ExitStatus {
+    fn to_tuple(&self) -> Vec<Token> {
+        let status: u64 = match self {
+            ExitStatus::Pending => 0,
+            ExitStatus::Challenged => 1,
+            ExitStatus::Finalized => 2,
+        };
+        vec![Token::Uint(status.into())]
+    }
+}
+
+impl Decodable for ExitStatus {
+    type Ok = Self;
+    fn from_tuple(tuple: &[Token]) -> Result<Self, AbiError> {
+        match tuple[0].clone().to_uint().map(|status| status.as_u64()) {
+            Some(0) => Ok(ExitStatus::Pending),
+            Some(1) => Ok(ExitStatus::Challenged),
+            Some(2) => Ok(ExitStatus::Finalized),
+            _ => Err(AbiError::from(AbiErrorKind::AbiDecode)),
+        }
+    }
+    fn get_param_types() -> Vec<ParamType> {
+        vec![ParamType::Uint(8)]
+    }
+}
+
+/// Exit started by `claimant`. `claim_nonce` is assigned by plasma contract in the order
+/// in which exits were started.
+#[derive(Clone, Debug, PartialEq, AbiDecodable, AbiEncodable)]
+pub struct ExitClaim {
+    pub claim_nonce: Integer,
+    pub claimant: Address,
+    pub block_number: Integer,
+    pub range: Range,
+    pub status: ExitStatus,
+}
+
+impl ExitClaim {
+    pub fn new(
+        claim_nonce: Integer,
+        claimant: Address,
+        block_number: Integer,
+        range: Range,
+        status: ExitStatus,
+    ) -> Self {
+        Self {
+            claim_nonce,
+            claimant,
+            block_number,
+            range,
+            status,
+        }
+    }
+}
+
+pub struct ExitDb<'a, KVS> {
+    db: &'a KVS,
+}
+
+impl<'a, KVS: KeyValueStore> ExitDb<'a, KVS> {
+    pub fn new(db: &'a KVS) -> Self {
+        Self { db }
+    }
+
+    pub fn put_exit(&self, exit: &ExitClaim) -> Result<(), Error> {
+        self.db
+            .bucket(&Bytes::from("exits").into())
+            .put(&exit.claim_nonce.0.into(), &exit.to_abi())
+            .map_err::<Error, _>(Into::into)
+    }
+
+    pub fn get_exit(&self, claim_nonce: Integer) -> Result<Option<ExitClaim>, Error> {
+        self.db
+            .bucket(&Bytes::from("exits").into())
+            .get(&claim_nonce.0.into())
+            .map_err::<Error, _>(Into::into)?
+            .map(|exit| ExitClaim::from_abi(&exit).map_err::<Error, _>(Into::into))
+            .transpose()
+    }
+
+    /// Returns all exits sorted by `claim_nonce`.
+    pub fn get_exits(&self) -> Result<Vec<ExitClaim>, Error> {
+        self.db
+            .bucket(&Bytes::from("exits").into())
+            .iter_all(&BaseDbKey::new(vec![]), Box::new(|_k, _v| true))
+            .iter()
+            .map(|kv| ExitClaim::from_abi(kv.get_value()).map_err::<Error, _>(Into::into))
+            .collect()
+    }
+
+    /// Updates status of the exit of `claim_nonce`. Returns false if the exit isn't stored.
+    pub fn update_status(&self, claim_nonce: Integer, status: ExitStatus) -> Result<bool, Error> {
+        match self.get_exit(claim_nonce)? {
+            Some(mut exit) => {
+                exit.status = status;
+                self.put_exit(&exit)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plasma_db::impls::kvs::CoreDbMemoryImpl;
+    use plasma_db::traits::DatabaseTrait;
+
+    #[test]
+    fn test_put_and_get_exits() {
+        let db = CoreDbMemoryImpl::open("test");
+        let exit_db = ExitDb::new(&db);
+        for i in [2, 0, 1].iter() {
+            let exit = ExitClaim::new(
+                Integer(*i),
+                Address::zero(),
+                Integer(1),
+                Range::new(*i * 10, (*i + 1) * 10),
+                ExitStatus::Pending,
+            );
+            assert!(exit_db.put_exit(&exit).is_ok());
+        }
+        let exits = exit_db.get_exits().unwrap();
+        assert_eq!(exits.len(), 3);
+        assert_eq!(exits[0].claim_nonce, Integer(0));
+        assert_eq!(exits[2].range, Range::new(20, 30));
+        assert_eq!(
+            exit_db.get_exit(Integer(1)).unwrap(),
+            Some(exits[1].clone())
+        );
+        assert_eq!(exit_db.get_exit(Integer(3)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_update_status() {
+        let db = CoreDbMemoryImpl::open("test");
+        let exit_db = ExitDb::new(&db);
+        let exit = ExitClaim::new(
+            Integer(0),
+            Address::zero(),
+            Integer(1),
+            Range::new(0, 10),
+            ExitStatus::Pending,
+        );
+        exit_db.put_exit(&exit).unwrap();
+        assert!(exit_db
+            .update_status(Integer(0), ExitStatus::Challenged)
+            .unwrap());
+        assert_eq!(
+            exit_db.get_exit(Integer(0)).unwrap().unwrap().status,
+            ExitStatus::Challenged
+        );
+        assert!(!exit_db
+            .update_status(Integer(1), ExitStatus::Finalized)
+            .unwrap());
+    }
+}
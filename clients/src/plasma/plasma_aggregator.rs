@@ -1,12 +1,14 @@
-use super::block_manager::BlockManager;
-use super::command::NewTransactionEvent;
+use super::block_manager::{BlockManager, BlockManagerStats};
+use super::command::{Command, InclusionProofResponse, LatencyPing, NewTransactionEvent};
 use super::error::{Error, ErrorKind};
 use super::plasma_block::PlasmaBlock;
 use super::plasma_client::PlasmaClientShell;
 use super::state_db::StateDb;
 use super::token::Token;
 use super::utils::*;
+use abi_utils::Decodable;
 use bytes::Bytes;
+use contract_wrapper::plasma_contract_adaptor::PlasmaContractAdaptor;
 use ethereum_types::Address;
 use ethsign::SecretKey;
 use ovm::db::{SignedByDb, TransactionDb};
@@ -14,9 +16,13 @@ use ovm::deciders::SignVerifier;
 use ovm::property_executor::PropertyExecutor;
 use ovm::types::Integer;
 use ovm::types::{StateUpdate, StateUpdateList};
-use plasma_core::data_structure::{Range, Transaction};
+use plasma_core::data_structure::{Range, Transaction, TransactionBatch};
 use plasma_db::traits::db::DatabaseTrait;
 use plasma_db::traits::kvs::KeyValueStore;
+use std::time::Duration;
+
+/// Timeout of deciding properties to validate an incoming transaction.
+pub const TRANSACTION_VALIDATION_TIMEOUT: Duration = Duration::from_millis(100);
 
 pub struct PlasmaAggregator<KVS: KeyValueStore> {
     aggregator_address: Address,
@@ -41,7 +47,17 @@ impl<KVS: KeyValueStore + DatabaseTrait> PlasmaAggregator<KVS> {
         let raw_key = hex::decode(private_key).unwrap();
         let secret_key = SecretKey::from_raw(&raw_key).unwrap();
         let my_address: Address = secret_key.public().address().into();
-        let block_manager = BlockManager::new(aggregator_address, commitment_contract_address);
+        let mut block_manager = BlockManager::new(aggregator_address, commitment_contract_address);
+        let mut decider: PropertyExecutor<KVS> = Default::default();
+        decider.set_decision_timeout(Some(TRANSACTION_VALIDATION_TIMEOUT));
+        // verified state updates cover all deposited ranges
+        let state_db = StateDb::new(decider.get_range_db());
+        for state_update in state_db.get_all_state_updates().unwrap_or_else(|_| vec![]) {
+            block_manager.add_deposited_range(
+                state_update.get_deposit_contract_address(),
+                state_update.get_range(),
+            );
+        }
 
         PlasmaAggregator {
             aggregator_address,
@@ -51,7 +67,7 @@ impl<KVS: KeyValueStore + DatabaseTrait> PlasmaAggregator<KVS> {
             //_secret_key: secret_key,
             _my_address: my_address,
             block_manager,
-            decider: Default::default(),
+            decider,
         }
     }
 
@@ -60,7 +76,8 @@ impl<KVS: KeyValueStore + DatabaseTrait> PlasmaAggregator<KVS> {
     //    InvalidTransaction Error.
     // 3. for all state_updates, check state transition using state_update.property.decide(transaction).
     //    any of these throw error, return InvalidTransaction Error.
-    // 4. if all transitions are verified, add new state_update to a queue.
+    // 4. if all transitions are verified, add new state_update to a queue
+    //    after validating it by BlockManager::apply_state_update.
     //
     // TODO:
     // - handle multi prev_states case.
@@ -69,10 +86,60 @@ impl<KVS: KeyValueStore + DatabaseTrait> PlasmaAggregator<KVS> {
         &mut self,
         transaction: Transaction,
     ) -> Result<NewTransactionEvent, Error> {
+        let mut new_txs = self.ingest_transactions(&[transaction])?;
+        Ok(new_txs.remove(0))
+    }
+
+    /// Ingests all transactions in `batch` signed by `sender`, or none of them if any is invalid.
+    /// Each transaction is verified against the state before the batch.
+    pub fn ingest_transaction_batch(
+        &mut self,
+        batch: TransactionBatch,
+        sender: Address,
+    ) -> Result<Vec<NewTransactionEvent>, Error> {
+        if !batch.verify_batch_signature(sender) {
+            return Err(Error::from(ErrorKind::InvalidTransaction));
+        }
+        self.ingest_transactions(batch.get_transactions())
+    }
+
+    /// Ingests all of `transactions`, or none of them if any is invalid.
+    fn ingest_transactions(
+        &self,
+        transactions: &[Transaction],
+    ) -> Result<Vec<NewTransactionEvent>, Error> {
+        let decider = &self.decider;
+        // witnesses and verified states written so far are rolled back if any step fails
+        decider.get_db().transaction(|_| {
+            decider.get_range_db().get_db().transaction(|_| {
+                let mut next_states = vec![];
+                let mut new_txs = vec![];
+                for transaction in transactions {
+                    let (next_state, new_tx) = self.execute_transaction(transaction)?;
+                    next_states.push(next_state);
+                    new_txs.push(new_tx);
+                }
+                let mut state_db = StateDb::new(decider.get_range_db());
+                for next_state in next_states.iter() {
+                    state_db.put_verified_state_update(next_state)?;
+                }
+                // enqueued last because block manager has its own db
+                self.block_manager.enqueue_batch(&next_states, &new_txs)?;
+                Ok(new_txs)
+            })
+        })
+    }
+
+    /// Verifies state transitions made by `transaction` and returns next state update
+    /// without enqueuing it.
+    fn execute_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<(StateUpdate, NewTransactionEvent), Error> {
         let transaction_db = TransactionDb::new(self.decider.get_range_db());
         let signed_by_db = SignedByDb::new(self.decider.get_db());
         let next_block_number = self.block_manager.get_current_block_number();
-        let mut state_db = StateDb::new(self.decider.get_range_db());
+        let state_db = StateDb::new(self.decider.get_range_db());
         let state_updates = state_db
             .get_verified_state_updates(
                 transaction.get_deposit_contract_address(),
@@ -97,31 +164,34 @@ impl<KVS: KeyValueStore + DatabaseTrait> PlasmaAggregator<KVS> {
             )
             .is_ok());
         // Check that the transaction deprecate all previous state_updates within same coin range.
+        let mut next_state = None;
         for prev_state in state_updates.clone() {
             // Current execute_state_transition returns next state_update which has the same range as transaction.
-            // It means same next_state is added to storage multiple times and it's overwrite.
-            if let Ok(next_state) = prev_state.execute_state_transition(
+            // It means all previous state_updates transition to the same next_state.
+            if let Ok(state) = prev_state.execute_state_transition(
                 &self.decider,
-                &transaction,
+                transaction,
                 Integer(next_block_number),
             ) {
-                self.block_manager.enqueue_state_update(&next_state)?;
-                state_db.put_verified_state_update(&next_state)?;
+                next_state = Some(state);
             } else {
                 return Err(Error::from(ErrorKind::InvalidTransaction));
             }
         }
+        let next_state = next_state.ok_or_else(|| Error::from(ErrorKind::InvalidTransaction))?;
         let prev_block_numbers = state_updates.iter().map(|s| s.get_block_number()).collect();
         let new_tx = NewTransactionEvent::new(prev_block_numbers, transaction.clone());
-        self.block_manager.enqueue_tx(new_tx.clone())?;
-        Ok(new_tx)
+        Ok((next_state, new_tx))
     }
 
-    pub fn submit_next_block(&mut self) -> Result<(), Error> {
+    /// Submits the next block and returns it signed by the aggregator so that it can be broadcast.
+    pub fn submit_next_block(&mut self) -> Result<PlasmaBlock, Error> {
         // dequeue all state_update stored in range db
         // generate block using that data.
         let block_manager = &mut self.block_manager;
-        block_manager.submit_next_block()
+        let mut block = block_manager.submit_next_block()?;
+        block.sign(&SecretKey::from_raw(&self._raw_key).unwrap());
+        Ok(block)
     }
 
     pub fn get_aggregator_addres(&self) -> Address {
@@ -140,8 +210,19 @@ impl<KVS: KeyValueStore + DatabaseTrait> PlasmaAggregator<KVS> {
         println!("{:?}", self.block_manager.get_queued_state_updates());
     }
 
-    pub fn insert_test_ranges(&mut self) {
+    /// Stores deposited `state_update` as verified and extends the total deposited range
+    /// checked by `BlockManager::apply_state_update`.
+    pub fn handle_deposit(&mut self, state_update: &StateUpdate) -> Result<(), Error> {
         let mut state_db = StateDb::new(self.decider.get_range_db());
+        state_db.put_verified_state_update(state_update)?;
+        self.block_manager.add_deposited_range(
+            state_update.get_deposit_contract_address(),
+            state_update.get_range(),
+        );
+        Ok(())
+    }
+
+    pub fn insert_test_ranges(&mut self) {
         let eth_token_address = Address::zero();
         let dai_token_address = string_to_address("0000000000000000000000000000000000000001");
         for i in 0..3 {
@@ -153,7 +234,7 @@ impl<KVS: KeyValueStore + DatabaseTrait> PlasmaAggregator<KVS> {
                     "627306090abab3a6e1400e9345bc60c78a8bef57",
                 )),
             );
-            assert!(state_db.put_verified_state_update(&state_update).is_ok());
+            assert!(self.handle_deposit(&state_update).is_ok());
         }
         for i in 0..3 {
             let state_update = StateUpdate::new(
@@ -164,7 +245,7 @@ impl<KVS: KeyValueStore + DatabaseTrait> PlasmaAggregator<KVS> {
                     "627306090abab3a6e1400e9345bc60c78a8bef57",
                 )),
             );
-            assert!(state_db.put_verified_state_update(&state_update).is_ok());
+            assert!(self.handle_deposit(&state_update).is_ok());
         }
     }
 
@@ -182,8 +263,83 @@ impl<KVS: KeyValueStore + DatabaseTrait> PlasmaAggregator<KVS> {
             .map(|b| StateUpdateList::new(b.get_state_updates().to_vec()))
     }
 
+    /// Returns the block of `block_number` signed by the aggregator.
     pub fn get_plasma_block_of_block(&self, block_number: Integer) -> Result<PlasmaBlock, Error> {
-        self.block_manager.get_block_range(block_number)
+        let mut block = self.block_manager.get_block_range(block_number)?;
+        block.merkelize()?;
+        block.sign(&SecretKey::from_raw(&self._raw_key).unwrap());
+        Ok(block)
+    }
+
+    /// Creates inclusion proof of the state update which is in `range` at `block_number`.
+    pub fn get_inclusion_proof(
+        &self,
+        block_number: Integer,
+        range: Range,
+    ) -> Result<InclusionProofResponse, Error> {
+        let mut block = self.block_manager.get_block_range(block_number)?;
+        let root = block.merkelize()?;
+        let state_update = block
+            .get_state_updates()
+            .iter()
+            .find(|s| s.get_range().intersection(&range).is_some())
+            .cloned()
+            .ok_or_else(|| Error::from(ErrorKind::InvalidParameter))?;
+        let inclusion_proof = block
+            .get_inclusion_proof(&state_update, state_update.get_deposit_contract_address())
+            .map_err(|_| Error::from(ErrorKind::InvalidParameter))?;
+        Ok(InclusionProofResponse::new(
+            block_number,
+            range,
+            inclusion_proof,
+            block.get_plasma_data_block(root, state_update),
+        ))
+    }
+
+    /// Creates inclusion proof of the first state update after `range` at `block_number`,
+    /// which proves that `range` isn't included in the block.
+    pub fn get_exclusion_proof(
+        &self,
+        block_number: Integer,
+        deposit_contract_address: Address,
+        range: Range,
+    ) -> Result<InclusionProofResponse, Error> {
+        let mut block = self.block_manager.get_block_range(block_number)?;
+        let root = block.merkelize()?;
+        let state_update = block
+            .get_next_state_update(deposit_contract_address, range)
+            .map_err(|_| Error::from(ErrorKind::InvalidParameter))?
+            .clone();
+        let inclusion_proof = block
+            .get_inclusion_proof(&state_update, deposit_contract_address)
+            .map_err(|_| Error::from(ErrorKind::InvalidParameter))?;
+        Ok(InclusionProofResponse::new(
+            block_number,
+            range,
+            inclusion_proof,
+            block.get_plasma_data_block(root, state_update),
+        ))
+    }
+
+    /// Responds to latency ping whose body is `body` with pong reflecting its timestamp.
+    pub fn handle_latency_ping(&self, body: &[u8]) -> Result<Command, Error> {
+        let ping = LatencyPing::from_abi(body)?;
+        Ok(Command::create_latency_pong(ping.timestamp))
+    }
+
+    pub fn stats(&self) -> BlockManagerStats {
+        self.block_manager.stats()
+    }
+
+    /// Reads the number of pending exits from plasma contract and reflects it to `stats`.
+    /// Should be called periodically.
+    pub fn update_pending_exits_on_chain(
+        &mut self,
+        plasma_contract: &PlasmaContractAdaptor,
+    ) -> Result<u64, Error> {
+        let exit_count = plasma_contract.get_exit_count()?;
+        self.block_manager.set_pending_exits_on_chain(exit_count);
+        Ok(exit_count)
     }
 
     pub fn register_token(_token: Token) {
@@ -210,9 +366,27 @@ mod tests {
     use ethereum_types::Address;
     use ethsign::SecretKey;
     use ovm::deciders::SignVerifier;
-    use plasma_core::data_structure::{Metadata, Range, Transaction, TransactionParams};
+    use plasma_core::data_structure::{Metadata, Nonce, Range, Transaction, TransactionParams};
     use plasma_db::impls::kvs::CoreDbMemoryImpl;
 
+    #[test]
+    fn test_handle_latency_ping() {
+        let aggregator: PlasmaAggregator<CoreDbMemoryImpl> = PlasmaAggregator::new(
+            Address::zero(),
+            Address::zero(),
+            Address::zero(),
+            "c87509a1c067bbde78beb793e6fa76530b6382a4c0241e5e4a9ec0a0f44dc0d3",
+        );
+        let ping = Command::create_latency_ping(Integer(100));
+        let pong = aggregator.handle_latency_ping(&ping.body).unwrap();
+        assert_eq!(pong.command_type, Integer(9));
+        assert_eq!(
+            LatencyPing::from_abi(&pong.body).unwrap().timestamp,
+            Integer(100)
+        );
+        assert!(aggregator.handle_latency_ping(b"invalid").is_err());
+    }
+
     #[test]
     fn test_ingest() {
         let mut aggregator: PlasmaAggregator<CoreDbMemoryImpl> = PlasmaAggregator::new(
@@ -225,15 +399,30 @@ mod tests {
             hex::decode("c87509a1c067bbde78beb793e6fa76530b6382a4c0241e5e4a9ec0a0f44dc0d3")
                 .unwrap();
         let secret_key = SecretKey::from_raw(&secret_key_raw).unwrap();
-        let test_range = Range::new(5, 15);
+        let sender = Address::from(secret_key.public().address());
         let parameters = PlasmaClientShell::create_ownership_state_object(Address::zero()).to_abi();
         aggregator.insert_test_ranges();
-        let transaction_params =
-            TransactionParams::new(Address::zero(), test_range, Bytes::from(parameters));
-        let signature = SignVerifier::sign(&secret_key, &Bytes::from(transaction_params.to_abi()));
-        let transaction =
-            Transaction::from_params(transaction_params, signature, Metadata::default());
-        let result = aggregator.ingest_transaction(transaction);
+        let create_transaction = |range: Range, nonce: u64| {
+            let transaction_params =
+                TransactionParams::new(Address::zero(), range, Bytes::from(parameters.clone()))
+                    .with_nonce(Nonce(nonce));
+            let signature =
+                SignVerifier::sign(&secret_key, &Bytes::from(transaction_params.to_abi()));
+            Transaction::from_params(
+                transaction_params,
+                signature,
+                Metadata::new(sender, Address::zero()),
+            )
+        };
+        let result = aggregator.ingest_transaction(create_transaction(Range::new(5, 15), 0));
+        assert!(result.is_ok());
+        // replayed nonce is rejected
+        let result = aggregator.ingest_transaction(create_transaction(Range::new(25, 35), 0));
+        assert_eq!(
+            format!("{}", result.err().unwrap().kind()),
+            format!("{}", ErrorKind::InvalidStateUpdate)
+        );
+        let result = aggregator.ingest_transaction(create_transaction(Range::new(25, 35), 1));
         assert!(result.is_ok());
     }
 }
@@ -1,18 +1,68 @@
 use super::command::NewTransactionEvent;
-use super::error::{Error, ErrorKind};
+use super::error::{Error, ErrorKind, ProofError};
 use abi_utils::{Decodable, Encodable, Error as PlasmaCoreError, ErrorKind as PlasmaCoreErrorKind};
 use bytes::Bytes;
 use ethabi::{ParamType, Token};
-use ethereum_types::Address;
+use ethereum_types::{Address, H256, U256};
+use ethsign::SecretKey;
 use merkle_interval_tree::{DoubleLayerTree, DoubleLayerTreeLeaf};
+use ovm::deciders::SignVerifier;
 use ovm::types::core::Integer;
-use ovm::types::{PlasmaDataBlock, StateUpdate};
+use ovm::types::{DoubleLayerTreeLeafExt, PlasmaDataBlock, StateUpdate};
+use plasma_core::data_structure::{BlockHeader, Range, Receipt};
+use std::cmp::Ordering;
+use std::sync::RwLock;
+use tiny_keccak::Keccak;
+
+fn get_transaction_hash(tx: &NewTransactionEvent) -> Bytes {
+    let mut sha3 = Keccak::new_sha3_256();
+    sha3.update(&tx.transaction.to_abi());
+    let mut res: [u8; 32] = [0; 32];
+    sha3.finalize(&mut res);
+    Bytes::from(&res[..])
+}
+
+/// Proof that a range isn't covered by any state update of a block.
+/// `leaf` is the first leaf after the range and its implicit interval contains the range.
+#[derive(Clone, Debug)]
+pub struct ExclusionProof {
+    pub block_number: u64,
+    pub leaf: DoubleLayerTreeLeaf,
+    pub merkle_proof: Bytes,
+}
+
+impl ExclusionProof {
+    pub fn new(block_number: u64, leaf: DoubleLayerTreeLeaf, merkle_proof: Bytes) -> Self {
+        Self {
+            block_number,
+            leaf,
+            merkle_proof,
+        }
+    }
+}
+
+impl Encodable for ExclusionProof {
+    fn to_tuple(&self) -> Vec<Token> {
+        vec![
+            Token::Uint(self.block_number.into()),
+            Token::Tuple(vec![
+                Token::Address(self.leaf.address),
+                Token::Uint(self.leaf.end.into()),
+                Token::Bytes(self.leaf.data.to_vec()),
+            ]),
+            Token::Bytes(self.merkle_proof.to_vec()),
+        ]
+    }
+}
 
 pub struct PlasmaBlock {
     block_number: Integer,
     state_updates: Vec<StateUpdate>,
     transactions: Vec<NewTransactionEvent>,
-    tree: Option<DoubleLayerTree>,
+    /// Signature of the block hash by the aggregator.
+    aggregator_signature: Option<Bytes>,
+    /// Cache of the tree built from state updates.
+    tree: RwLock<Option<DoubleLayerTree>>,
 }
 
 impl PlasmaBlock {
@@ -25,7 +75,8 @@ impl PlasmaBlock {
             block_number: Integer::new(block_number),
             state_updates,
             transactions,
-            tree: None,
+            aggregator_signature: None,
+            tree: RwLock::new(None),
         }
     }
 
@@ -41,33 +92,234 @@ impl PlasmaBlock {
         &self.transactions
     }
 
+    /// Sorts transactions by `(range.start, tx_hash)` so that
+    /// blocks made from the same transactions are identical.
+    pub fn reorder_transactions(&mut self) {
+        self.transactions.sort_by_cached_key(|tx| {
+            (
+                tx.transaction.get_range().get_start(),
+                get_transaction_hash(tx),
+            )
+        });
+    }
+
+    /// Merges adjacent state updates which have the same deposit contract, block number and property
+    /// so that the tree has fewer leaves. It should be called before `merkelize`.
+    pub fn merge_state_updates(&mut self) {
+        self.state_updates
+            .sort_by_key(|s| (s.get_deposit_contract_address(), s.get_range().get_start()));
+        let mut merged: Vec<StateUpdate> = Vec::with_capacity(self.state_updates.len());
+        for state_update in self.state_updates.drain(..) {
+            if let Some(last) = merged.last_mut() {
+                if last.get_deposit_contract_address()
+                    == state_update.get_deposit_contract_address()
+                    && last.get_block_number() == state_update.get_block_number()
+                    && last.get_property() == state_update.get_property()
+                    && last.get_range().get_end() == state_update.get_range().get_start()
+                {
+                    let range = Range::new(
+                        last.get_range().get_start(),
+                        state_update.get_range().get_end(),
+                    );
+                    last.set_range(range);
+                    continue;
+                }
+            }
+            merged.push(state_update);
+        }
+        self.state_updates = merged;
+        *self.tree.write().unwrap() = None;
+    }
+
     pub fn get_root(&self) -> Option<Bytes> {
-        if let Some(tree) = &self.tree {
+        if let Some(tree) = &*self.tree.read().unwrap() {
             Some(tree.get_root())
         } else {
             None
         }
     }
 
+    /// Returns keccak256 of ABI encoded block number and state root.
+    /// Panics if the block has not been merkelized.
+    pub fn get_block_hash(&self) -> H256 {
+        let root = self
+            .get_root()
+            .expect("block must be merkelized before computing block hash");
+        self.get_block_hash_with_root(&root)
+    }
+
+    fn get_block_hash_with_root(&self, root: &Bytes) -> H256 {
+        let encoded = ethabi::encode(&[
+            Token::Uint(self.block_number.0.into()),
+            Token::FixedBytes(root.to_vec()),
+        ]);
+        let mut keccak = Keccak::new_keccak256();
+        keccak.update(&encoded);
+        let mut res: [u8; 32] = [0; 32];
+        keccak.finalize(&mut res);
+        H256::from(res)
+    }
+
+    /// Returns header of the block. The state root is zero if the block has no state updates.
+    /// PlasmaBlock doesn't record time, so the timestamp is 0.
+    pub fn header(&self) -> BlockHeader {
+        let state_root = self
+            .get_root()
+            .or_else(|| self.compute_state_root().ok())
+            .map_or_else(H256::zero, |root| H256::from_slice(&root));
+        BlockHeader::new(
+            self.block_number.0,
+            state_root,
+            0,
+            self.aggregator_signature.clone().unwrap_or_default(),
+        )
+    }
+
+    pub fn get_aggregator_signature(&self) -> Option<&Bytes> {
+        self.aggregator_signature.as_ref()
+    }
+
+    /// Signs the block hash with `key` of the aggregator.
+    /// Panics if the block has not been merkelized.
+    pub fn sign(&mut self, key: &SecretKey) {
+        let block_hash = Bytes::from(self.get_block_hash().as_bytes());
+        self.aggregator_signature = Some(SignVerifier::sign(key, &block_hash));
+    }
+
+    /// Returns true if the block is signed by `aggregator_address`.
+    /// The root is computed if the block has not been merkelized.
+    pub fn verify_aggregator_signature(&self, aggregator_address: Address) -> bool {
+        let root = match self.get_root().or_else(|| self.compute_state_root().ok()) {
+            Some(root) => root,
+            None => return false,
+        };
+        match &self.aggregator_signature {
+            Some(signature) => SignVerifier::verify(
+                &Bytes::from(self.get_block_hash_with_root(&root).as_bytes()),
+                signature,
+                aggregator_address,
+            ),
+            None => false,
+        }
+    }
+
     pub fn get_inclusion_proof_with_index(&self, address: Address, index: usize) -> Option<Bytes> {
-        if let Some(tree) = &self.tree {
+        if let Some(tree) = &*self.tree.read().unwrap() {
             Some(tree.get_inclusion_proof(address, index))
         } else {
             None
         }
     }
 
-    pub fn get_inclusion_proof(&self, state_update: StateUpdate) -> Option<Bytes> {
-        // TODO: we shoud use tree.get_index(data)
-        if let Some(index) = self
+    /// Returns the state update of the leaf which contains `state_update`.
+    /// It differs from `state_update` if `merge_state_updates` merged it with adjacent ones.
+    pub fn get_leaf_state_update(
+        &self,
+        state_update: &StateUpdate,
+        deposit_address: Address,
+    ) -> Option<&StateUpdate> {
+        self.state_updates.iter().find(|s| {
+            s.get_deposit_contract_address() == deposit_address
+                && (s.get_hash() == state_update.get_hash()
+                    || (s.get_deposit_contract_address()
+                        == state_update.get_deposit_contract_address()
+                        && s.get_block_number() == state_update.get_block_number()
+                        && s.get_property() == state_update.get_property()
+                        && s.get_range().is_subrange(&state_update.get_range())))
+        })
+    }
+
+    /// Returns inclusion proof of `state_update` under `deposit_address`.
+    /// The proof is of the leaf of `get_leaf_state_update`, so merged state updates are proven
+    /// with the merged one. The tree is built at the first call and cached.
+    pub fn get_inclusion_proof(
+        &self,
+        state_update: &StateUpdate,
+        deposit_address: Address,
+    ) -> Result<Bytes, ProofError> {
+        let leaf_state_update = self
+            .get_leaf_state_update(state_update, deposit_address)
+            .ok_or(ProofError::StateUpdateNotInBlock)?;
+        if self.tree.read().unwrap().is_none() {
+            let tree = self.build_tree().ok_or(ProofError::EmptyBlock)?;
+            *self.tree.write().unwrap() = Some(tree);
+        }
+        let leaf = DoubleLayerTreeLeaf::from_state_update(leaf_state_update, deposit_address);
+        let tree = self.tree.read().unwrap();
+        let tree = tree.as_ref().ok_or(ProofError::EmptyBlock)?;
+        let index = tree.get_index(deposit_address, &leaf.data);
+        Ok(tree.get_inclusion_proof(deposit_address, index))
+    }
+
+    /// Generates receipt of the transaction whose hash is `tx_hash`.
+    /// The receipt has inclusion proof of the state update which covers the transaction's range.
+    pub fn generate_receipt(&self, tx_hash: H256) -> Result<Receipt, ProofError> {
+        let tx = self
+            .transactions
+            .iter()
+            .find(|tx| get_transaction_hash(tx).as_ref() == tx_hash.as_bytes())
+            .ok_or(ProofError::TransactionNotInBlock)?;
+        let range = tx.transaction.get_range();
+        let deposit_address = tx.transaction.get_deposit_contract_address();
+        let state_update = self
+            .state_updates
+            .iter()
+            .find(|s| {
+                s.get_deposit_contract_address() == deposit_address
+                    && s.get_range().is_subrange(&range)
+            })
+            .ok_or(ProofError::StateUpdateNotInBlock)?;
+        let inclusion_proof = self.get_inclusion_proof(state_update, deposit_address)?;
+        let root = self.get_root().ok_or(ProofError::EmptyBlock)?;
+        Ok(Receipt::new(
+            tx_hash,
+            self.block_number,
+            inclusion_proof,
+            H256::from_slice(&root),
+        ))
+    }
+
+    /// Returns the first state update under `deposit_address` after `range`.
+    /// State updates don't need to be sorted.
+    pub fn get_next_state_update(
+        &self,
+        deposit_address: Address,
+        range: Range,
+    ) -> Result<&StateUpdate, ProofError> {
+        let mut next: Option<&StateUpdate> = None;
+        for state_update in self
             .state_updates
             .iter()
-            .position(|s| s.get_hash() == state_update.get_hash())
+            .filter(|s| s.get_deposit_contract_address() == deposit_address)
         {
-            self.get_inclusion_proof_with_index(state_update.get_deposit_contract_address(), index)
-        } else {
-            None
+            let su_range = state_update.get_range();
+            if su_range.intersection(&range).is_some() {
+                return Err(ProofError::RangeIncluded);
+            }
+            if range.get_end() <= su_range.get_start()
+                && next.map_or(true, |n| su_range.get_start() < n.get_range().get_start())
+            {
+                next = Some(state_update);
+            }
         }
+        next.ok_or(ProofError::StateUpdateNotInBlock)
+    }
+
+    /// Generates exclusion proof of `range` under `deposit_address`.
+    /// The proof is the inclusion proof of the first state update after `range`
+    /// if no state update of the block intersects `range`.
+    pub fn get_exclusion_proof(
+        &self,
+        deposit_address: Address,
+        range: Range,
+    ) -> Result<ExclusionProof, ProofError> {
+        let state_update = self.get_next_state_update(deposit_address, range)?;
+        let merkle_proof = self.get_inclusion_proof(state_update, deposit_address)?;
+        Ok(ExclusionProof::new(
+            self.block_number.0,
+            DoubleLayerTreeLeaf::from_state_update(state_update, deposit_address),
+            merkle_proof,
+        ))
     }
 
     pub fn get_plasma_data_block(&self, root: Bytes, state_update: StateUpdate) -> PlasmaDataBlock {
@@ -81,26 +333,34 @@ impl PlasmaBlock {
         )
     }
 
-    pub fn merkelize(&mut self) -> Result<Bytes, Error> {
+    fn build_tree(&self) -> Option<DoubleLayerTree> {
         if self.state_updates.is_empty() {
-            return Err(Error::from(ErrorKind::MerkelizingError));
-        }
-        let mut leaves = vec![];
-        for s in self.state_updates.iter() {
-            leaves.push(DoubleLayerTreeLeaf {
-                address: s.get_deposit_contract_address(),
-                end: s.get_range().get_end(),
-                data: Bytes::from(s.to_abi()),
-            });
+            return None;
         }
+        let leaves: Vec<DoubleLayerTreeLeaf> = self
+            .state_updates
+            .iter()
+            .map(|s| DoubleLayerTreeLeaf::from_state_update(s, s.get_deposit_contract_address()))
+            .collect();
+        Some(DoubleLayerTree::generate(&leaves))
+    }
 
-        let tree = DoubleLayerTree::generate(&leaves);
-        self.tree = Some(tree);
-        if let Some(root) = self.get_root() {
-            Ok(root)
-        } else {
-            Err(Error::from(ErrorKind::MerkelizingError))
-        }
+    /// Computes the root of state updates without caching the tree.
+    /// Blocks received from others can be verified without being mutated.
+    pub fn compute_state_root(&self) -> Result<Bytes, Error> {
+        self.build_tree()
+            .map(|tree| tree.get_root())
+            .ok_or_else(|| Error::from(ErrorKind::MerkelizingError))
+    }
+
+    /// Computes the root and caches the tree for inclusion proofs.
+    pub fn merkelize(&mut self) -> Result<Bytes, Error> {
+        let tree = self
+            .build_tree()
+            .ok_or_else(|| Error::from(ErrorKind::MerkelizingError))?;
+        let root = tree.get_root();
+        *self.tree.write().unwrap() = Some(tree);
+        Ok(root)
     }
 }
 
@@ -120,6 +380,11 @@ impl Encodable for PlasmaBlock {
                     .map(|t| Token::Bytes(t.to_abi()))
                     .collect(),
             ),
+            Token::Bytes(
+                self.aggregator_signature
+                    .as_ref()
+                    .map_or(vec![], |signature| signature.to_vec()),
+            ),
         ]
     }
 }
@@ -130,9 +395,18 @@ impl Decodable for PlasmaBlock {
         let block_number = tuple[0].clone().to_uint();
         let state_updates = tuple[1].clone().to_array();
         let transactions = tuple[2].clone().to_array();
-        if let (Some(block_number), Some(state_updates), Some(transactions)) =
-            (block_number, state_updates, transactions)
-        {
+        let aggregator_signature = tuple[3].clone().to_bytes();
+        if let (
+            Some(block_number),
+            Some(state_updates),
+            Some(transactions),
+            Some(aggregator_signature),
+        ) = (
+            block_number,
+            state_updates,
+            transactions,
+            aggregator_signature,
+        ) {
             let state_updates: Result<Vec<_>, _> = state_updates
                 .iter()
                 .map(|s| {
@@ -158,7 +432,12 @@ impl Decodable for PlasmaBlock {
                     block_number: Integer(block_number.as_u64()),
                     state_updates: s,
                     transactions: t,
-                    tree: None,
+                    aggregator_signature: if aggregator_signature.is_empty() {
+                        None
+                    } else {
+                        Some(Bytes::from(aggregator_signature))
+                    },
+                    tree: RwLock::new(None),
                 })
             } else {
                 Err(PlasmaCoreError::from(PlasmaCoreErrorKind::AbiDecode))
@@ -168,11 +447,434 @@ impl Decodable for PlasmaBlock {
         }
     }
 
+    /// Decodes legacy ABI which doesn't have `aggregator_signature` as unsigned block.
+    fn from_abi(data: &[u8]) -> Result<Self, PlasmaCoreError> {
+        // Offset of `state_updates` points right after the head, which has 3 words in legacy ABI.
+        let is_legacy =
+            data.len() >= 64 && U256::from_big_endian(&data[32..64]) == U256::from(3 * 32);
+        let mut param_types = Self::get_param_types();
+        if is_legacy {
+            param_types.pop();
+        }
+        let mut tuple = ethabi::decode(&param_types, data)?;
+        if is_legacy {
+            tuple.push(Token::Bytes(vec![]));
+        }
+        Self::from_tuple(&tuple)
+    }
+
     fn get_param_types() -> Vec<ParamType> {
         vec![
             ParamType::Uint(64),
             ParamType::Array(Box::new(ParamType::Bytes)),
             ParamType::Array(Box::new(ParamType::Bytes)),
+            ParamType::Bytes,
         ]
     }
 }
+
+/// Blocks are compared only by block number so that `Vec<PlasmaBlock>` can be sorted.
+impl PartialEq for PlasmaBlock {
+    fn eq(&self, other: &Self) -> bool {
+        self.block_number == other.block_number
+    }
+}
+
+/// Note that this is ordering-only equality, not content equality.
+/// Compare `to_abi()` to check contents of blocks.
+impl Eq for PlasmaBlock {}
+
+impl PartialOrd for PlasmaBlock {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PlasmaBlock {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.get_block_number().cmp(&other.get_block_number())
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl plasma_core::schemars::JsonSchema for PlasmaBlock {
+    fn schema_name() -> String {
+        "PlasmaBlock".to_owned()
+    }
+    fn json_schema(
+        gen: &mut plasma_core::schemars::gen::SchemaGenerator,
+    ) -> plasma_core::schemars::schema::Schema {
+        use plasma_core::data_structure::schema::{array_schema, hex_schema, object_schema};
+        use plasma_core::data_structure::Transaction;
+        object_schema(vec![
+            ("block_number", gen.subschema_for::<u64>()),
+            (
+                "state_updates",
+                array_schema(gen.subschema_for::<StateUpdate>()),
+            ),
+            (
+                "transactions",
+                array_schema(object_schema(vec![
+                    (
+                        "prev_state_block_numbers",
+                        array_schema(gen.subschema_for::<u64>()),
+                    ),
+                    ("transaction", gen.subschema_for::<Transaction>()),
+                ])),
+            ),
+            (
+                "aggregator_signature",
+                hex_schema(
+                    "65 bytes hex-encoded signature of the aggregator. Empty if it isn't signed",
+                    None,
+                ),
+            ),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ovm::types::Property;
+    use plasma_core::data_structure::{Metadata, Range, Transaction};
+
+    fn create_tx(start: u64, end: u64, signature: &'static str) -> NewTransactionEvent {
+        NewTransactionEvent::new(
+            vec![Integer::new(0)],
+            Transaction::new(
+                Address::zero(),
+                Range::new(start, end),
+                Bytes::default(),
+                Bytes::from(signature),
+                Metadata::default(),
+            ),
+        )
+    }
+
+    #[test]
+    fn test_reorder_transactions() {
+        let state_updates = vec![StateUpdate::new(
+            Integer::new(1),
+            Address::zero(),
+            Range::new(0, 100),
+            Property::new(Address::zero(), vec![]),
+        )];
+        let txs = vec![
+            create_tx(50, 100, "a"),
+            create_tx(0, 10, "b"),
+            create_tx(0, 10, "c"),
+        ];
+        let mut reversed_txs = txs.clone();
+        reversed_txs.reverse();
+        let mut block1 = PlasmaBlock::new(1, state_updates.clone(), txs);
+        let mut block2 = PlasmaBlock::new(1, state_updates, reversed_txs);
+        block1.reorder_transactions();
+        block2.reorder_transactions();
+
+        assert_eq!(block1.to_abi(), block2.to_abi());
+        assert_eq!(
+            block1.get_transactions()[2].transaction.get_range(),
+            Range::new(50, 100)
+        );
+        assert_eq!(block1.merkelize().unwrap(), block2.merkelize().unwrap());
+    }
+
+    #[test]
+    fn test_merge_state_updates() {
+        let state_update = |start: u64, end: u64, owner: u64| {
+            StateUpdate::new(
+                Integer::new(1),
+                Address::zero(),
+                Range::new(start, end),
+                Property::new(Address::from_low_u64_be(owner), vec![]),
+            )
+        };
+        let mut block = PlasmaBlock::new(
+            1,
+            vec![
+                state_update(10, 20, 1),
+                state_update(0, 10, 1),
+                state_update(20, 30, 2),
+                state_update(40, 50, 2),
+            ],
+            vec![],
+        );
+        block.merge_state_updates();
+        assert_eq!(
+            block.get_state_updates(),
+            &[
+                state_update(0, 20, 1),
+                state_update(20, 30, 2),
+                state_update(40, 50, 2)
+            ][..]
+        );
+    }
+
+    #[test]
+    fn test_generate_receipt() {
+        let tx = create_tx(0, 50, "a");
+        let block = PlasmaBlock::new(
+            2,
+            vec![StateUpdate::new(
+                Integer::new(2),
+                Address::zero(),
+                Range::new(0, 100),
+                Property::new(Address::zero(), vec![]),
+            )],
+            vec![tx.clone()],
+        );
+        let tx_hash = H256::from_slice(&get_transaction_hash(&tx));
+        let receipt = block.generate_receipt(tx_hash).unwrap();
+        assert_eq!(receipt.get_transaction_hash(), tx_hash);
+        assert_eq!(receipt.get_block_number(), Integer::new(2));
+        assert_eq!(
+            Bytes::from(receipt.get_state_root().as_bytes()),
+            block.compute_state_root().unwrap()
+        );
+        assert_eq!(
+            block.generate_receipt(H256::zero()),
+            Err(ProofError::TransactionNotInBlock)
+        );
+    }
+
+    #[test]
+    fn test_get_block_hash() {
+        let create_block = || {
+            let mut block = PlasmaBlock::new(
+                1,
+                vec![StateUpdate::new(
+                    Integer::new(1),
+                    Address::zero(),
+                    Range::new(0, 100),
+                    Property::new(Address::zero(), vec![]),
+                )],
+                vec![],
+            );
+            block.merkelize().unwrap();
+            block
+        };
+        let block = create_block();
+        assert_eq!(block.get_block_hash(), block.get_block_hash());
+        assert_eq!(block.get_block_hash(), create_block().get_block_hash());
+        let mut other = PlasmaBlock::new(
+            2,
+            vec![StateUpdate::new(
+                Integer::new(2),
+                Address::zero(),
+                Range::new(0, 100),
+                Property::new(Address::zero(), vec![]),
+            )],
+            vec![],
+        );
+        other.merkelize().unwrap();
+        assert_ne!(block.get_block_hash(), other.get_block_hash());
+    }
+
+    #[test]
+    fn test_sign_and_verify_aggregator_signature() {
+        let raw_key =
+            hex::decode("c87509a1c067bbde78beb793e6fa76530b6382a4c0241e5e4a9ec0a0f44dc0d3")
+                .unwrap();
+        let secret_key = SecretKey::from_raw(&raw_key).unwrap();
+        let aggregator_address: Address = secret_key.public().address().into();
+        let mut block = PlasmaBlock::new(
+            1,
+            vec![StateUpdate::new(
+                Integer::new(1),
+                Address::zero(),
+                Range::new(0, 100),
+                Property::new(Address::zero(), vec![]),
+            )],
+            vec![],
+        );
+        block.merkelize().unwrap();
+        assert!(!block.verify_aggregator_signature(aggregator_address));
+        block.sign(&secret_key);
+        assert!(block.verify_aggregator_signature(aggregator_address));
+        assert!(!block.verify_aggregator_signature(Address::zero()));
+        let decoded = PlasmaBlock::from_abi(&block.to_abi()).unwrap();
+        assert!(decoded.verify_aggregator_signature(aggregator_address));
+        let header = block.header();
+        assert_eq!(header.get_block_number(), 1);
+        assert_eq!(
+            header.get_state_root().as_bytes(),
+            &block.get_root().unwrap()[..]
+        );
+        assert_eq!(
+            Some(header.get_aggregator_signature()),
+            block.get_aggregator_signature()
+        );
+    }
+
+    #[test]
+    fn test_from_abi_with_legacy_abi() {
+        let state_update = StateUpdate::new(
+            Integer::new(1),
+            Address::zero(),
+            Range::new(0, 100),
+            Property::new(Address::zero(), vec![]),
+        );
+        let legacy_encoded = ethabi::encode(&[
+            Token::Uint(1.into()),
+            Token::Array(vec![Token::Bytes(state_update.to_abi())]),
+            Token::Array(vec![Token::Bytes(create_tx(0, 100, "a").to_abi())]),
+        ]);
+        let decoded = PlasmaBlock::from_abi(&legacy_encoded).unwrap();
+        assert_eq!(decoded.get_block_number(), 1);
+        assert_eq!(decoded.get_state_updates().len(), 1);
+        assert_eq!(decoded.get_transactions().len(), 1);
+        assert_eq!(decoded.get_aggregator_signature(), None);
+    }
+
+    #[test]
+    fn test_compute_state_root() {
+        let block = PlasmaBlock::new(
+            1,
+            vec![StateUpdate::new(
+                Integer::new(1),
+                Address::zero(),
+                Range::new(0, 100),
+                Property::new(Address::zero(), vec![]),
+            )],
+            vec![],
+        );
+        let root = block.compute_state_root().unwrap();
+        assert!(block.get_root().is_none());
+        let mut block = block;
+        assert_eq!(block.merkelize().unwrap(), root);
+        assert!(PlasmaBlock::new(1, vec![], vec![])
+            .compute_state_root()
+            .is_err());
+    }
+
+    #[test]
+    fn test_sort_blocks() {
+        let mut blocks = vec![
+            PlasmaBlock::new(3, vec![], vec![]),
+            PlasmaBlock::new(1, vec![], vec![create_tx(0, 10, "a")]),
+            PlasmaBlock::new(2, vec![], vec![]),
+        ];
+        blocks.sort();
+        let block_numbers: Vec<u64> = blocks.iter().map(|b| b.get_block_number()).collect();
+        assert_eq!(block_numbers, vec![1, 2, 3]);
+        assert!(
+            PlasmaBlock::new(1, vec![], vec![])
+                == PlasmaBlock::new(1, vec![], vec![create_tx(0, 10, "a")])
+        );
+    }
+
+    #[test]
+    fn test_get_inclusion_proof() {
+        let deposit_address = Address::from_low_u64_be(1);
+        let state_updates: Vec<StateUpdate> = (0..3)
+            .map(|i| {
+                StateUpdate::new(
+                    Integer::new(1),
+                    deposit_address,
+                    Range::new(i * 10, (i + 1) * 10),
+                    Property::new(Address::zero(), vec![]),
+                )
+            })
+            .collect();
+        let block = PlasmaBlock::new(1, state_updates.clone(), vec![]);
+        let proof = block
+            .get_inclusion_proof(&state_updates[1], deposit_address)
+            .unwrap();
+        let root = block.get_root().unwrap();
+        let leaf = DoubleLayerTreeLeaf::from_state_update(&state_updates[1], deposit_address);
+        assert!(DoubleLayerTree::verify(&leaf, proof, &root));
+
+        let other = StateUpdate::new(
+            Integer::new(1),
+            deposit_address,
+            Range::new(30, 40),
+            Property::new(Address::zero(), vec![]),
+        );
+        assert_eq!(
+            block.get_inclusion_proof(&other, deposit_address),
+            Err(ProofError::StateUpdateNotInBlock)
+        );
+
+        // state update of each transaction is proven with the merged leaf
+        let mut merged = PlasmaBlock::new(1, state_updates.clone(), vec![]);
+        merged.merge_state_updates();
+        let root = merged.merkelize().unwrap();
+        let leaf_state_update = merged
+            .get_leaf_state_update(&state_updates[1], deposit_address)
+            .unwrap();
+        assert_eq!(leaf_state_update.get_range(), Range::new(0, 30));
+        let proof = merged
+            .get_inclusion_proof(&state_updates[1], deposit_address)
+            .unwrap();
+        let leaf = DoubleLayerTreeLeaf::from_state_update(leaf_state_update, deposit_address);
+        assert!(DoubleLayerTree::verify(&leaf, proof, &root));
+    }
+
+    #[test]
+    fn test_get_exclusion_proof() {
+        let deposit_address = Address::from_low_u64_be(1);
+        let state_updates: Vec<StateUpdate> = vec![(0, 10), (20, 30)]
+            .into_iter()
+            .map(|(start, end)| {
+                StateUpdate::new(
+                    Integer::new(1),
+                    deposit_address,
+                    Range::new(start, end),
+                    Property::new(Address::zero(), vec![]),
+                )
+            })
+            .collect();
+        let block = PlasmaBlock::new(1, state_updates.clone(), vec![]);
+        let proof = block
+            .get_exclusion_proof(deposit_address, Range::new(12, 18))
+            .unwrap();
+        assert_eq!(proof.block_number, 1);
+        assert_eq!(proof.leaf.end, 30);
+        let root = block.get_root().unwrap();
+        assert!(DoubleLayerTree::verify(
+            &proof.leaf,
+            proof.merkle_proof,
+            &root
+        ));
+        assert_eq!(
+            block
+                .get_exclusion_proof(deposit_address, Range::new(5, 15))
+                .unwrap_err(),
+            ProofError::RangeIncluded
+        );
+        assert_eq!(
+            block
+                .get_exclusion_proof(deposit_address, Range::new(30, 40))
+                .unwrap_err(),
+            ProofError::StateUpdateNotInBlock
+        );
+    }
+
+    #[test]
+    fn test_get_next_state_update_of_unsorted_block() {
+        let deposit_address = Address::from_low_u64_be(1);
+        let state_updates: Vec<StateUpdate> = vec![(40, 50), (20, 30), (0, 10)]
+            .into_iter()
+            .map(|(start, end)| {
+                StateUpdate::new(
+                    Integer::new(1),
+                    deposit_address,
+                    Range::new(start, end),
+                    Property::new(Address::zero(), vec![]),
+                )
+            })
+            .collect();
+        let block = PlasmaBlock::new(1, state_updates, vec![]);
+        let next = block
+            .get_next_state_update(deposit_address, Range::new(12, 18))
+            .unwrap();
+        assert_eq!(next.get_range(), Range::new(20, 30));
+        assert_eq!(
+            block
+                .get_next_state_update(deposit_address, Range::new(45, 60))
+                .unwrap_err(),
+            ProofError::RangeIncluded
+        );
+    }
+}
@@ -1,48 +1,140 @@
-use super::command::{Command, NewTransactionEvent};
-use super::plasma_block::PlasmaBlock;
+use super::command::{
+    Command, InclusionProofResponse, LatencyPing, NewTransactionEvent, PingRequest,
+};
+use super::error::{Error, ErrorKind, HealthError, PingError};
+use super::exit_db::{ExitClaim, ExitDb, ExitStatus};
+use super::plasma_block::{ExclusionProof, PlasmaBlock};
+use super::pubsub_auth::{connect_to_aggregator, SecretKeySigner};
 use super::state_db::StateDb;
 use super::token::Token;
 use super::utils::string_to_address;
 use super::wallet_manager::WalletManager;
 use abi_utils::{Decodable, Encodable};
 use bytes::Bytes;
+use contract_wrapper::commitment_contract_adaptor::CommitmentContractAdaptor;
+use contract_wrapper::error::{Error as ContractError, ErrorKind as ContractErrorKind};
 use contract_wrapper::plasma_contract_adaptor::PlasmaContractAdaptor;
 use ethabi::Contract as ContractABI;
 use ethabi::{Event, EventParam, ParamType};
-use ethereum_types::Address;
+use ethereum_types::{Address, H256};
 use ethsign::SecretKey;
-use event_watcher::event_db::EventDbImpl;
-use event_watcher::event_watcher::{EventHandler, EventWatcher, Log};
+use event_watcher::event_db::{EventDb, EventDbImpl};
+use event_watcher::event_watcher::{DecodedParam, EventHandler, EventWatcher, Log};
+use futures::sync::mpsc::{unbounded, UnboundedSender};
+use futures::{Future, IntoFuture, Stream};
+use merkle_interval_tree::{DoubleLayerTree, DoubleLayerTreeLeaf};
 use ovm::db::{RangeAtBlockDb, SignedByDb, TransactionDb, TransactionFilterBuilder};
 use ovm::deciders::SignVerifier;
 use ovm::property_executor::PropertyExecutor;
-use ovm::types::{Integer, Property, PropertyInput, StateUpdate};
+use ovm::types::{DoubleLayerTreeLeafExt, Integer, Property, PropertyInput, StateUpdate};
 use ovm::DeciderManager;
 use plasma_core::data_structure::{Metadata, Range, Transaction, TransactionParams};
 use plasma_db::impls::kvs::CoreDbLevelDbImpl;
 use plasma_db::traits::db::DatabaseTrait;
 use plasma_db::traits::kvs::KeyValueStore;
-use pubsub_messaging::{connect, Client as PubsubClient, ClientHandler, Message, Sender};
+use pubsub_messaging::{Client as PubsubClient, ClientHandler, CloseCode, Message, Sender};
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::BufReader;
+use std::sync::mpsc::{channel, Sender as ResponseSender};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Default endpoint of Ethereum JSON RPC.
+pub const DEFAULT_ETH_ENDPOINT: &str = "http://127.0.0.1:8545";
+/// Default path of commitment contract ABI.
+pub const DEFAULT_COMMITMENT_CONTRACT_ABI_PATH: &str = "../contract-wrapper/CommitmentChain.json";
+/// Default path of plasma contract ABI.
+pub const DEFAULT_PLASMA_CONTRACT_ABI_PATH: &str = "PlasmaContract.json";
 
 pub struct PlasmaClientShell {
     aggregator_endpoint: String,
     commitment_contract_address: Address,
+    eth_endpoint: String,
+    commitment_contract_abi_path: String,
+    /// Plasma contract which plasma chain ID is detected from. None if it isn't used.
+    plasma_contract_address: Option<Address>,
+    plasma_contract_abi_path: String,
+    /// Address of the aggregator which signs blocks.
+    aggregator_address: Address,
     controller: Option<PlasmaClientController>,
+    /// Connection kept alive while blocks are subscribed.
+    block_subscription: Option<PubsubClient>,
+    /// Signs challenges of the aggregator.
+    auth_signer: SecretKeySigner,
 }
 
 impl PlasmaClientShell {
-    pub fn new(aggregator_endpoint: String, commitment_contract_address: Address) -> Self {
+    pub fn new(
+        aggregator_endpoint: String,
+        commitment_contract_address: Address,
+        aggregator_address: Address,
+    ) -> Self {
         Self {
             aggregator_endpoint,
             commitment_contract_address,
+            eth_endpoint: DEFAULT_ETH_ENDPOINT.to_string(),
+            commitment_contract_abi_path: DEFAULT_COMMITMENT_CONTRACT_ABI_PATH.to_string(),
+            plasma_contract_address: None,
+            plasma_contract_abi_path: DEFAULT_PLASMA_CONTRACT_ABI_PATH.to_string(),
+            aggregator_address,
             controller: None,
+            block_subscription: None,
+            auth_signer: SecretKeySigner::random(),
         }
     }
 
+    /// Sets key which the client is authenticated by the aggregator with.
+    /// A random key is used if it isn't set.
+    pub fn set_auth_key(&mut self, secret_key: SecretKey) {
+        self.auth_signer = SecretKeySigner::new(secret_key);
+    }
+
+    /// Sets address of the aggregator. Blocks not signed by it are ignored.
+    pub fn set_aggregator_address(&mut self, aggregator_address: Address) {
+        self.aggregator_address = aggregator_address;
+        if let Some(controller) = &self.controller {
+            controller
+                .plasma_client
+                .lock()
+                .unwrap()
+                .set_aggregator_address(aggregator_address);
+        }
+    }
+
+    /// Sets endpoint of Ethereum JSON RPC which commitment contract and plasma contract are read from.
+    pub fn set_eth_endpoint(&mut self, eth_endpoint: &str) {
+        self.eth_endpoint = eth_endpoint.to_string();
+    }
+
+    /// Sets path of commitment contract ABI.
+    pub fn set_commitment_contract_abi_path(&mut self, path: &str) {
+        self.commitment_contract_abi_path = path.to_string();
+    }
+
+    /// Sets address of plasma contract. Plasma chain ID is detected from it on `connect`.
+    pub fn set_plasma_contract_address(&mut self, plasma_contract_address: Address) {
+        self.plasma_contract_address = Some(plasma_contract_address);
+    }
+
+    /// Sets path of plasma contract ABI.
+    pub fn set_plasma_contract_abi_path(&mut self, path: &str) {
+        self.plasma_contract_abi_path = path.to_string();
+    }
+
+    fn commitment_contract(&self) -> Result<CommitmentContractAdaptor, Error> {
+        let f = File::open(&self.commitment_contract_abi_path)?;
+        let reader = BufReader::new(f);
+        let contract_abi =
+            ContractABI::load(reader).map_err(|_| Error::from(ErrorKind::ContractError))?;
+        CommitmentContractAdaptor::new(
+            &self.eth_endpoint,
+            self.commitment_contract_address,
+            contract_abi,
+        )
+        .map_err(Into::into)
+    }
+
     /// Claim for ownership
     pub fn create_ownership_state_object(to_address: Address) -> Property {
         /*
@@ -55,7 +147,7 @@ impl PlasmaClientShell {
             ])),
             PropertyInput::ConstantBytes(Bytes::from("tx")),
             PropertyInput::ConstantProperty(DeciderManager::signed_by_decider(vec![
-                PropertyInput::ConstantAddress(to_address),
+                PropertyInput::from_address(to_address),
                 PropertyInput::Placeholder(Bytes::from("tx")),
             ])),
         ])
@@ -70,15 +162,13 @@ impl PlasmaClientShell {
          */
         DeciderManager::for_all_such_that_decider(
             // less than quantifier
-            DeciderManager::q_less_than(vec![PropertyInput::ConstantInteger(
-                specified_block_number,
-            )]),
+            DeciderManager::q_less_than(vec![PropertyInput::from_integer(specified_block_number)]),
             Bytes::from("block"),
             DeciderManager::for_all_such_that_decider(
                 // block range quantifier
                 DeciderManager::q_block(vec![
-                    PropertyInput::Placeholder(Bytes::from("block")),
-                    PropertyInput::ConstantRange(range),
+                    PropertyInput::placeholder("block"),
+                    PropertyInput::from_range(range),
                 ]),
                 Bytes::from("state_update"),
                 DeciderManager::is_deprecated(vec![PropertyInput::Placeholder(Bytes::from(
@@ -94,10 +184,55 @@ impl PlasmaClientShell {
         plasma_client.get_my_address(session)
     }
 
-    pub fn connect(&mut self) {
-        let plasma_client = PlasmaClient::<CoreDbLevelDbImpl>::new(Address::zero());
+    /// Stores exits of exit events emitted by plasma contract so that `list_exits` returns them.
+    pub fn watch_exits(&self) -> Result<(), Error> {
+        let controller = self
+            .controller
+            .clone()
+            .ok_or_else(|| Error::from(ErrorKind::ConnectionError))?;
+        let plasma_contract = controller.plasma_client.lock().unwrap().plasma_contract()?;
+        let db = EventDbImpl::from(CoreDbLevelDbImpl::open("exiteventdb"));
+        let (eloop, fetcher) = plasma_contract.listen_exit_events(db)?;
+        tokio::spawn(fetcher.for_each(move |logs| {
+            let _eloop = &eloop;
+            controller
+                .plasma_client
+                .lock()
+                .unwrap()
+                .handle_exit_events(&logs);
+            Ok(())
+        }));
+        Ok(())
+    }
+
+    /// Returns exits of the session's address which haven't been finalized.
+    pub fn list_exits(&self, session: &Bytes) -> Result<Vec<ExitClaim>, Error> {
+        let controller = self.controller.clone().unwrap();
+        let plasma_client = controller.plasma_client.lock().unwrap();
+        plasma_client.list_exits(session)
+    }
+
+    /// Connects to the aggregator and starts watching commitment contract.
+    /// Returns error if plasma chain ID can't be detected from the plasma contract set by
+    /// `set_plasma_contract_address`.
+    pub fn connect(&mut self) -> Result<(), Error> {
+        let mut plasma_client = match self.plasma_contract_address {
+            Some(plasma_contract_address) => PlasmaClient::<CoreDbLevelDbImpl>::new(
+                plasma_contract_address,
+                self.aggregator_address,
+                &self.eth_endpoint,
+                &self.plasma_contract_abi_path,
+            )?,
+            None => PlasmaClient::without_plasma_contract(self.aggregator_address),
+        };
+        plasma_client.set_auth_signer(self.auth_signer.clone());
         let controller = PlasmaClientController::new(plasma_client);
-        let pubsub_client = connect(self.aggregator_endpoint.clone(), controller.clone()).unwrap();
+        let pubsub_client = connect_to_aggregator(
+            self.aggregator_endpoint.clone(),
+            controller.clone(),
+            self.auth_signer.clone(),
+        )
+        .map_err(|_| Error::from(ErrorKind::ConnectionError))?;
         self.controller = Some(controller.clone_by_pubsub_client(pubsub_client));
         let abi: Vec<Event> = vec![Event {
             name: "BlockSubmitted".to_owned(),
@@ -118,13 +253,107 @@ impl PlasmaClientShell {
         let kvs = CoreDbLevelDbImpl::open("eventdb");
         let db = EventDbImpl::from(kvs);
         let watcher = EventWatcher::new(
-            "http://localhost:8545",
+            &self.eth_endpoint,
             self.commitment_contract_address,
             abi,
             db,
             self.controller.clone().unwrap(),
         );
         tokio::spawn(watcher);
+        Ok(())
+    }
+    /// Reconnects to the aggregator of `new_endpoint`.
+    /// Returns error and keeps the current connection if new aggregator is unreachable
+    /// or isn't on the same commitment contract.
+    pub fn switch_aggregator(&mut self, new_endpoint: &str) -> Result<(), Error> {
+        let controller = self
+            .controller
+            .clone()
+            .ok_or_else(|| Error::from(ErrorKind::ConnectionError))?;
+        verify_aggregator(
+            new_endpoint,
+            self.commitment_contract_address,
+            self.auth_signer.clone(),
+        )?;
+        let pubsub_client = connect_to_aggregator(
+            new_endpoint.to_string(),
+            controller.clone(),
+            self.auth_signer.clone(),
+        )
+        .map_err(|_| Error::from(ErrorKind::ConnectionError))?;
+        if let Some(old_pubsub_client) = controller.pubsub_client.clone() {
+            let _ = old_pubsub_client.sender.close(CloseCode::Normal);
+        }
+        self.aggregator_endpoint = new_endpoint.to_string();
+        self.controller = Some(controller.clone_by_pubsub_client(pubsub_client));
+        Ok(())
+    }
+    /// Subscribes to new blocks published by the aggregator.
+    /// Blocks not signed by the aggregator or whose root doesn't match the root
+    /// on commitment contract are dropped.
+    /// The stream fails if the root can't be fetched from commitment contract.
+    pub fn subscribe_to_blocks(
+        &mut self,
+    ) -> Result<impl Stream<Item = PlasmaBlock, Error = Error>, Error> {
+        let contract = Arc::new(self.commitment_contract()?);
+        let (sender, receiver) = unbounded();
+        let pubsub_client = connect_to_aggregator(
+            self.aggregator_endpoint.clone(),
+            BlockSubscriptionHandler {
+                sender,
+                aggregator_address: self.aggregator_address,
+            },
+            self.auth_signer.clone(),
+        )
+        .map_err(|_| Error::from(ErrorKind::ConnectionError))?;
+        if let Some(old_pubsub_client) = self.block_subscription.replace(pubsub_client) {
+            let _ = old_pubsub_client.sender.close(CloseCode::Normal);
+        }
+        Ok(filter_verified_blocks(
+            receiver.map_err(|_| Error::from(ErrorKind::ConnectionError)),
+            move |block_number| contract.get_root_async(block_number),
+        ))
+    }
+    /// Fetches inclusion proof of the state update in `range` at `block_number` from the aggregator.
+    /// The proof is verified with the root on commitment contract and stored locally.
+    pub fn request_inclusion_proof(&self, block_number: u64, range: Range) -> Result<Bytes, Error> {
+        let controller = self
+            .controller
+            .clone()
+            .ok_or_else(|| Error::from(ErrorKind::ConnectionError))?;
+        let response = controller.request_inclusion_proof(Integer(block_number), range)?;
+        let contract = self.commitment_contract()?;
+        let root = contract.get_root(block_number)?;
+        let plasma_client = controller.plasma_client.lock().unwrap();
+        plasma_client.store_inclusion_proof(&response, Bytes::from(root.as_bytes()))?;
+        Ok(response.inclusion_proof)
+    }
+
+    /// Requests exclusion proof of `range` at `block_number` to the aggregator
+    /// and verifies it with the root of commitment contract.
+    pub fn get_proof_of_exclusion(
+        &self,
+        block_number: u64,
+        range: Range,
+    ) -> Result<ExclusionProof, Error> {
+        let controller = self
+            .controller
+            .clone()
+            .ok_or_else(|| Error::from(ErrorKind::ConnectionError))?;
+        let deposit_contract_address = controller
+            .plasma_client
+            .lock()
+            .unwrap()
+            .deposit_contract_address;
+        let response = controller.request_exclusion_proof(
+            Integer(block_number),
+            deposit_contract_address,
+            range,
+        )?;
+        let contract = self.commitment_contract()?;
+        let root = contract.get_root(block_number)?;
+        let plasma_client = controller.plasma_client.lock().unwrap();
+        plasma_client.get_proof_of_exclusion(&response, Bytes::from(root.as_bytes()))
     }
     pub fn search_range(&self, deposit_contract_address: Address, amount: u64) -> Option<Range> {
         self.controller
@@ -153,16 +382,20 @@ impl PlasmaClientShell {
         end: u64,
         state_object: Property,
         metadata: Metadata,
-    ) {
+    ) -> Result<(), Error> {
         let deposit_contract_address = deposit_contract_address.unwrap_or_else(Address::zero);
         let controller = self.controller.clone().unwrap();
-        let tx = controller.plasma_client.lock().unwrap().create_transaction(
-            session,
-            deposit_contract_address,
-            Range::new(start, end),
-            Bytes::from(state_object.to_abi()),
-            metadata,
-        );
+        let tx = controller
+            .plasma_client
+            .lock()
+            .unwrap()
+            .create_transaction(
+                session,
+                deposit_contract_address,
+                Range::new(start, end),
+                Bytes::from(state_object.to_abi()),
+                metadata,
+            )?;
         let command = Command {
             command_type: Integer(0),
             body: Bytes::from(tx.to_abi()),
@@ -170,6 +403,7 @@ impl PlasmaClientShell {
         let mut pubsub_client = controller.pubsub_client.clone().unwrap();
         let msg = Message::new("Aggregator".to_string(), command.to_abi());
         pubsub_client.send(msg);
+        Ok(())
     }
     pub fn ownership_property(&self, session: &Bytes, to_address: Address) -> (Property, Metadata) {
         (
@@ -212,26 +446,24 @@ impl PlasmaClientShell {
         let controller = self.controller.clone().unwrap();
         let plasma_client = controller.plasma_client.lock().unwrap();
         let my_address = plasma_client.get_my_address(session).unwrap();
-        let balances: HashMap<Address, u64> = plasma_client
+        // state updates may overlap, so ranges are coalesced before summing up.
+        let ranges: HashMap<Address, Vec<Range>> = plasma_client
             .get_all_state_updates()
             .iter()
-            .filter(|s| {
-                let p = &s.get_property().inputs[2];
-                if let PropertyInput::ConstantProperty(signed_by) = p {
-                    if let PropertyInput::ConstantAddress(address) = signed_by.inputs[0] {
-                        return address == my_address;
-                    }
-                }
-                false
-            })
+            .filter(|s| s.get_owner() == Some(my_address))
             .fold(HashMap::new(), |mut acc, s| {
-                let deposit_contract = s.get_deposit_contract_address();
-                let b = acc.get(&deposit_contract).unwrap_or(&0);
-                let new_balance = b + s.get_range().get_end() - s.get_range().get_start();
-                acc.insert(deposit_contract, new_balance);
+                acc.entry(s.get_deposit_contract_address())
+                    .or_insert_with(Vec::new)
+                    .push(s.get_range());
                 acc
             });
-        balances
+        ranges
+            .into_iter()
+            .map(|(deposit_contract, ranges)| {
+                let balance = Range::coalesce(ranges).iter().map(|r| r.get_amount()).sum();
+                (deposit_contract, balance)
+            })
+            .collect()
     }
     pub fn get_related_transactions(&self, session: &Bytes) -> Vec<Transaction> {
         self.controller
@@ -260,10 +492,14 @@ impl PlasmaClientShell {
     }
 }
 
+type PendingProofRequest = (Integer, Range, ResponseSender<InclusionProofResponse>);
+
 #[derive(Clone)]
 pub struct PlasmaClientController {
     pub plasma_client: Arc<Mutex<PlasmaClient<CoreDbLevelDbImpl>>>,
     pub pubsub_client: Option<PubsubClient>,
+    pending_proof_requests: Arc<Mutex<Vec<PendingProofRequest>>>,
+    pending_exclusion_proof_requests: Arc<Mutex<Vec<PendingProofRequest>>>,
 }
 
 impl PlasmaClientController {
@@ -271,12 +507,78 @@ impl PlasmaClientController {
         Self {
             plasma_client: Arc::new(Mutex::new(plasma_client)),
             pubsub_client: None,
+            pending_proof_requests: Arc::new(Mutex::new(vec![])),
+            pending_exclusion_proof_requests: Arc::new(Mutex::new(vec![])),
         }
     }
     fn clone_by_pubsub_client(&self, pubsub_client: PubsubClient) -> Self {
         PlasmaClientController {
             plasma_client: self.plasma_client.clone(),
             pubsub_client: Some(pubsub_client),
+            pending_proof_requests: self.pending_proof_requests.clone(),
+            pending_exclusion_proof_requests: self.pending_exclusion_proof_requests.clone(),
+        }
+    }
+    /// Sends inclusion proof request and waits for the response.
+    fn request_inclusion_proof(
+        &self,
+        block_number: Integer,
+        range: Range,
+    ) -> Result<InclusionProofResponse, Error> {
+        let mut pubsub_client = self
+            .pubsub_client
+            .clone()
+            .ok_or_else(|| Error::from(ErrorKind::ConnectionError))?;
+        let (tx, rx) = channel();
+        self.pending_proof_requests
+            .lock()
+            .unwrap()
+            .push((block_number, range, tx));
+        let command = Command::create_inclusion_proof_request(block_number, range);
+        pubsub_client.send(Message::new("Aggregator".to_string(), command.to_abi()));
+        let response = rx.recv_timeout(Duration::from_secs(10));
+        self.pending_proof_requests
+            .lock()
+            .unwrap()
+            .retain(|(b, r, _)| *b != block_number || *r != range);
+        response.map_err(|_| Error::from(ErrorKind::ConnectionError))
+    }
+    /// Sends exclusion proof request and waits for the inclusion proof of the adjacent leaf.
+    fn request_exclusion_proof(
+        &self,
+        block_number: Integer,
+        deposit_contract_address: Address,
+        range: Range,
+    ) -> Result<InclusionProofResponse, Error> {
+        let mut pubsub_client = self
+            .pubsub_client
+            .clone()
+            .ok_or_else(|| Error::from(ErrorKind::ConnectionError))?;
+        let (tx, rx) = channel();
+        self.pending_exclusion_proof_requests
+            .lock()
+            .unwrap()
+            .push((block_number, range, tx));
+        let command =
+            Command::create_exclusion_proof_request(block_number, deposit_contract_address, range);
+        pubsub_client.send(Message::new("Aggregator".to_string(), command.to_abi()));
+        let response = rx.recv_timeout(Duration::from_secs(10));
+        self.pending_exclusion_proof_requests
+            .lock()
+            .unwrap()
+            .retain(|(b, r, _)| *b != block_number || *r != range);
+        response.map_err(|_| Error::from(ErrorKind::ConnectionError))
+    }
+    fn handle_proof_response(
+        pending_requests: &Mutex<Vec<PendingProofRequest>>,
+        response: InclusionProofResponse,
+    ) {
+        let pending_requests = pending_requests.lock().unwrap();
+        if let Some((_, _, tx)) = pending_requests
+            .iter()
+            .find(|(b, r, _)| *b == response.block_number && *r == response.range)
+        {
+            let _ = tx.send(response);
         }
     }
     fn fetch_block(&self, block_number: Integer) {
@@ -312,6 +614,16 @@ impl ClientHandler for PlasmaClientController {
         } else if command.command_type.0 == 4 {
             plasma_client
                 .handle_new_transaction(&NewTransactionEvent::from_abi(&command.body).unwrap());
+        } else if command.command_type.0 == 7 {
+            Self::handle_proof_response(
+                &self.pending_proof_requests,
+                InclusionProofResponse::from_abi(&command.body).unwrap(),
+            );
+        } else if command.command_type.0 == 11 {
+            Self::handle_proof_response(
+                &self.pending_exclusion_proof_requests,
+                InclusionProofResponse::from_abi(&command.body).unwrap(),
+            );
         } else {
             println!("undefined command type {:?}", command.command_type.0);
         }
@@ -320,6 +632,13 @@ impl ClientHandler for PlasmaClientController {
 
 impl EventHandler for PlasmaClientController {
     fn on_event(&self, log: &Log) {
+        if log.event_signature == state_update_event().signature() {
+            self.plasma_client
+                .lock()
+                .unwrap()
+                .handle_state_update_event(log);
+            return;
+        }
         let decoded_param = log.params.first().unwrap();
         println!(
             "block number is {:?}",
@@ -330,38 +649,499 @@ impl EventHandler for PlasmaClientController {
     }
 }
 
+/// Event emitted by plasma contract when state update is created.
+pub fn state_update_event() -> Event {
+    Event {
+        name: "StateUpdateCreated".to_owned(),
+        inputs: vec![EventParam {
+            name: "stateUpdate".to_owned(),
+            kind: ParamType::Bytes,
+            indexed: false,
+        }],
+        anonymous: false,
+    }
+}
+
+/// Event emitted by plasma contract when exit is started.
+pub fn exit_started_event() -> Event {
+    Event {
+        name: "ExitStarted".to_owned(),
+        inputs: vec![
+            EventParam {
+                name: "claimNonce".to_owned(),
+                kind: ParamType::Uint(256),
+                indexed: false,
+            },
+            EventParam {
+                name: "claimant".to_owned(),
+                kind: ParamType::Address,
+                indexed: false,
+            },
+            EventParam {
+                name: "blockNumber".to_owned(),
+                kind: ParamType::Uint(256),
+                indexed: false,
+            },
+            EventParam {
+                name: "start".to_owned(),
+                kind: ParamType::Uint(256),
+                indexed: false,
+            },
+            EventParam {
+                name: "end".to_owned(),
+                kind: ParamType::Uint(256),
+                indexed: false,
+            },
+        ],
+        anonymous: false,
+    }
+}
+
+/// Event emitted by plasma contract when exit is challenged.
+pub fn exit_challenged_event() -> Event {
+    claim_nonce_event("ExitChallenged")
+}
+
+/// Event emitted by plasma contract when exit is finalized.
+pub fn exit_finalized_event() -> Event {
+    claim_nonce_event("ExitFinalized")
+}
+
+fn claim_nonce_event(name: &str) -> Event {
+    Event {
+        name: name.to_owned(),
+        inputs: vec![EventParam {
+            name: "claimNonce".to_owned(),
+            kind: ParamType::Uint(256),
+            indexed: false,
+        }],
+        anonymous: false,
+    }
+}
+
+/// Decodes pending exit of ExitStarted event.
+fn decode_exit_started_event(params: &[DecodedParam]) -> Option<ExitClaim> {
+    let uint = |i: usize| {
+        params
+            .get(i)
+            .and_then(|p| p.token.clone().to_uint())
+            .map(|n| n.as_u64())
+    };
+    let claimant = params.get(1).and_then(|p| p.token.clone().to_address())?;
+    Some(ExitClaim::new(
+        Integer(uint(0)?),
+        claimant,
+        Integer(uint(2)?),
+        Range::new(uint(3)?, uint(4)?),
+        ExitStatus::Pending,
+    ))
+}
+
+/// Decodes state update of StateUpdateCreated event.
+fn decode_state_update_event(params: &[DecodedParam]) -> Option<StateUpdate> {
+    params
+        .first()
+        .and_then(|p| p.token.clone().to_bytes())
+        .and_then(|b| StateUpdate::from_abi(&b).ok())
+}
+
 /// Plasma Client on OVM.
 pub struct PlasmaClient<KVS: KeyValueStore> {
     deposit_contract_address: Address,
     decider: PropertyExecutor<KVS>,
+    my_address: Option<Address>,
+    /// Address of the aggregator which signs blocks.
+    aggregator_address: Address,
+    on_state_update_received: Option<Box<dyn Fn(StateUpdate) + Send>>,
+    /// Endpoint of Ethereum JSON RPC which plasma contract is read from.
+    eth_endpoint: String,
+    plasma_contract_abi_path: String,
+    /// Chain ID read from plasma contract. None if the client doesn't use plasma contract.
+    plasma_chain_id: Option<u64>,
+    /// Signs challenges of the aggregator.
+    auth_signer: SecretKeySigner,
+}
+
+/// Result of `PlasmaClient::health_check`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HealthStatus {
+    pub eth_node: bool,
+    pub aggregator: bool,
+    pub contract_deployed: bool,
+    /// 0 if ethereum node is unreachable.
+    pub current_eth_block: u64,
+}
+
+impl HealthStatus {
+    pub fn is_healthy(&self) -> bool {
+        self.eth_node && self.aggregator && self.contract_deployed
+    }
+
+    /// Returns the first failed check as error.
+    pub fn into_result(self) -> Result<Self, HealthError> {
+        if !self.eth_node {
+            Err(HealthError::EthNodeUnreachable)
+        } else if !self.aggregator {
+            Err(HealthError::AggregatorUnreachable)
+        } else if !self.contract_deployed {
+            Err(HealthError::ContractNotDeployed)
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+/// Forwards timestamps of latency pongs to `ping_aggregator`.
+#[derive(Clone)]
+struct PingHandler {
+    sender: Arc<Mutex<ResponseSender<Integer>>>,
+}
+
+impl ClientHandler for PingHandler {
+    fn handle_message(&self, msg: Message, _sender: Sender) {
+        if let Ok(command) = Command::from_abi(&msg.message) {
+            if command.command_type.0 == 9 {
+                if let Ok(pong) = LatencyPing::from_abi(&command.body) {
+                    let _ = self.sender.lock().unwrap().send(pong.timestamp);
+                }
+            }
+        }
+    }
+}
+
+/// Forwards commitment contract address of ping response to `verify_aggregator`.
+/// None is sent if the aggregator closes connection.
+#[derive(Clone)]
+struct PingResponseHandler {
+    sender: Arc<Mutex<ResponseSender<Option<Address>>>>,
+}
+
+impl ClientHandler for PingResponseHandler {
+    fn handle_message(&self, msg: Message, _sender: Sender) {
+        if let Ok(command) = Command::from_abi(&msg.message) {
+            if command.command_type.0 == 12 {
+                if let Ok(response) = PingRequest::from_abi(&command.body) {
+                    let _ = self
+                        .sender
+                        .lock()
+                        .unwrap()
+                        .send(Some(response.commitment_contract_address));
+                }
+            }
+        }
+    }
+    fn handle_close(&self) {
+        let _ = self.sender.lock().unwrap().send(None);
+    }
+}
+
+/// Sends ping request to the aggregator of `aggregator_endpoint` and waits for the response.
+/// Returns error if the aggregator isn't on `commitment_contract_address`.
+fn verify_aggregator(
+    aggregator_endpoint: &str,
+    commitment_contract_address: Address,
+    auth_signer: SecretKeySigner,
+) -> Result<(), Error> {
+    let (tx, rx) = channel();
+    let mut pubsub_client = connect_to_aggregator(
+        aggregator_endpoint.to_string(),
+        PingResponseHandler {
+            sender: Arc::new(Mutex::new(tx)),
+        },
+        auth_signer,
+    )
+    .map_err(|_| Error::from(ErrorKind::ConnectionError))?;
+    let command = Command::create_ping_request(commitment_contract_address);
+    pubsub_client.send(Message::new("Aggregator".to_string(), command.to_abi()));
+    let result = rx.recv_timeout(PING_TIMEOUT);
+    let _ = pubsub_client.sender.close(CloseCode::Normal);
+    match result {
+        Ok(Some(address)) if address == commitment_contract_address => Ok(()),
+        Ok(_) => Err(Error::from(ErrorKind::CommitmentContractMismatch)),
+        Err(_) => Err(Error::from(ErrorKind::ConnectionError)),
+    }
+}
+
+/// Time to wait for latency pong from the aggregator.
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
+/// Latency of the aggregator above which `health_check` warns.
+const SLOW_AGGREGATOR_LATENCY: Duration = Duration::from_millis(500);
+
+/// Forwards blocks received from the aggregator to the subscription stream.
+#[derive(Clone)]
+struct BlockSubscriptionHandler {
+    sender: UnboundedSender<PlasmaBlock>,
+    aggregator_address: Address,
+}
+
+impl ClientHandler for BlockSubscriptionHandler {
+    fn handle_message(&self, msg: Message, _sender: Sender) {
+        if let Ok(command) = Command::from_abi(&msg.message) {
+            if command.command_type.0 == 3 {
+                if let Ok(block) = PlasmaBlock::from_abi(&command.body) {
+                    if block.verify_aggregator_signature(self.aggregator_address) {
+                        let _ = self.sender.unbounded_send(block);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Drops blocks whose root doesn't match the root returned by `get_root`.
+/// Blocks not submitted yet are also dropped, and other errors of `get_root` fail the stream.
+fn filter_verified_blocks<S, F, R>(
+    blocks: S,
+    get_root: F,
+) -> impl Stream<Item = PlasmaBlock, Error = Error>
+where
+    S: Stream<Item = PlasmaBlock, Error = Error>,
+    F: Fn(u64) -> R,
+    R: IntoFuture<Item = H256, Error = ContractError>,
+{
+    blocks
+        .and_then(move |block| {
+            get_root(block.get_block_number())
+                .into_future()
+                .then(move |result| match result {
+                    Ok(root) => {
+                        let is_valid = block
+                            .compute_state_root()
+                            .map(|computed_root| computed_root == Bytes::from(root.as_bytes()))
+                            .unwrap_or(false);
+                        Ok(if is_valid { Some(block) } else { None })
+                    }
+                    Err(e) => match e.kind() {
+                        ContractErrorKind::RootNotFound => Ok(None),
+                        _ => Err(Error::from(e)),
+                    },
+                })
+        })
+        .filter_map(|block| block)
 }
 
 impl<KVS: KeyValueStore + DatabaseTrait> PlasmaClient<KVS> {
-    pub fn new(deposit_contract_address: Address) -> Self {
+    /// Creates client on plasma contract of `deposit_contract_address` which accepts only blocks
+    /// signed by `aggregator_address`. Plasma chain ID is read from the contract through
+    /// `eth_endpoint` with the ABI of `plasma_contract_abi_path`.
+    pub fn new(
+        deposit_contract_address: Address,
+        aggregator_address: Address,
+        eth_endpoint: &str,
+        plasma_contract_abi_path: &str,
+    ) -> Result<Self, Error> {
+        let mut plasma_client = Self::create(
+            deposit_contract_address,
+            aggregator_address,
+            eth_endpoint,
+            plasma_contract_abi_path,
+        );
+        plasma_client.detect_plasma_chain_id()?;
+        Ok(plasma_client)
+    }
+
+    /// Creates client which doesn't read plasma contract, so plasma chain ID is unknown.
+    pub fn without_plasma_contract(aggregator_address: Address) -> Self {
+        Self::create(
+            Address::zero(),
+            aggregator_address,
+            DEFAULT_ETH_ENDPOINT,
+            DEFAULT_PLASMA_CONTRACT_ABI_PATH,
+        )
+    }
+
+    fn create(
+        deposit_contract_address: Address,
+        aggregator_address: Address,
+        eth_endpoint: &str,
+        plasma_contract_abi_path: &str,
+    ) -> Self {
         PlasmaClient {
             deposit_contract_address,
             decider: Default::default(),
+            my_address: None,
+            aggregator_address,
+            on_state_update_received: None,
+            eth_endpoint: eth_endpoint.to_string(),
+            plasma_contract_abi_path: plasma_contract_abi_path.to_string(),
+            plasma_chain_id: None,
+            auth_signer: SecretKeySigner::random(),
         }
     }
 
-    /// Deposit to plasma contract
-    /// Send ethereum transaction to Plasma Deposit Contract.
-    /// amount: amount to deposit
-    /// property: initial state object
-    pub fn deposit(&self, session: &Bytes, amount: u64, property: Property) {
-        // TODO: add PlasmaContractABI
-        let f = File::open("PlasmaContract.json").unwrap();
-        let reader = BufReader::new(f);
-        let contract_abi = ContractABI::load(reader).unwrap();
+    /// Creates client like `new` after checking ethereum node, aggregator and plasma contract.
+    pub fn new_with_health_check(
+        deposit_contract_address: Address,
+        aggregator_address: Address,
+        eth_endpoint: &str,
+        plasma_contract_abi_path: &str,
+        aggregator_endpoint: &str,
+    ) -> Result<Self, HealthError> {
+        let mut plasma_client = Self::create(
+            deposit_contract_address,
+            aggregator_address,
+            eth_endpoint,
+            plasma_contract_abi_path,
+        );
+        plasma_client
+            .health_check(aggregator_endpoint)?
+            .into_result()?;
+        plasma_client
+            .detect_plasma_chain_id()
+            .map_err(|_| HealthError::PlasmaChainIdNotDetected)?;
+        Ok(plasma_client)
+    }
+
+    /// Returns plasma chain ID. None if the client doesn't use plasma contract.
+    pub fn get_plasma_chain_id(&self) -> Option<u64> {
+        self.plasma_chain_id
+    }
+
+    fn detect_plasma_chain_id(&mut self) -> Result<u64, Error> {
+        let plasma_chain_id = self.plasma_contract()?.get_plasma_chain_id()?;
+        self.plasma_chain_id = Some(plasma_chain_id);
+        Ok(plasma_chain_id)
+    }
+
+    fn plasma_contract(&self) -> Result<PlasmaContractAdaptor, Error> {
+        let f = File::open(&self.plasma_contract_abi_path)?;
+        let contract_abi = ContractABI::load(BufReader::new(f))
+            .map_err(|_| Error::from(ErrorKind::ContractError))?;
+        PlasmaContractAdaptor::new(
+            &self.eth_endpoint,
+            &self.deposit_contract_address.to_string(),
+            contract_abi,
+        )
+        .map_err(Into::into)
+    }
+
+    /// Sets address of the aggregator. Blocks not signed by it are ignored.
+    pub fn set_aggregator_address(&mut self, aggregator_address: Address) {
+        self.aggregator_address = aggregator_address;
+    }
+
+    /// Sets signer which the client is authenticated by the aggregator with.
+    pub fn set_auth_signer(&mut self, auth_signer: SecretKeySigner) {
+        self.auth_signer = auth_signer;
+    }
+
+    /// Checks that ethereum node is reachable, the aggregator of `aggregator_endpoint` is reachable
+    /// and plasma contract is deployed. Should be called before `deposit` or sending transactions.
+    pub fn health_check(&self, aggregator_endpoint: &str) -> Result<HealthStatus, HealthError> {
+        let f = File::open(&self.plasma_contract_abi_path)
+            .map_err(|_| HealthError::ContractAbiNotFound)?;
+        let contract_abi =
+            ContractABI::load(BufReader::new(f)).map_err(|_| HealthError::ContractAbiNotFound)?;
         let plasma_contract = PlasmaContractAdaptor::new(
-            "http://127.0.0.1:9545",
+            &self.eth_endpoint,
             &self.deposit_contract_address.to_string(),
             contract_abi,
         )
-        .unwrap();
-        // TODO: handle result
-        let _result =
-            plasma_contract.deposit(self.get_my_address(session).unwrap(), amount, property);
+        .map_err(|_| HealthError::InvalidEndpoint)?;
+        let current_eth_block = plasma_contract.get_eth_block_number().ok();
+        let contract_deployed = plasma_contract.is_deployed().unwrap_or(false);
+        let aggregator = match self.ping_aggregator(aggregator_endpoint) {
+            Ok(latency) => {
+                if latency > SLOW_AGGREGATOR_LATENCY {
+                    println!("warning: aggregator latency is {:?}", latency);
+                }
+                true
+            }
+            Err(_) => false,
+        };
+        Ok(HealthStatus {
+            eth_node: current_eth_block.is_some(),
+            aggregator,
+            contract_deployed,
+            current_eth_block: current_eth_block.unwrap_or(0),
+        })
+    }
+
+    /// Sends latency ping with current timestamp to the aggregator of `aggregator_endpoint`
+    /// and returns round-trip time until the pong reflecting the timestamp is received.
+    pub fn ping_aggregator(&self, aggregator_endpoint: &str) -> Result<Duration, PingError> {
+        self.ping_aggregator_with_timeout(aggregator_endpoint, PING_TIMEOUT)
+    }
+
+    fn ping_aggregator_with_timeout(
+        &self,
+        aggregator_endpoint: &str,
+        timeout: Duration,
+    ) -> Result<Duration, PingError> {
+        let (tx, rx) = channel();
+        let mut pubsub_client = connect_to_aggregator(
+            aggregator_endpoint.to_string(),
+            PingHandler {
+                sender: Arc::new(Mutex::new(tx)),
+            },
+            self.auth_signer.clone(),
+        )
+        .map_err(|_| PingError::Unreachable)?;
+        let timestamp = Integer(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+        );
+        let started_at = Instant::now();
+        pubsub_client.send(Message::new(
+            "Aggregator".to_string(),
+            Command::create_latency_ping(timestamp).to_abi(),
+        ));
+        let result = rx.recv_timeout(timeout);
+        let latency = started_at.elapsed();
+        let _ = pubsub_client.sender.close(CloseCode::Normal);
+        match result {
+            Ok(reflected) if reflected == timestamp => Ok(latency),
+            Ok(_) => Err(PingError::UnexpectedResponse),
+            Err(_) => Err(PingError::Timeout(timeout)),
+        }
+    }
+
+    /// Registers callback called when state update owned by the session's address is received.
+    pub fn set_on_state_update_received(&mut self, callback: Box<dyn Fn(StateUpdate) + Send>) {
+        self.on_state_update_received = Some(callback);
+    }
+
+    /// Subscribes state update event of plasma contract for the session's address.
+    pub fn watch_state_updates<T, E>(&mut self, session: &Bytes, watcher: &mut EventWatcher<T, E>)
+    where
+        T: EventDb + Send + Sync,
+        E: EventHandler,
+    {
+        self.my_address = self.get_my_address(session);
+        watcher.subscribe(state_update_event());
+    }
+
+    /// Handles StateUpdateCreated event.
+    /// Stores state update and calls callback if it is owned by watching address.
+    pub fn handle_state_update_event(&self, log: &Log) {
+        if let Some(state_update) = decode_state_update_event(&log.params) {
+            self.handle_created_state_update(state_update);
+        }
+    }
+
+    fn handle_created_state_update(&self, state_update: StateUpdate) {
+        if self.my_address.is_none() || state_update.get_owner() != self.my_address {
+            return;
+        }
+        self.update_state_updates(vec![state_update.clone()]);
+        if let Some(callback) = &self.on_state_update_received {
+            callback(state_update);
+        }
+    }
+
+    /// Deposit to plasma contract
+    /// Send ethereum transaction to Plasma Deposit Contract.
+    /// amount: amount to deposit
+    /// property: initial state object
+    pub fn deposit(&self, session: &Bytes, amount: u64, property: Property) -> Result<H256, Error> {
+        let my_address = self
+            .get_my_address(session)
+            .ok_or_else(|| Error::from(ErrorKind::InvalidParameter))?;
+        self.plasma_contract()?
+            .deposit(my_address, amount, property)
+            .map_err(Into::into)
     }
 
     /// Creates new account
@@ -384,6 +1164,8 @@ impl<KVS: KeyValueStore + DatabaseTrait> PlasmaClient<KVS> {
 
     /// Create transaction to update state for specific coin range.
     /// TODO: maybe need to specify Property for how state transition works.
+    /// The nonce follows the last transaction of the session's address accepted by the aggregator.
+    /// Returns error if the session has no secret key.
     pub fn create_transaction(
         &self,
         session: &Bytes,
@@ -391,18 +1173,40 @@ impl<KVS: KeyValueStore + DatabaseTrait> PlasmaClient<KVS> {
         range: Range,
         parameters: Bytes,
         metadata: Metadata,
-    ) -> Transaction {
+    ) -> Result<Transaction, Error> {
+        let wallet = WalletManager::new(self.decider.get_db());
+        let secret_key = wallet
+            .get_key(session)
+            .ok_or_else(|| Error::from(ErrorKind::InvalidParameter))?;
+        let nonce = wallet.get_next_nonce(secret_key.public().address().into())?;
         let transaction_params =
-            TransactionParams::new(deposit_contract_address, range, parameters);
+            TransactionParams::new(deposit_contract_address, range, parameters).with_nonce(nonce);
+        let signature = SignVerifier::sign(&secret_key, &Bytes::from(transaction_params.to_abi()));
+        Ok(Transaction::from_params(
+            transaction_params,
+            signature,
+            metadata,
+        ))
+    }
 
-        let wallet = WalletManager::new(self.decider.get_db());
-        if let Some(secret_key) = wallet.get_key(session) {
-            let signature =
-                SignVerifier::sign(&secret_key, &Bytes::from(transaction_params.to_abi()));
-            Transaction::from_params(transaction_params, signature, metadata)
-        } else {
-            panic!("secret key not found");
-        }
+    /// Creates signed transaction which transfers ownership of `range` to `recipient`.
+    pub fn create_transfer_transaction(
+        &self,
+        session: &Bytes,
+        range: Range,
+        recipient: Address,
+    ) -> Result<Transaction, Error> {
+        let state_object = PlasmaClientShell::create_ownership_state_object(recipient);
+        let my_address = self
+            .get_my_address(session)
+            .ok_or_else(|| Error::from(ErrorKind::InvalidParameter))?;
+        self.create_transaction(
+            session,
+            self.deposit_contract_address,
+            range,
+            Bytes::from(state_object.to_abi()),
+            Metadata::new(my_address, recipient),
+        )
     }
 
     /// Start exit on plasma. return exit property
@@ -412,35 +1216,207 @@ impl<KVS: KeyValueStore + DatabaseTrait> PlasmaClient<KVS> {
         PlasmaClientShell::create_checkpoint_property(block_number, range)
     }
 
+    /// Handles exit events fetched by `PlasmaContractAdaptor::listen_exit_events`.
+    /// ExitStarted events are handled first so that an exit challenged or finalized
+    /// in the same batch has its status updated.
+    pub fn handle_exit_events(&self, logs: &[Log]) {
+        self.handle_decoded_exit_events(
+            logs.iter()
+                .map(|log| (log.event_signature, &log.params[..]))
+                .collect(),
+        )
+    }
+
+    /// Handles pairs of event signature and params of exit events.
+    fn handle_decoded_exit_events(&self, events: Vec<(H256, &[DecodedParam])>) {
+        let (started, others): (Vec<_>, Vec<_>) = events
+            .into_iter()
+            .partition(|(signature, _)| *signature == exit_started_event().signature());
+        for (signature, params) in started.into_iter().chain(others) {
+            if let Err(e) = self.handle_exit_event(signature, params) {
+                println!("failed to handle exit event: {}", e);
+            }
+        }
+    }
+
+    fn handle_exit_event(&self, signature: H256, params: &[DecodedParam]) -> Result<(), Error> {
+        let exit_db = ExitDb::new(self.decider.get_db());
+        if signature == exit_started_event().signature() {
+            let exit = decode_exit_started_event(params)
+                .ok_or_else(|| Error::from(ErrorKind::InvalidParameter))?;
+            // the same event can be fetched again, and it must not reset the status
+            if exit_db.get_exit(exit.claim_nonce)?.is_none() {
+                exit_db.put_exit(&exit)?;
+            }
+            return Ok(());
+        }
+        let status = if signature == exit_challenged_event().signature() {
+            ExitStatus::Challenged
+        } else if signature == exit_finalized_event().signature() {
+            ExitStatus::Finalized
+        } else {
+            return Ok(());
+        };
+        let claim_nonce = params
+            .first()
+            .and_then(|p| p.token.clone().to_uint())
+            .ok_or_else(|| Error::from(ErrorKind::InvalidParameter))?;
+        exit_db.update_status(Integer(claim_nonce.as_u64()), status)?;
+        Ok(())
+    }
+
+    /// Returns status of the exit of `claim_nonce`. None if the exit isn't stored.
+    pub fn get_exit_status(&self, claim_nonce: Integer) -> Result<Option<ExitStatus>, Error> {
+        Ok(ExitDb::new(self.decider.get_db())
+            .get_exit(claim_nonce)?
+            .map(|exit| exit.status))
+    }
+
+    /// Returns exits of the session's address which haven't been finalized, sorted by claim nonce.
+    pub fn list_exits(&self, session: &Bytes) -> Result<Vec<ExitClaim>, Error> {
+        let claimant = self
+            .get_my_address(session)
+            .ok_or_else(|| Error::from(ErrorKind::InvalidParameter))?;
+        let exits = ExitDb::new(self.decider.get_db()).get_exits()?;
+        Ok(exits
+            .into_iter()
+            .filter(|exit| exit.claimant == claimant && exit.status != ExitStatus::Finalized)
+            .collect())
+    }
+
+    pub fn list_exits_page(
+        &self,
+        session: &Bytes,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<ExitClaim>, Error> {
+        Ok(self
+            .list_exits(session)?
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .collect())
+    }
+
     /// Handle exit on plasma.
     /// After dispute period, withdraw from Plasma Contract.
-    pub fn finalize_exit(&self, session: &Bytes, state_update: StateUpdate, range: Range) {
-        // TODO: add PlasmaContractABI
-        let f = File::open("PlasmaContract.json").unwrap();
-        let reader = BufReader::new(f);
-        let contract_abi = ContractABI::load(reader).unwrap();
-        let plasma_contract = PlasmaContractAdaptor::new(
-            "http://127.0.0.1:9545",
-            &self.deposit_contract_address.to_string(),
-            contract_abi,
-        )
-        .unwrap();
+    /// Exits of `range` are marked as finalized only if withdrawal succeeded.
+    pub fn finalize_exit(
+        &self,
+        session: &Bytes,
+        state_update: StateUpdate,
+        range: Range,
+    ) -> Result<(), Error> {
+        let my_address = self
+            .get_my_address(session)
+            .ok_or_else(|| Error::from(ErrorKind::InvalidParameter))?;
+        let plasma_contract = self.plasma_contract()?;
 
         // TODO: create checkpoint struct
         // TODO: decide check point is exitable
         let checkpoint = (state_update, range);
+        plasma_contract.withdraw(my_address, checkpoint)?;
 
-        // TODO: handle result
-        let _result = plasma_contract.withdraw(self.get_my_address(session).unwrap(), checkpoint);
+        self.mark_exits_finalized(my_address, range)
+    }
+
+    fn mark_exits_finalized(&self, claimant: Address, range: Range) -> Result<(), Error> {
+        let exit_db = ExitDb::new(self.decider.get_db());
+        for exit in exit_db
+            .get_exits()?
+            .into_iter()
+            .filter(|exit| exit.claimant == claimant && exit.range == range)
+        {
+            exit_db.update_status(exit.claim_nonce, ExitStatus::Finalized)?;
+        }
+        Ok(())
     }
 
     /// Challenge to specific exit by claiming contradicting statement.
     pub fn challenge(&self) {}
 
+    /// Verifies inclusion proof received from aggregator with `root` and stores it.
+    pub fn store_inclusion_proof(
+        &self,
+        response: &InclusionProofResponse,
+        root: Bytes,
+    ) -> Result<(), Error> {
+        let plasma_data_block = &response.plasma_data_block;
+        if *plasma_data_block.get_root() != root {
+            return Err(Error::from(ErrorKind::InvalidParameter));
+        }
+        let state_update = StateUpdate::from_abi(plasma_data_block.get_data())?;
+        let leaf = DoubleLayerTreeLeaf::from_state_update(
+            &state_update,
+            plasma_data_block.get_deposit_contract_address(),
+        );
+        if !DoubleLayerTree::verify(&leaf, response.inclusion_proof.clone(), &root) {
+            return Err(Error::from(ErrorKind::InvalidParameter));
+        }
+        RangeAtBlockDb::new(self.decider.get_range_db())
+            .store_witness(
+                root,
+                response.inclusion_proof.clone(),
+                plasma_data_block.clone(),
+            )
+            .map_err(|_| Error::from(ErrorKind::PlasmaDbError))
+    }
+
+    /// Generates exclusion proof of `response.range` from the inclusion proof of the adjacent leaf
+    /// received from the aggregator. The inclusion proof is verified with `root` and stored.
+    pub fn get_proof_of_exclusion(
+        &self,
+        response: &InclusionProofResponse,
+        root: Bytes,
+    ) -> Result<ExclusionProof, Error> {
+        let range = response.range;
+        let range_at_block_db = RangeAtBlockDb::new(self.decider.get_range_db());
+        if range_at_block_db
+            .get_witnesses(response.block_number, range)
+            .map_err(|_| Error::from(ErrorKind::PlasmaDbError))?
+            .iter()
+            .any(|record| {
+                record.plasma_data_block.get_deposit_contract_address()
+                    == self.deposit_contract_address
+            })
+        {
+            return Err(Error::from(ErrorKind::InvalidParameter));
+        }
+        let plasma_data_block = &response.plasma_data_block;
+        if plasma_data_block.get_deposit_contract_address() != self.deposit_contract_address
+            || plasma_data_block.get_updated_range().get_start() < range.get_end()
+        {
+            return Err(Error::from(ErrorKind::InvalidParameter));
+        }
+        self.store_inclusion_proof(response, root)?;
+        let state_update = StateUpdate::from_abi(plasma_data_block.get_data())?;
+        Ok(ExclusionProof::new(
+            response.block_number.0,
+            DoubleLayerTreeLeaf::from_state_update(&state_update, self.deposit_contract_address),
+            response.inclusion_proof.clone(),
+        ))
+    }
+
+    /// Returns true if state updates of `block` produce `root`.
+    /// `block` isn't mutated so that it can be verified before being stored.
+    pub fn verify_block(&self, block: &PlasmaBlock, root: &Bytes) -> bool {
+        block
+            .compute_state_root()
+            .map(|computed_root| &computed_root == root)
+            .unwrap_or(false)
+    }
+
     /// Handle BlockSubmitted Event from aggregator
     /// check new state update and verify, store them.
     pub fn handle_new_block(&self, mut block: PlasmaBlock) {
         println!("handle_new_block {:?}", block.get_block_number());
+        if !block.verify_aggregator_signature(self.aggregator_address) {
+            println!(
+                "block {} isn't signed by the aggregator",
+                block.get_block_number()
+            );
+            return;
+        }
         // println!("handle_new_block {:?} {:?}", block.get_block_number(), block.get_state_updates());
         let range_db = self.decider.get_range_db();
         let range_at_block_db = RangeAtBlockDb::new(range_db);
@@ -452,7 +1428,9 @@ impl<KVS: KeyValueStore + DatabaseTrait> PlasmaClient<KVS> {
             assert!(range_at_block_db
                 .store_witness(
                     root.clone(),
-                    block.get_inclusion_proof(s.clone()).unwrap(),
+                    block
+                        .get_inclusion_proof(s, s.get_deposit_contract_address())
+                        .unwrap(),
                     block.get_plasma_data_block(root.clone(), s.clone()),
                 )
                 .is_ok());
@@ -461,6 +1439,7 @@ impl<KVS: KeyValueStore + DatabaseTrait> PlasmaClient<KVS> {
             for previous_block_number in tx.clone().prev_state_block_numbers {
                 transaction_db.put_transaction(previous_block_number.0, tx.transaction.clone());
             }
+            self.record_nonce(&tx.transaction);
             let message = Bytes::from(tx.transaction.to_body_abi());
             assert!(signed_by_db
                 .store_witness(
@@ -506,6 +1485,19 @@ impl<KVS: KeyValueStore + DatabaseTrait> PlasmaClient<KVS> {
         for previous_block_number in event.clone().prev_state_block_numbers {
             transaction_db.put_transaction(previous_block_number.0, event.transaction.clone());
         }
+        self.record_nonce(&event.transaction);
+    }
+
+    /// Records nonce of `transaction` accepted by the aggregator so that the next transaction
+    /// of the same sender follows it.
+    fn record_nonce(&self, transaction: &Transaction) {
+        let mut wallet = WalletManager::new(self.decider.get_db());
+        if let Err(e) = wallet.record_nonce(
+            transaction.get_metadata().get_from(),
+            transaction.get_nonce(),
+        ) {
+            println!("failed to record nonce: {}", e);
+        }
     }
 
     pub fn insert_test_ranges(&mut self) {
@@ -586,3 +1578,500 @@ impl<KVS: KeyValueStore + DatabaseTrait> PlasmaClient<KVS> {
         transaction_db.query_transaction(filter).unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plasma::pubsub_auth::{AddressAuthenticator, AGGREGATOR_SERVER_ID};
+    use crate::plasma::PlasmaAggregator;
+    use bincode::serialize;
+    use futures::stream;
+    use plasma_core::data_structure::Nonce;
+    use plasma_db::impls::kvs::CoreDbMemoryImpl;
+    use pubsub_messaging::{spawn_server_with_auth, ServerHandler, WsMessage};
+    use std::thread::sleep;
+
+    /// Answers ping requests like the aggregator on `commitment_contract_address`.
+    #[derive(Clone)]
+    struct PingServerHandler {
+        commitment_contract_address: Address,
+    }
+
+    impl ServerHandler for PingServerHandler {
+        fn handle_message(&mut self, msg: Message, sender: Sender) {
+            let command = Command::from_abi(&msg.message).unwrap();
+            if command.command_type.0 != 5 {
+                return;
+            }
+            let request = PingRequest::from_abi(&command.body).unwrap();
+            if request.commitment_contract_address != self.commitment_contract_address {
+                let _ = sender.close(CloseCode::Policy);
+                return;
+            }
+            let response = Message::new(
+                "BROADCAST".to_owned(),
+                Command::create_ping_response(self.commitment_contract_address).to_abi(),
+            );
+            let _ = sender.send(WsMessage::Binary(serialize(&response).unwrap()));
+        }
+    }
+
+    fn create_block(block_number: u64) -> PlasmaBlock {
+        PlasmaBlock::new(
+            block_number,
+            vec![StateUpdate::new(
+                Integer::new(block_number),
+                Address::zero(),
+                Range::new(0, 100),
+                Property::new(Address::zero(), vec![]),
+            )],
+            vec![],
+        )
+    }
+
+    #[test]
+    fn test_decode_state_update_event() {
+        let state_update = StateUpdate::new(
+            Integer::new(1),
+            Address::zero(),
+            Range::new(0, 100),
+            PlasmaClientShell::create_ownership_state_object(Address::zero()),
+        );
+        let params = vec![DecodedParam {
+            event_param: state_update_event().inputs[0].clone(),
+            token: ethabi::Token::Bytes(state_update.to_abi()),
+        }];
+        assert_eq!(decode_state_update_event(&params), Some(state_update));
+        assert_eq!(decode_state_update_event(&[]), None);
+    }
+
+    #[test]
+    fn test_handle_created_state_update() {
+        let mut plasma_client: PlasmaClient<CoreDbMemoryImpl> =
+            PlasmaClient::without_plasma_contract(Address::zero());
+        let received = Arc::new(Mutex::new(vec![]));
+        let received_by_callback = received.clone();
+        plasma_client.set_on_state_update_received(Box::new(move |state_update| {
+            received_by_callback.lock().unwrap().push(state_update)
+        }));
+        let my_address = Address::from_low_u64_be(1);
+        let create_state_update = |owner: Address, start: u64, end: u64| {
+            StateUpdate::new(
+                Integer::new(1),
+                Address::zero(),
+                Range::new(start, end),
+                PlasmaClientShell::create_ownership_state_object(owner),
+            )
+        };
+        // ignored until the address is watched
+        plasma_client.handle_created_state_update(create_state_update(my_address, 0, 10));
+        assert!(received.lock().unwrap().is_empty());
+
+        plasma_client.my_address = Some(my_address);
+        plasma_client.handle_created_state_update(create_state_update(Address::zero(), 0, 10));
+        assert!(received.lock().unwrap().is_empty());
+        let state_update = create_state_update(my_address, 10, 20);
+        plasma_client.handle_created_state_update(state_update.clone());
+        assert_eq!(*received.lock().unwrap(), vec![state_update.clone()]);
+        assert!(plasma_client
+            .get_all_state_updates()
+            .contains(&state_update));
+    }
+
+    #[test]
+    fn test_create_transfer_transaction() {
+        let plasma_client: PlasmaClient<CoreDbMemoryImpl> =
+            PlasmaClient::without_plasma_contract(Address::zero());
+        let (session, secret_key) = plasma_client.create_account();
+        let sender = Address::from(secret_key.public().address());
+        let recipient = Address::from_low_u64_be(1);
+        let tx = plasma_client
+            .create_transfer_transaction(&session, Range::new(0, 10), recipient)
+            .unwrap();
+        assert_eq!(tx.get_range(), Range::new(0, 10));
+        assert_eq!(tx.get_metadata().get_from(), sender);
+        assert!(SignVerifier::verify(
+            &Bytes::from(tx.to_body_abi()),
+            tx.get_signature(),
+            sender
+        ));
+        // unknown session has no secret key
+        assert!(plasma_client
+            .create_transfer_transaction(&Bytes::from("unknown"), Range::new(0, 10), recipient)
+            .is_err());
+    }
+
+    #[test]
+    fn test_nonce_follows_accepted_transaction() {
+        let plasma_client: PlasmaClient<CoreDbMemoryImpl> =
+            PlasmaClient::without_plasma_contract(Address::zero());
+        let (session, _) = plasma_client.create_account();
+        let recipient = Address::from_low_u64_be(1);
+        let tx = plasma_client
+            .create_transfer_transaction(&session, Range::new(0, 10), recipient)
+            .unwrap();
+        assert_eq!(tx.get_nonce(), Nonce(0));
+        // nonce doesn't advance until the aggregator accepts the transaction
+        let tx = plasma_client
+            .create_transfer_transaction(&session, Range::new(0, 10), recipient)
+            .unwrap();
+        assert_eq!(tx.get_nonce(), Nonce(0));
+        plasma_client.handle_new_transaction(&NewTransactionEvent::new(vec![Integer(0)], tx));
+        let tx = plasma_client
+            .create_transfer_transaction(&session, Range::new(0, 10), recipient)
+            .unwrap();
+        assert_eq!(tx.get_nonce(), Nonce(1));
+    }
+
+    /// Creates event signature and params of exit event.
+    fn create_exit_event(event: Event, tokens: Vec<ethabi::Token>) -> (H256, Vec<DecodedParam>) {
+        (
+            event.signature(),
+            event
+                .inputs
+                .into_iter()
+                .zip(tokens)
+                .map(|(event_param, token)| DecodedParam { event_param, token })
+                .collect(),
+        )
+    }
+
+    fn create_exit_started_event(
+        claim_nonce: u64,
+        claimant: Address,
+        start: u64,
+        end: u64,
+    ) -> (H256, Vec<DecodedParam>) {
+        create_exit_event(
+            exit_started_event(),
+            vec![
+                ethabi::Token::Uint(claim_nonce.into()),
+                ethabi::Token::Address(claimant),
+                ethabi::Token::Uint(1.into()),
+                ethabi::Token::Uint(start.into()),
+                ethabi::Token::Uint(end.into()),
+            ],
+        )
+    }
+
+    fn handle_exit_events(
+        plasma_client: &PlasmaClient<CoreDbMemoryImpl>,
+        events: Vec<(H256, Vec<DecodedParam>)>,
+    ) {
+        plasma_client.handle_decoded_exit_events(
+            events
+                .iter()
+                .map(|(signature, params)| (*signature, &params[..]))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_handle_exit_events() {
+        let plasma_client: PlasmaClient<CoreDbMemoryImpl> =
+            PlasmaClient::without_plasma_contract(Address::zero());
+        let (session, secret_key) = plasma_client.create_account();
+        let claimant = Address::from(secret_key.public().address());
+        // challenged before ExitStarted in the same batch
+        handle_exit_events(
+            &plasma_client,
+            vec![
+                create_exit_event(exit_challenged_event(), vec![ethabi::Token::Uint(1.into())]),
+                create_exit_started_event(1, claimant, 100, 200),
+                create_exit_started_event(0, claimant, 0, 100),
+                create_exit_started_event(2, Address::zero(), 200, 300),
+            ],
+        );
+        assert_eq!(
+            plasma_client.get_exit_status(Integer(1)).unwrap(),
+            Some(ExitStatus::Challenged)
+        );
+        assert_eq!(plasma_client.get_exit_status(Integer(3)).unwrap(), None);
+        let exits = plasma_client.list_exits(&session).unwrap();
+        assert_eq!(
+            exits,
+            vec![
+                ExitClaim::new(
+                    Integer(0),
+                    claimant,
+                    Integer(1),
+                    Range::new(0, 100),
+                    ExitStatus::Pending
+                ),
+                ExitClaim::new(
+                    Integer(1),
+                    claimant,
+                    Integer(1),
+                    Range::new(100, 200),
+                    ExitStatus::Challenged
+                ),
+            ]
+        );
+
+        // fetched again, the status isn't reset
+        handle_exit_events(
+            &plasma_client,
+            vec![
+                create_exit_started_event(1, claimant, 100, 200),
+                create_exit_event(exit_finalized_event(), vec![ethabi::Token::Uint(0.into())]),
+            ],
+        );
+        assert_eq!(
+            plasma_client.get_exit_status(Integer(1)).unwrap(),
+            Some(ExitStatus::Challenged)
+        );
+        let exits = plasma_client.list_exits_page(&session, 0, 10).unwrap();
+        assert_eq!(exits.len(), 1);
+        assert_eq!(exits[0].claim_nonce, Integer(1));
+    }
+
+    #[test]
+    fn test_mark_exits_finalized() {
+        let plasma_client: PlasmaClient<CoreDbMemoryImpl> =
+            PlasmaClient::without_plasma_contract(Address::zero());
+        let claimant = Address::from_low_u64_be(1);
+        handle_exit_events(
+            &plasma_client,
+            vec![
+                create_exit_started_event(0, claimant, 0, 100),
+                create_exit_started_event(1, claimant, 100, 200),
+            ],
+        );
+        plasma_client
+            .mark_exits_finalized(claimant, Range::new(0, 100))
+            .unwrap();
+        assert_eq!(
+            plasma_client.get_exit_status(Integer(0)).unwrap(),
+            Some(ExitStatus::Finalized)
+        );
+        assert_eq!(
+            plasma_client.get_exit_status(Integer(1)).unwrap(),
+            Some(ExitStatus::Pending)
+        );
+    }
+
+    #[test]
+    fn test_get_proof_of_exclusion() {
+        let plasma_client: PlasmaClient<CoreDbMemoryImpl> =
+            PlasmaClient::without_plasma_contract(Address::zero());
+        let mut block = PlasmaBlock::new(
+            1,
+            vec![(0, 10), (20, 30)]
+                .into_iter()
+                .map(|(start, end)| {
+                    StateUpdate::new(
+                        Integer::new(1),
+                        Address::zero(),
+                        Range::new(start, end),
+                        Property::new(Address::zero(), vec![]),
+                    )
+                })
+                .collect(),
+            vec![],
+        );
+        let root = block.merkelize().unwrap();
+        let create_response = |range: Range, state_update: &StateUpdate| {
+            InclusionProofResponse::new(
+                Integer(1),
+                range,
+                block
+                    .get_inclusion_proof(state_update, Address::zero())
+                    .unwrap(),
+                block.get_plasma_data_block(root.clone(), state_update.clone()),
+            )
+        };
+        let next = block
+            .get_next_state_update(Address::zero(), Range::new(12, 18))
+            .unwrap();
+        let proof = plasma_client
+            .get_proof_of_exclusion(&create_response(Range::new(12, 18), next), root.clone())
+            .unwrap();
+        assert_eq!(proof.block_number, 1);
+        assert_eq!(proof.leaf.end, 30);
+        // the leaf before the range doesn't prove exclusion
+        let prev = &block.get_state_updates()[0];
+        assert!(plasma_client
+            .get_proof_of_exclusion(&create_response(Range::new(12, 18), prev), root.clone())
+            .is_err());
+    }
+
+    #[test]
+    fn test_detect_plasma_chain_id() {
+        let plasma_client: PlasmaClient<CoreDbMemoryImpl> =
+            PlasmaClient::without_plasma_contract(Address::zero());
+        assert_eq!(plasma_client.get_plasma_chain_id(), None);
+        // the ABI has no getPlasmaChainId
+        assert!(PlasmaClient::<CoreDbMemoryImpl>::new(
+            Address::zero(),
+            Address::zero(),
+            DEFAULT_ETH_ENDPOINT,
+            DEFAULT_COMMITMENT_CONTRACT_ABI_PATH,
+        )
+        .is_err());
+        assert!(PlasmaClient::<CoreDbMemoryImpl>::new(
+            Address::zero(),
+            Address::zero(),
+            DEFAULT_ETH_ENDPOINT,
+            "NotFound.json",
+        )
+        .is_err());
+    }
+
+    /// Answers latency pings with `aggregator`, or with a wrong timestamp if it's None.
+    #[derive(Clone)]
+    struct LatencyPingServerHandler {
+        aggregator: Option<Arc<Mutex<PlasmaAggregator<CoreDbMemoryImpl>>>>,
+    }
+
+    impl ServerHandler for LatencyPingServerHandler {
+        fn handle_message(&mut self, msg: Message, sender: Sender) {
+            let command = Command::from_abi(&msg.message).unwrap();
+            let pong = match &self.aggregator {
+                Some(aggregator) => aggregator
+                    .lock()
+                    .unwrap()
+                    .handle_latency_ping(&command.body)
+                    .unwrap(),
+                None => Command::create_latency_pong(Integer(0)),
+            };
+            let response = Message::new("BROADCAST".to_owned(), pong.to_abi());
+            let _ = sender.send(WsMessage::Binary(serialize(&response).unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_ping_aggregator() {
+        let aggregator = PlasmaAggregator::new(
+            Address::zero(),
+            Address::zero(),
+            Address::zero(),
+            "c87509a1c067bbde78beb793e6fa76530b6382a4c0241e5e4a9ec0a0f44dc0d3",
+        );
+        let _server = spawn_server_with_auth(
+            "127.0.0.1:18443".to_owned(),
+            AGGREGATOR_SERVER_ID.to_owned(),
+            LatencyPingServerHandler {
+                aggregator: Some(Arc::new(Mutex::new(aggregator))),
+            },
+            AddressAuthenticator::default(),
+        )
+        .unwrap();
+        let _wrong_server = spawn_server_with_auth(
+            "127.0.0.1:18444".to_owned(),
+            AGGREGATOR_SERVER_ID.to_owned(),
+            LatencyPingServerHandler { aggregator: None },
+            AddressAuthenticator::default(),
+        )
+        .unwrap();
+        // doesn't answer latency ping
+        let _silent_server = spawn_server_with_auth(
+            "127.0.0.1:18445".to_owned(),
+            AGGREGATOR_SERVER_ID.to_owned(),
+            PingServerHandler {
+                commitment_contract_address: Address::zero(),
+            },
+            AddressAuthenticator::default(),
+        )
+        .unwrap();
+        sleep(Duration::from_millis(100));
+        let plasma_client: PlasmaClient<CoreDbMemoryImpl> =
+            PlasmaClient::without_plasma_contract(Address::zero());
+        assert!(plasma_client.ping_aggregator("127.0.0.1:18443").is_ok());
+        assert_eq!(
+            plasma_client.ping_aggregator("127.0.0.1:18444"),
+            Err(PingError::UnexpectedResponse)
+        );
+        assert_eq!(
+            plasma_client
+                .ping_aggregator_with_timeout("127.0.0.1:18445", Duration::from_millis(200)),
+            Err(PingError::Timeout(Duration::from_millis(200)))
+        );
+    }
+
+    #[test]
+    fn test_verify_aggregator() {
+        let commitment_contract_address = Address::from_low_u64_be(1);
+        let _server = spawn_server_with_auth(
+            "127.0.0.1:18441".to_owned(),
+            AGGREGATOR_SERVER_ID.to_owned(),
+            PingServerHandler {
+                commitment_contract_address,
+            },
+            AddressAuthenticator::default(),
+        )
+        .unwrap();
+        sleep(Duration::from_millis(100));
+        assert!(verify_aggregator(
+            "127.0.0.1:18441",
+            commitment_contract_address,
+            SecretKeySigner::random()
+        )
+        .is_ok());
+        let err = verify_aggregator(
+            "127.0.0.1:18441",
+            Address::from_low_u64_be(2),
+            SecretKeySigner::random(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            format!("{}", err.kind()),
+            format!("{}", ErrorKind::CommitmentContractMismatch)
+        );
+        let err = verify_aggregator(
+            "127.0.0.1:18442",
+            commitment_contract_address,
+            SecretKeySigner::random(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            format!("{}", err.kind()),
+            format!("{}", ErrorKind::ConnectionError)
+        );
+    }
+
+    #[test]
+    fn test_filter_verified_blocks() {
+        let valid_root = H256::from_slice(&create_block(1).compute_state_root().unwrap());
+        let blocks = stream::iter_ok(vec![create_block(1), create_block(2), create_block(3)]);
+        let verified: Vec<u64> =
+            filter_verified_blocks(blocks, |block_number| match block_number {
+                1 => Ok(valid_root),
+                2 => Ok(H256::zero()),
+                _ => Err(ContractError::from(ContractErrorKind::RootNotFound)),
+            })
+            .map(|block| block.get_block_number())
+            .collect()
+            .wait()
+            .unwrap();
+        assert_eq!(verified, vec![1]);
+
+        let blocks = stream::iter_ok(vec![create_block(1)]);
+        let result = filter_verified_blocks(blocks, |_| -> Result<H256, ContractError> {
+            Err(ContractError::from(ContractErrorKind::Web3))
+        })
+        .collect()
+        .wait();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_handle_new_block_signed_by_aggregator() {
+        let secret_key = SecretKey::from_raw(&[1u8; 32]).unwrap();
+        let aggregator_address: Address = secret_key.public().address().into();
+        let plasma_client: PlasmaClient<CoreDbMemoryImpl> =
+            PlasmaClient::without_plasma_contract(aggregator_address);
+        let state_update = create_block(1).get_state_updates()[0].clone();
+        let mut block = create_block(1);
+        block.merkelize().unwrap();
+        plasma_client.handle_new_block(block.clone());
+        assert!(!plasma_client
+            .get_all_state_updates()
+            .contains(&state_update));
+
+        block.sign(&secret_key);
+        plasma_client.handle_new_block(block);
+        assert!(plasma_client
+            .get_all_state_updates()
+            .contains(&state_update));
+    }
+}
@@ -0,0 +1,263 @@
+use bytes::Bytes;
+use ethereum_types::Address;
+use ethsign::SecretKey;
+use ovm::deciders::signed_by_decider::{bytes_to_signature, hash};
+use ovm::deciders::SignVerifier;
+use pubsub_messaging::{
+    auth_message, connect_with_auth, Authenticator, ChallengeSigner, Client, ClientHandler,
+    Result as PubsubResult, AUTH_NONCE_LENGTH,
+};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Server id of the aggregator which clients sign together with the nonce.
+pub const AGGREGATOR_SERVER_ID: &str = "aggregator";
+
+/// Authenticates pubsub clients by ethereum address recovered from the signed nonce.
+/// Any address is accepted if `allowlist` is None.
+#[derive(Clone, Default)]
+pub struct AddressAuthenticator {
+    allowlist: Option<HashSet<Address>>,
+}
+
+impl AddressAuthenticator {
+    pub fn new(allowlist: Option<HashSet<Address>>) -> Self {
+        Self { allowlist }
+    }
+
+    fn recover(message: &[u8], signature: &[u8]) -> Option<Address> {
+        if signature.len() != 65 {
+            return None;
+        }
+        bytes_to_signature(&Bytes::from(signature))
+            .recover(hash(&Bytes::from(message)).as_bytes())
+            .ok()
+            .map(|public_key| public_key.address().into())
+    }
+}
+
+impl Authenticator for AddressAuthenticator {
+    /// Returns hex string of the recovered address.
+    fn authenticate(
+        &self,
+        server_id: &str,
+        nonce: &[u8; AUTH_NONCE_LENGTH],
+        signature: &[u8],
+    ) -> Option<String> {
+        let address = Self::recover(&auth_message(server_id, nonce), signature)?;
+        match &self.allowlist {
+            Some(allowlist) if !allowlist.contains(&address) => None,
+            _ => Some(format!("{:?}", address)),
+        }
+    }
+}
+
+/// Signs nonce of pubsub server with the ethereum private key.
+/// Only the domain separated `auth_message` is signed, so the signature can't be used as
+/// signature of transactions.
+#[derive(Clone)]
+pub struct SecretKeySigner {
+    secret_key: Arc<SecretKey>,
+}
+
+impl SecretKeySigner {
+    pub fn new(secret_key: SecretKey) -> Self {
+        Self {
+            secret_key: Arc::new(secret_key),
+        }
+    }
+
+    /// Creates signer of a new random key.
+    pub fn random() -> Self {
+        let secret_key_raw = secp256k1::SecretKey::random(&mut rand::thread_rng()).serialize();
+        Self::new(SecretKey::from_raw(&secret_key_raw).unwrap())
+    }
+}
+
+/// Connects to the aggregator of `aggregator_endpoint` and authenticates with `signer`.
+pub fn connect_to_aggregator<T>(
+    aggregator_endpoint: String,
+    handler: T,
+    signer: SecretKeySigner,
+) -> PubsubResult<Client>
+where
+    T: ClientHandler + Clone + Send + Sync + 'static,
+{
+    connect_with_auth(
+        aggregator_endpoint,
+        AGGREGATOR_SERVER_ID.to_owned(),
+        handler,
+        signer,
+    )
+}
+
+impl ChallengeSigner for SecretKeySigner {
+    fn sign_challenge(&self, server_id: &str, nonce: &[u8; AUTH_NONCE_LENGTH]) -> Vec<u8> {
+        SignVerifier::sign(
+            &self.secret_key,
+            &Bytes::from(auth_message(server_id, nonce)),
+        )
+        .to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bincode::serialize;
+    use pubsub_messaging::{
+        connect_with_auth, spawn_server, spawn_server_with_auth, ClientHandler, Message, Sender,
+        ServerHandler, WsMessage,
+    };
+    use std::sync::mpsc::{channel, Sender as ChannelSender};
+    use std::sync::{Arc, Mutex};
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    #[derive(Clone)]
+    struct NopClientHandler;
+
+    impl ClientHandler for NopClientHandler {
+        fn handle_message(&self, _msg: Message, _sender: Sender) {}
+    }
+
+    /// Sends authenticated identities and received messages to the channel.
+    #[derive(Clone)]
+    struct RecordingServerHandler {
+        events: Arc<Mutex<ChannelSender<Message>>>,
+        challenges: Vec<Vec<u8>>,
+    }
+
+    impl ServerHandler for RecordingServerHandler {
+        fn handle_message(&mut self, msg: Message, _sender: Sender) {
+            let _ = self.events.lock().unwrap().send(msg);
+        }
+        fn handle_open(&mut self, sender: Sender) {
+            for challenge in self.challenges.iter() {
+                let message = Message::auth_challenge(challenge.clone());
+                let _ = sender.send(WsMessage::Binary(serialize(&message).unwrap()));
+            }
+        }
+        fn handle_authenticated(&mut self, identity: String, _sender: Sender) {
+            let _ = self
+                .events
+                .lock()
+                .unwrap()
+                .send(Message::new("authenticated".to_string(), identity.into()));
+        }
+    }
+
+    #[test]
+    fn test_authenticate() {
+        let secret_key = SecretKey::from_raw(&[1u8; 32]).unwrap();
+        let address: Address = secret_key.public().address().into();
+        let signer = SecretKeySigner::new(secret_key);
+        let nonce = [7u8; AUTH_NONCE_LENGTH];
+        let signature = signer.sign_challenge("aggregator", &nonce);
+
+        let authenticator = AddressAuthenticator::default();
+        assert_eq!(
+            authenticator.authenticate("aggregator", &nonce, &signature),
+            Some(format!("{:?}", address))
+        );
+        assert_ne!(
+            authenticator.authenticate("other", &nonce, &signature),
+            Some(format!("{:?}", address))
+        );
+        assert_eq!(
+            authenticator.authenticate("aggregator", &nonce, &signature[1..]),
+            None
+        );
+        // raw nonce isn't signed
+        assert_ne!(
+            AddressAuthenticator::recover(&nonce, &signature),
+            Some(address)
+        );
+
+        let mut allowlist = HashSet::new();
+        allowlist.insert(Address::zero());
+        let authenticator = AddressAuthenticator::new(Some(allowlist));
+        assert_eq!(
+            authenticator.authenticate("aggregator", &nonce, &signature),
+            None
+        );
+    }
+
+    #[test]
+    fn test_handshake() {
+        let secret_key = SecretKey::from_raw(&[1u8; 32]).unwrap();
+        let address: Address = secret_key.public().address().into();
+        let (tx, rx) = channel();
+        let handler = RecordingServerHandler {
+            events: Arc::new(Mutex::new(tx)),
+            challenges: vec![],
+        };
+        let _server = spawn_server_with_auth(
+            "127.0.0.1:18431".to_owned(),
+            "aggregator".to_owned(),
+            handler,
+            AddressAuthenticator::default(),
+        )
+        .unwrap();
+        sleep(Duration::from_millis(100));
+        let _client = connect_with_auth(
+            "127.0.0.1:18431".to_owned(),
+            "aggregator".to_owned(),
+            NopClientHandler,
+            SecretKeySigner::new(secret_key),
+        )
+        .unwrap();
+        let event = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(event.to, "authenticated");
+        assert_eq!(event.message, format!("{:?}", address).into_bytes());
+    }
+
+    #[test]
+    fn test_challenge_is_answered_once() {
+        let secret_key = SecretKey::from_raw(&[1u8; 32]).unwrap();
+        let address: Address = secret_key.public().address().into();
+        let (tx, rx) = channel();
+        let nonce = [7u8; AUTH_NONCE_LENGTH];
+        // malicious server sends challenges which aren't part of the handshake
+        let handler = RecordingServerHandler {
+            events: Arc::new(Mutex::new(tx)),
+            challenges: vec![nonce.to_vec(), b"transaction body".to_vec()],
+        };
+        let _server = spawn_server("127.0.0.1:18432".to_owned(), handler).unwrap();
+        sleep(Duration::from_millis(100));
+        let _client = connect_with_auth(
+            "127.0.0.1:18432".to_owned(),
+            "aggregator".to_owned(),
+            NopClientHandler,
+            SecretKeySigner::new(secret_key),
+        )
+        .unwrap();
+        let response = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(response.is_auth_response());
+        assert_eq!(
+            AddressAuthenticator::recover(&auth_message("aggregator", &nonce), &response.message),
+            Some(address)
+        );
+        assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+    }
+
+    #[test]
+    fn test_malformed_challenge_is_not_answered() {
+        let secret_key = SecretKey::from_raw(&[1u8; 32]).unwrap();
+        let (tx, rx) = channel();
+        let handler = RecordingServerHandler {
+            events: Arc::new(Mutex::new(tx)),
+            challenges: vec![b"transaction body".to_vec()],
+        };
+        let _server = spawn_server("127.0.0.1:18433".to_owned(), handler).unwrap();
+        sleep(Duration::from_millis(100));
+        let _client = connect_with_auth(
+            "127.0.0.1:18433".to_owned(),
+            "aggregator".to_owned(),
+            NopClientHandler,
+            SecretKeySigner::new(secret_key),
+        )
+        .unwrap();
+        assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+    }
+}
@@ -1,5 +1,7 @@
-use super::error::Error;
+use super::error::{Error, ErrorKind};
 use bytes::Bytes;
+use ethereum_types::Address;
+use plasma_core::data_structure::Nonce;
 use plasma_db::traits::kvs::KeyValueStore;
 
 pub struct WalletDb<'a, KVS> {
@@ -25,4 +27,29 @@ impl<'a, KVS: KeyValueStore> WalletDb<'a, KVS> {
             .map(|op| op.unwrap_or_else(|| vec![]))
             .map_err::<Error, _>(Into::into)
     }
+
+    /// Returns the last nonce of `address` accepted by the aggregator.
+    pub fn get_last_nonce(&self, address: Address) -> Result<Option<Nonce>, Error> {
+        let nonce_opt = self
+            .db
+            .bucket(&Bytes::from("nonces").into())
+            .get(&address.as_bytes().into())
+            .map_err::<Error, _>(Into::into)?;
+        match nonce_opt {
+            Some(nonce) if nonce.len() == 8 => {
+                let mut buf = [0u8; 8];
+                buf.copy_from_slice(&nonce);
+                Ok(Some(Nonce(u64::from_be_bytes(buf))))
+            }
+            Some(_) => Err(Error::from(ErrorKind::StorageIntegrityError)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn put_last_nonce(&mut self, address: Address, nonce: Nonce) -> Result<(), Error> {
+        self.db
+            .bucket(&Bytes::from("nonces").into())
+            .put(&address.as_bytes().into(), &nonce.0.to_be_bytes())
+            .map_err::<Error, _>(Into::into)
+    }
 }
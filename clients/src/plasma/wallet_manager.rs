@@ -1,6 +1,9 @@
+use super::error::Error;
 use super::wallet_db::WalletDb;
 use bytes::Bytes;
+use ethereum_types::Address;
 use ethsign::SecretKey as EthSecretKey;
+use plasma_core::data_structure::Nonce;
 use plasma_db::traits::kvs::KeyValueStore;
 use rand::*;
 use secp256k1::SecretKey;
@@ -35,14 +38,29 @@ impl<'a, KVS: KeyValueStore> WalletManager<'a, KVS> {
         (session, EthSecretKey::from_raw(&secret_key_raw).unwrap())
     }
 
+    /// Returns None if no key is stored for the session.
     pub fn get_key(&self, session: &Bytes) -> Option<EthSecretKey> {
-        match self
-            .db
+        self.db
             .get_private_key(session)
-            .map(|k| EthSecretKey::from_raw(&k).unwrap())
-        {
-            Ok(k) => Some(k),
-            Err(_) => None,
+            .ok()
+            .and_then(|k| EthSecretKey::from_raw(&k).ok())
+    }
+
+    /// Returns the nonce for the next transaction of `address`.
+    /// Transactions must be accepted by the aggregator one by one, because the nonce only
+    /// advances when an accepted transaction is received.
+    pub fn get_next_nonce(&self, address: Address) -> Result<Nonce, Error> {
+        Ok(self
+            .db
+            .get_last_nonce(address)?
+            .map_or_else(Nonce::default, Nonce::next))
+    }
+
+    /// Records `nonce` of a transaction of `address` accepted by the aggregator.
+    pub fn record_nonce(&mut self, address: Address, nonce: Nonce) -> Result<(), Error> {
+        match self.db.get_last_nonce(address)? {
+            Some(last_nonce) if last_nonce >= nonce => Ok(()),
+            _ => self.db.put_last_nonce(address, nonce),
         }
     }
 }
@@ -66,5 +84,22 @@ mod tests {
                 .to_vec(),
             private_key.public().bytes().to_vec()
         );
+        assert!(wallet_manager.get_key(&Bytes::from("unknown")).is_none());
+    }
+
+    #[test]
+    fn test_next_nonce() {
+        let kvs = CoreDbMemoryImpl::open("test");
+        let mut wallet_manager = WalletManager::new(&kvs);
+        let address = Address::from_low_u64_be(1);
+        assert_eq!(wallet_manager.get_next_nonce(address).unwrap(), Nonce(0));
+        assert!(wallet_manager.record_nonce(address, Nonce(1)).is_ok());
+        // nonce received out of order doesn't move it back
+        assert!(wallet_manager.record_nonce(address, Nonce(0)).is_ok());
+        assert_eq!(wallet_manager.get_next_nonce(address).unwrap(), Nonce(2));
+        assert_eq!(
+            wallet_manager.get_next_nonce(Address::zero()).unwrap(),
+            Nonce(0)
+        );
     }
 }
@@ -1,16 +1,38 @@
 use crate::error::{Error, ErrorKind};
 use bytes::Bytes;
 use ethabi::Contract as ContractABI;
+use ethabi::{decode, Event, ParamType, Token, Topic, TopicFilter};
+use failure::Fail;
+use std::sync::RwLock;
+use std::thread;
+use std::time::{Duration, Instant};
+use tiny_keccak::Keccak;
 use web3::contract::{Contract, Options};
 use web3::futures::Future;
 use web3::transports::{EventLoopHandle, Http};
-use web3::types::{Address, H256};
+use web3::types::{Address, BlockNumber, FilterBuilder, H256, U256};
+
+/// Default number of retries of block submission.
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+/// Default wait before the first retry. It's doubled each retry.
+pub const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+/// Default time to wait for confirmations of block submission.
+pub const DEFAULT_CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(600);
+/// Interval of polling transaction receipt.
+const CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_secs(1);
 
 pub struct CommitmentContractAdaptor {
     _eloop: EventLoopHandle,
-    _web3: web3::Web3<web3::transports::Http>,
-    _address: Address,
+    web3: web3::Web3<web3::transports::Http>,
+    address: Address,
     inner: Contract<Http>,
+    /// `BlockSubmitted` event from which submitted roots are read.
+    block_submitted: Event,
+    /// Locally cached root of the historical commitment tree whose leaves are block roots.
+    history_root: RwLock<Option<H256>>,
+    gas_warning_threshold: Option<u64>,
+    max_retries: u32,
+    initial_backoff: Duration,
 }
 
 impl CommitmentContractAdaptor {
@@ -18,36 +40,368 @@ impl CommitmentContractAdaptor {
         let (_eloop, http) = web3::transports::Http::new(host)
             .map_err(|_| Error::from(ErrorKind::FailedToConnect))?;
         let web3 = web3::Web3::new(http);
+        let block_submitted = abi.event("BlockSubmitted")?.clone();
 
         let contract = Contract::new(web3.eth(), address, abi);
 
         Ok(Self {
-            _web3: web3,
+            web3,
             _eloop,
-            _address: address,
+            address,
             inner: contract,
+            block_submitted,
+            history_root: RwLock::new(None),
+            gas_warning_threshold: None,
+            max_retries: DEFAULT_MAX_RETRIES,
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
         })
     }
 
-    pub fn submit_block(
+    /// Sets how many times and how long after `submit_block` is retried on transient failures.
+    pub fn set_retry_policy(&mut self, max_retries: u32, initial_backoff: Duration) {
+        self.max_retries = max_retries;
+        self.initial_backoff = initial_backoff;
+    }
+
+    /// Sets the gas amount over which `estimate_block_submission_gas` warns.
+    pub fn set_gas_warning_threshold(&mut self, threshold: u64) {
+        self.gas_warning_threshold = Some(threshold);
+    }
+
+    /// Caches the root of the historical commitment tree used by `verify_root`.
+    pub fn set_history_root(&self, history_root: H256) {
+        *self.history_root.write().unwrap() = Some(history_root);
+    }
+
+    /// Verifies `claimed_root` of `block_number` against the cached history root without RPC call.
+    /// `proof` is the concatenation of 32 bytes sibling hashes from leaf to root.
+    pub fn verify_root(&self, block_number: u64, claimed_root: H256, proof: &[u8]) -> bool {
+        if let Some(history_root) = *self.history_root.read().unwrap() {
+            if let Some(computed) = compute_history_root(block_number, claimed_root, proof) {
+                return computed == history_root;
+            }
+        }
+        false
+    }
+
+    /// Gets the root submitted for `block_number` from `BlockSubmitted` events.
+    /// Returns `RootNotFound` error if the block hasn't been submitted yet.
+    pub fn get_root(&self, block_number: u64) -> Result<H256, Error> {
+        self.get_root_async(block_number).wait()
+    }
+
+    /// Same as `get_root`, but returns a future instead of blocking.
+    pub fn get_root_async(&self, block_number: u64) -> impl Future<Item = H256, Error = Error> {
+        let filter = FilterBuilder::default()
+            .address(vec![self.address])
+            .from_block(BlockNumber::Earliest)
+            .to_block(BlockNumber::Latest)
+            .topic_filter(TopicFilter {
+                topic0: Topic::This(self.block_submitted.signature()),
+                topic1: Topic::Any,
+                topic2: Topic::Any,
+                topic3: Topic::Any,
+            })
+            .build();
+        self.web3
+            .eth()
+            .logs(filter)
+            .map_err(|_| Error::from(ErrorKind::Web3))
+            .and_then(move |logs| {
+                let mut root = None;
+                for log in logs.iter().filter(|log| log.removed != Some(true)) {
+                    let (submitted_block_number, submitted_root) =
+                        decode_block_submitted(&log.data.0)?;
+                    if submitted_block_number == block_number {
+                        root = Some(submitted_root);
+                    }
+                }
+                root.ok_or_else(|| Error::from(ErrorKind::RootNotFound))
+            })
+    }
+
+    /// Estimates gas of `submit_root` with the same calldata as `submit_block`.
+    pub fn estimate_block_submission_gas(
         &self,
         from: Address,
         block_number: u64,
-        root: Bytes,
-    ) -> Result<H256, Error> {
-        let result = self.inner.call(
+        root: H256,
+    ) -> Result<u64, Error> {
+        let result = self.inner.estimate_gas(
             "submit_root",
-            (block_number, H256::from_slice(root.to_vec().as_slice())),
+            (block_number, root),
             from,
             Options::default(),
         );
+        let gas: U256 = result.wait()?;
+        let gas = gas.as_u64();
+        if let Some(threshold) = self.gas_warning_threshold {
+            if gas > threshold {
+                println!(
+                    "WARNING: estimated gas {} of block {} exceeds threshold {}",
+                    gas, block_number, threshold
+                );
+            }
+        }
+        Ok(gas)
+    }
+
+    /// Polls receipt of `tx_hash` until it has `confirmations` confirmations.
+    /// The block including the transaction counts as the first confirmation.
+    /// Returns `Timeout` error if it isn't confirmed within `timeout`,
+    /// e.g. the transaction was dropped or replaced.
+    pub fn wait_for_confirmations(
+        &self,
+        tx_hash: H256,
+        confirmations: u32,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        poll_until(timeout, CONFIRMATION_POLL_INTERVAL, || {
+            let receipt = self
+                .web3
+                .eth()
+                .transaction_receipt(tx_hash)
+                .wait()
+                .map_err(|_| Error::from(ErrorKind::Web3))?;
+            if let Some(included_at) = receipt.and_then(|r| r.block_number) {
+                let latest = self
+                    .web3
+                    .eth()
+                    .block_number()
+                    .wait()
+                    .map_err(|_| Error::from(ErrorKind::Web3))?;
+                return Ok(
+                    latest >= included_at && latest - included_at + 1 >= U256::from(confirmations)
+                );
+            }
+            Ok(false)
+        })
+    }
+
+    /// Submits root with the estimated gas plus 50% buffer.
+    /// Transient failures are retried with exponential backoff unless the transaction
+    /// may have been broadcast, which returns `MaybeBroadcast` error.
+    pub fn submit_block(
+        &self,
+        from: Address,
+        block_number: u64,
+        root: Bytes,
+    ) -> Result<H256, Error> {
+        let root = H256::from_slice(root.to_vec().as_slice());
+        submit_with_retry(self.max_retries, self.initial_backoff, || {
+            let gas = self.estimate_block_submission_gas(from, block_number, root)?;
+            let result = self.inner.call(
+                "submit_root",
+                (block_number, root),
+                from,
+                Options::with(|opt| opt.gas = Some(U256::from(gas + gas / 2))),
+            );
+            result.wait().map_err(|e| {
+                let error = Error::from(e);
+                match error.kind() {
+                    // the node refused the transaction
+                    ErrorKind::Reverted | ErrorKind::Rejected => error,
+                    _ => Error::from(error.context(ErrorKind::MaybeBroadcast)),
+                }
+            })
+        })
+    }
+}
 
-        match result.wait() {
-            Ok(r) => Ok(r),
+/// Calls `f` until it succeeds, retrying at most `max_retries` times.
+/// The wait starts from `initial_backoff` and doubles each retry.
+/// Reverted transaction isn't retried because it fails again, and transaction which may have
+/// been broadcast isn't retried because the root would be submitted twice.
+fn submit_with_retry<T, F>(max_retries: u32, initial_backoff: Duration, f: F) -> Result<T, Error>
+where
+    F: Fn() -> Result<T, Error>,
+{
+    let mut backoff = initial_backoff;
+    let mut retries = 0;
+    loop {
+        match f() {
+            Ok(r) => return Ok(r),
             Err(e) => {
-                println!("{:?}", e);
-                Err(e.into())
+                let is_retryable = match e.kind() {
+                    ErrorKind::Reverted | ErrorKind::MaybeBroadcast => false,
+                    _ => true,
+                };
+                if !is_retryable || retries >= max_retries {
+                    return Err(e);
+                }
+                retries += 1;
+                println!(
+                    "submission failed: {}, retrying ({}/{}) in {:?}",
+                    e, retries, max_retries, backoff
+                );
+                thread::sleep(backoff);
+                backoff *= 2;
             }
         }
     }
 }
+
+/// Calls `f` every `interval` until it returns true.
+/// Returns `Timeout` error if it doesn't return true within `timeout`.
+fn poll_until<F>(timeout: Duration, interval: Duration, mut f: F) -> Result<(), Error>
+where
+    F: FnMut() -> Result<bool, Error>,
+{
+    let deadline = Instant::now() + timeout;
+    loop {
+        if f()? {
+            return Ok(());
+        }
+        if Instant::now() + interval > deadline {
+            return Err(Error::from(ErrorKind::Timeout));
+        }
+        thread::sleep(interval);
+    }
+}
+
+/// Decodes data of `BlockSubmitted(uint64 blockNumber, bytes32 root)` event.
+fn decode_block_submitted(data: &[u8]) -> Result<(u64, H256), Error> {
+    let tokens = decode(&[ParamType::Uint(64), ParamType::FixedBytes(32)], data)?;
+    match (&tokens[0], &tokens[1]) {
+        (Token::Uint(block_number), Token::FixedBytes(root)) if root.len() == 32 => {
+            Ok((block_number.low_u64(), H256::from_slice(root)))
+        }
+        _ => Err(Error::from(ErrorKind::Abi)),
+    }
+}
+
+fn hash_pair(left: &H256, right: &H256) -> H256 {
+    let mut sha3 = Keccak::new_sha3_256();
+    sha3.update(left.as_bytes());
+    sha3.update(right.as_bytes());
+    let mut res: [u8; 32] = [0; 32];
+    sha3.finalize(&mut res);
+    H256::from(res)
+}
+
+/// Computes the root of the historical commitment tree from a block root and its inclusion proof.
+fn compute_history_root(block_number: u64, claimed_root: H256, proof: &[u8]) -> Option<H256> {
+    if proof.len() % 32 != 0 || proof.len() / 32 > 64 {
+        return None;
+    }
+    let mut index = block_number;
+    let mut computed = claimed_root;
+    for sibling in proof.chunks(32) {
+        let sibling = H256::from_slice(sibling);
+        computed = if index & 1 == 0 {
+            hash_pair(&computed, &sibling)
+        } else {
+            hash_pair(&sibling, &computed)
+        };
+        index >>= 1;
+    }
+    if index != 0 {
+        return None;
+    }
+    Some(computed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_history_root() {
+        let roots: Vec<H256> = (0..4).map(|i| H256::from_low_u64_be(i + 1)).collect();
+        let left = hash_pair(&roots[0], &roots[1]);
+        let right = hash_pair(&roots[2], &roots[3]);
+        let history_root = hash_pair(&left, &right);
+        let mut proof = roots[3].as_bytes().to_vec();
+        proof.extend_from_slice(left.as_bytes());
+        assert_eq!(
+            compute_history_root(2, roots[2], &proof),
+            Some(history_root)
+        );
+        assert_ne!(
+            compute_history_root(2, roots[3], &proof),
+            Some(history_root)
+        );
+        assert_eq!(compute_history_root(4, roots[2], &proof), None);
+        assert_eq!(compute_history_root(2, roots[2], &proof[1..]), None);
+    }
+
+    #[test]
+    fn test_submit_with_retry() {
+        let calls = std::cell::Cell::new(0);
+        let result = submit_with_retry(3, Duration::from_millis(1), || {
+            calls.set(calls.get() + 1);
+            if calls.get() < 3 {
+                Err(Error::from(ErrorKind::Web3))
+            } else {
+                Ok(calls.get())
+            }
+        });
+        assert_eq!(result.unwrap(), 3);
+
+        calls.set(0);
+        let result: Result<(), Error> = submit_with_retry(3, Duration::from_millis(1), || {
+            calls.set(calls.get() + 1);
+            Err(Error::from(ErrorKind::Web3))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls.get(), 4);
+
+        calls.set(0);
+        let result: Result<(), Error> = submit_with_retry(3, Duration::from_millis(1), || {
+            calls.set(calls.get() + 1);
+            Err(Error::from(ErrorKind::Reverted))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls.get(), 1);
+
+        calls.set(0);
+        let result: Result<(), Error> = submit_with_retry(3, Duration::from_millis(1), || {
+            calls.set(calls.get() + 1);
+            Err(Error::from(ErrorKind::MaybeBroadcast))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn test_decode_block_submitted() {
+        let root = H256::from_low_u64_be(100);
+        let data = ethabi::encode(&[
+            Token::Uint(U256::from(5)),
+            Token::FixedBytes(root.as_bytes().to_vec()),
+        ]);
+        assert_eq!(decode_block_submitted(&data).unwrap(), (5, root));
+        assert!(decode_block_submitted(&data[..32]).is_err());
+    }
+
+    #[test]
+    fn test_block_submitted_is_in_abi() {
+        let f = std::fs::File::open("CommitmentChain.json").unwrap();
+        let abi = ContractABI::load(std::io::BufReader::new(f)).unwrap();
+        let event = abi.event("BlockSubmitted").unwrap();
+        let kinds: Vec<ParamType> = event.inputs.iter().map(|i| i.kind.clone()).collect();
+        assert_eq!(kinds, vec![ParamType::Uint(64), ParamType::FixedBytes(32)]);
+    }
+
+    #[test]
+    fn test_poll_until() {
+        let polls = std::cell::Cell::new(0);
+        let result = poll_until(Duration::from_secs(1), Duration::from_millis(1), || {
+            polls.set(polls.get() + 1);
+            Ok(polls.get() == 3)
+        });
+        assert!(result.is_ok());
+        assert_eq!(polls.get(), 3);
+
+        let result = poll_until(Duration::from_millis(10), Duration::from_millis(1), || {
+            Ok(false)
+        });
+        match result {
+            Err(e) => match e.kind() {
+                ErrorKind::Timeout => {}
+                _ => panic!("unexpected error kind"),
+            },
+            Ok(_) => panic!("polling must time out"),
+        }
+    }
+}
@@ -4,6 +4,14 @@ use std::fmt;
 use std::fmt::Display;
 
 use web3::contract::Error as Web3Error;
+use web3::Error as ApiError;
+
+/// JSON RPC error code of reverted execution returned by geth.
+const EXECUTION_REVERTED: i64 = 3;
+/// JSON RPC error code of failed VM execution returned by parity.
+const VM_EXECUTION_ERROR: i64 = -32015;
+/// JSON RPC error code of server error, which ganache returns with VM exception.
+const SERVER_ERROR: i64 = -32000;
 
 #[derive(Fail, Debug)]
 pub enum ErrorKind {
@@ -15,6 +23,16 @@ pub enum ErrorKind {
     Abi,
     #[fail(display = "Web3 error")]
     Web3,
+    #[fail(display = "Transaction reverted")]
+    Reverted,
+    #[fail(display = "Transaction rejected by node")]
+    Rejected,
+    #[fail(display = "Transaction may have been broadcast")]
+    MaybeBroadcast,
+    #[fail(display = "Timeout")]
+    Timeout,
+    #[fail(display = "Root not found")]
+    RootNotFound,
 }
 
 #[derive(Debug)]
@@ -70,10 +88,47 @@ impl From<AbiError> for Error {
     }
 }
 
+/// Returns true if JSON RPC error of `code` and `message` means EVM execution is reverted.
+fn is_reverted(code: i64, message: &str) -> bool {
+    match code {
+        EXECUTION_REVERTED | VM_EXECUTION_ERROR => true,
+        SERVER_ERROR => message.starts_with("VM Exception while processing transaction: revert"),
+        _ => false,
+    }
+}
+
 impl From<Web3Error> for Error {
-    fn from(_error: Web3Error) -> Error {
+    /// Error response of the node is `Reverted` or `Rejected`. Others are `Web3`.
+    fn from(error: Web3Error) -> Error {
+        let kind = match &error {
+            Web3Error::Api(ApiError::Rpc(rpc_error)) => {
+                if is_reverted(rpc_error.code.code(), &rpc_error.message) {
+                    ErrorKind::Reverted
+                } else {
+                    ErrorKind::Rejected
+                }
+            }
+            _ => ErrorKind::Web3,
+        };
         Error {
-            inner: Context::new(ErrorKind::Web3),
+            inner: Context::new(kind),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_reverted() {
+        assert!(is_reverted(EXECUTION_REVERTED, "execution reverted"));
+        assert!(is_reverted(VM_EXECUTION_ERROR, "VM execution error."));
+        assert!(is_reverted(
+            SERVER_ERROR,
+            "VM Exception while processing transaction: revert"
+        ));
+        assert!(!is_reverted(SERVER_ERROR, "nonce too low"));
+        assert!(!is_reverted(-32601, "revert"));
+    }
+}
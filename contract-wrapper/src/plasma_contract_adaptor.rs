@@ -1,7 +1,9 @@
 use crate::error::{Error, ErrorKind};
 use ethabi::Contract as ContractABI;
-use ethabi::Token;
+use ethabi::{Event, Token};
 use ethereum_types::U256;
+use event_watcher::event_db::EventDb;
+use event_watcher::event_watcher::EventFetcher;
 use ovm::types::core::Property;
 use ovm::types::StateUpdate;
 use plasma_core::data_structure::Range;
@@ -12,11 +14,16 @@ use web3::types::{Address, H256};
 
 pub struct PlasmaContractAdaptor {
     _eloop: EventLoopHandle,
-    _web3: web3::Web3<web3::transports::Http>,
-    _address: Address,
+    web3: web3::Web3<web3::transports::Http>,
+    address: Address,
     inner: Contract<Http>,
+    abi: ContractABI,
+    exit_count_warning_threshold: Option<u64>,
 }
 
+/// Names of events emitted through the lifecycle of an exit.
+pub const EXIT_EVENT_NAMES: [&str; 3] = ["ExitStarted", "ExitChallenged", "ExitFinalized"];
+
 impl PlasmaContractAdaptor {
     pub fn new(host: &str, address: &str, abi: ContractABI) -> Result<Self, Error> {
         let (_eloop, http) = web3::transports::Http::new(host)
@@ -26,16 +33,98 @@ impl PlasmaContractAdaptor {
         let address: Address = address
             .parse()
             .map_err(|_| Error::from(ErrorKind::InvalidInputType))?;
-        let contract = Contract::new(web3.eth(), address, abi);
+        let contract = Contract::new(web3.eth(), address, abi.clone());
 
         Ok(Self {
-            _web3: web3,
+            web3,
             _eloop,
-            _address: address,
+            address,
             inner: contract,
+            abi,
+            exit_count_warning_threshold: None,
         })
     }
 
+    /// Returns fetcher of exit events defined in the contract ABI.
+    /// The returned EventLoopHandle must be kept alive while fetching.
+    /// Returns error if the ABI has none of `EXIT_EVENT_NAMES`.
+    pub fn listen_exit_events<T: EventDb>(
+        self,
+        db: T,
+    ) -> Result<(EventLoopHandle, EventFetcher<T>), Error> {
+        let events: Vec<Event> = self
+            .abi
+            .events()
+            .filter(|event| EXIT_EVENT_NAMES.contains(&event.name.as_str()))
+            .cloned()
+            .collect();
+        if events.is_empty() {
+            return Err(Error::from(ErrorKind::Abi));
+        }
+        Ok((
+            self._eloop,
+            EventFetcher::new(self.web3, self.address, events, db),
+        ))
+    }
+
+    /// Sets the number of pending exits over which `get_exit_count` warns.
+    pub fn set_exit_count_warning_threshold(&mut self, threshold: u64) {
+        self.exit_count_warning_threshold = Some(threshold);
+    }
+
+    /// Gets the number of pending exits on chain.
+    /// Returns ABI error without calling if the contract ABI has no `getExitCount`.
+    pub fn get_exit_count(&self) -> Result<u64, Error> {
+        self.abi.function("getExitCount")?;
+        let result = self
+            .inner
+            .query("getExitCount", (), None, Options::default(), None);
+        let exit_count: U256 = result.wait()?;
+        let exit_count = exit_count.as_u64();
+        if let Some(threshold) = self.exit_count_warning_threshold {
+            if exit_count > threshold {
+                println!(
+                    "WARNING: {} pending exits exceed threshold {}",
+                    exit_count, threshold
+                );
+            }
+        }
+        Ok(exit_count)
+    }
+
+    /// Gets the chain ID which distinguishes plasma chains deployed on the same network.
+    /// Returns ABI error without calling if the contract ABI has no `getPlasmaChainId`.
+    pub fn get_plasma_chain_id(&self) -> Result<u64, Error> {
+        self.abi.function("getPlasmaChainId")?;
+        let result = self
+            .inner
+            .query("getPlasmaChainId", (), None, Options::default(), None);
+        let chain_id: U256 = result.wait()?;
+        Ok(chain_id.as_u64())
+    }
+
+    /// Gets the latest block number of the connected ethereum node.
+    pub fn get_eth_block_number(&self) -> Result<u64, Error> {
+        let block_number = self
+            .web3
+            .eth()
+            .block_number()
+            .wait()
+            .map_err(|_| Error::from(ErrorKind::Web3))?;
+        Ok(block_number.as_u64())
+    }
+
+    /// Returns true if contract code exists at the address.
+    pub fn is_deployed(&self) -> Result<bool, Error> {
+        let code = self
+            .web3
+            .eth()
+            .code(self.address, None)
+            .wait()
+            .map_err(|_| Error::from(ErrorKind::Web3))?;
+        Ok(!code.0.is_empty())
+    }
+
     pub fn deposit(&self, from: Address, amount: u64, property: Property) -> Result<H256, Error> {
         let params: Token = property.into();
         let result = self.inner.call(
@@ -60,3 +149,34 @@ impl PlasmaContractAdaptor {
         Ok(H256::zero())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::BufReader;
+
+    fn create_adaptor() -> PlasmaContractAdaptor {
+        // commitment contract ABI has no view functions of plasma contract
+        let f = File::open("CommitmentChain.json").unwrap();
+        let abi = ContractABI::load(BufReader::new(f)).unwrap();
+        PlasmaContractAdaptor::new(
+            "http://127.0.0.1:9545",
+            "0000000000000000000000000000000000000000",
+            abi,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_get_exit_count_without_abi() {
+        let err = create_adaptor().get_exit_count().unwrap_err();
+        assert_eq!(format!("{}", err.kind()), format!("{}", ErrorKind::Abi));
+    }
+
+    #[test]
+    fn test_get_plasma_chain_id_without_abi() {
+        let err = create_adaptor().get_plasma_chain_id().unwrap_err();
+        assert_eq!(format!("{}", err.kind()), format!("{}", ErrorKind::Abi));
+    }
+}
@@ -1,12 +1,26 @@
+pub mod address;
+pub mod block_header;
 pub mod error;
 pub mod metadata;
+pub mod nonce;
 pub mod range;
+pub mod receipt;
+#[cfg(feature = "schemars")]
+pub mod schema;
+pub mod signed_message;
 pub mod state_object;
 pub mod state_update;
 pub mod transaction;
+pub mod transaction_batch;
 
+pub use self::address::{PlasmaAddressExt, PlasmaAddressRegistry};
+pub use self::block_header::BlockHeader;
 pub use self::metadata::Metadata;
+pub use self::nonce::Nonce;
 pub use self::range::Range;
+pub use self::receipt::Receipt;
+pub use self::signed_message::SignedMessage;
 pub use self::state_object::StateObject;
 pub use self::state_update::StateUpdate;
 pub use self::transaction::{Transaction, TransactionParams};
+pub use self::transaction_batch::TransactionBatch;
@@ -0,0 +1,120 @@
+use ethereum_types::Address;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use tiny_keccak::Keccak;
+
+lazy_static! {
+    static ref ADDRESS_REGISTRY: PlasmaAddressRegistry = PlasmaAddressRegistry::default();
+}
+
+pub trait PlasmaAddressExt {
+    fn is_zero(&self) -> bool;
+    /// Returns mixed-case checksum encoding described in EIP-55.
+    fn checksum_encode(&self) -> String;
+}
+
+impl PlasmaAddressExt for Address {
+    fn is_zero(&self) -> bool {
+        *self == Address::zero()
+    }
+    fn checksum_encode(&self) -> String {
+        let hex_address = hex_encode(self.as_bytes());
+        let mut keccak = Keccak::new_keccak256();
+        keccak.update(hex_address.as_bytes());
+        let mut hash: [u8; 32] = [0; 32];
+        keccak.finalize(&mut hash);
+        let checksummed: String = hex_address
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                let nibble = if i % 2 == 0 {
+                    hash[i / 2] >> 4
+                } else {
+                    hash[i / 2] & 0x0f
+                };
+                if nibble >= 8 {
+                    c.to_ascii_uppercase()
+                } else {
+                    c
+                }
+            })
+            .collect();
+        format!("0x{}", checksummed)
+    }
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Global registry of known contract addresses and their names for diagnostic logging.
+#[derive(Default)]
+pub struct PlasmaAddressRegistry {
+    names: RwLock<HashMap<Address, String>>,
+}
+
+impl PlasmaAddressRegistry {
+    pub fn global() -> &'static PlasmaAddressRegistry {
+        &ADDRESS_REGISTRY
+    }
+    pub fn register(&self, address: Address, name: &str) {
+        self.names
+            .write()
+            .unwrap()
+            .insert(address, name.to_string());
+    }
+    pub fn get_name(&self, address: Address) -> Option<String> {
+        self.names.read().unwrap().get(&address).cloned()
+    }
+    pub fn is_registered(&self, address: Address) -> bool {
+        self.names.read().unwrap().contains_key(&address)
+    }
+    /// Returns `name(0x..)` if the address is registered, otherwise the checksum encoded address.
+    pub fn describe(&self, address: Address) -> String {
+        match self.get_name(address) {
+            Some(name) => format!("{}({})", name, address.checksum_encode()),
+            None => address.checksum_encode(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PlasmaAddressExt, PlasmaAddressRegistry};
+    use ethereum_types::Address;
+
+    fn address_from_hex(s: &str) -> Address {
+        let bytes: Vec<u8> = (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect();
+        Address::from_slice(&bytes)
+    }
+
+    #[test]
+    fn test_checksum_encode() {
+        let address = address_from_hex("5aaeb6053f3e94c9b9a09f33669435e7ef1beaed");
+        assert_eq!(
+            address.checksum_encode(),
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"
+        );
+        assert!(PlasmaAddressExt::is_zero(&Address::zero()));
+        assert!(!PlasmaAddressExt::is_zero(&address));
+    }
+
+    #[test]
+    fn test_registry() {
+        let registry = PlasmaAddressRegistry::default();
+        let address = address_from_hex("5aaeb6053f3e94c9b9a09f33669435e7ef1beaed");
+        assert_eq!(
+            registry.describe(address),
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"
+        );
+        registry.register(address, "DepositContract");
+        assert!(registry.is_registered(address));
+        assert_eq!(
+            registry.describe(address),
+            "DepositContract(0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed)"
+        );
+    }
+}
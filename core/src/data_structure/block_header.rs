@@ -0,0 +1,104 @@
+use abi_utils::{Decodable, Encodable, Error as AbiError, ErrorKind as AbiErrorKind};
+use bytes::Bytes;
+use ethabi::{ParamType, Token};
+use ethereum_types::H256;
+
+/// Header of plasma block without transactions and state updates,
+/// which is exchanged with light clients.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockHeader {
+    block_number: u64,
+    state_root: H256,
+    timestamp: u64,
+    /// Empty if the block isn't signed.
+    aggregator_signature: Bytes,
+}
+
+impl BlockHeader {
+    pub fn new(
+        block_number: u64,
+        state_root: H256,
+        timestamp: u64,
+        aggregator_signature: Bytes,
+    ) -> Self {
+        BlockHeader {
+            block_number,
+            state_root,
+            timestamp,
+            aggregator_signature,
+        }
+    }
+    pub fn get_block_number(&self) -> u64 {
+        self.block_number
+    }
+    pub fn get_state_root(&self) -> H256 {
+        self.state_root
+    }
+    pub fn get_timestamp(&self) -> u64 {
+        self.timestamp
+    }
+    pub fn get_aggregator_signature(&self) -> &Bytes {
+        &self.aggregator_signature
+    }
+}
+
+impl Encodable for BlockHeader {
+    fn to_tuple(&self) -> Vec<Token> {
+        vec![
+            Token::Uint(self.block_number.into()),
+            Token::FixedBytes(self.state_root.as_bytes().to_vec()),
+            Token::Uint(self.timestamp.into()),
+            Token::Bytes(self.aggregator_signature.to_vec()),
+        ]
+    }
+}
+
+impl Decodable for BlockHeader {
+    type Ok = Self;
+    fn from_tuple(tuple: &[Token]) -> Result<Self, AbiError> {
+        let block_number = tuple[0].clone().to_uint();
+        let state_root = tuple[1].clone().to_fixed_bytes();
+        let timestamp = tuple[2].clone().to_uint();
+        let aggregator_signature = tuple[3].clone().to_bytes();
+        if let (Some(block_number), Some(state_root), Some(timestamp), Some(aggregator_signature)) =
+            (block_number, state_root, timestamp, aggregator_signature)
+        {
+            Ok(BlockHeader::new(
+                block_number.as_u64(),
+                H256::from_slice(&state_root),
+                timestamp.as_u64(),
+                Bytes::from(aggregator_signature),
+            ))
+        } else {
+            Err(AbiError::from(AbiErrorKind::AbiDecode))
+        }
+    }
+    fn get_param_types() -> Vec<ParamType> {
+        vec![
+            ParamType::Uint(64),
+            ParamType::FixedBytes(32),
+            ParamType::Uint(64),
+            ParamType::Bytes,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlockHeader;
+    use abi_utils::{Decodable, Encodable};
+    use bytes::Bytes;
+    use ethereum_types::H256;
+
+    #[test]
+    fn test_abi_encode() {
+        let header = BlockHeader::new(
+            1,
+            H256::from_low_u64_be(2),
+            1_570_000_000,
+            Bytes::from(&b"signature"[..]),
+        );
+        let decoded = BlockHeader::from_abi(&header.to_abi()).unwrap();
+        assert_eq!(decoded, header);
+    }
+}
@@ -20,6 +20,26 @@ pub enum ErrorKind {
     AbiDecode,
     #[fail(display = "Invalid Transaction")]
     InvalidTransaction,
+    #[fail(display = "Invalid Split Point")]
+    InvalidSplitPoint,
+}
+
+/// error definition for parsing Range from string.
+#[derive(Fail, Debug, PartialEq)]
+pub enum ParseRangeError {
+    #[fail(display = "Invalid Range Format")]
+    InvalidFormat,
+    #[fail(display = "Invalid Range Number")]
+    InvalidNumber,
+    #[fail(display = "Range start must be less than end")]
+    InvalidBounds,
+}
+
+/// error definition for iterating values of Range.
+#[derive(Fail, Debug, PartialEq)]
+pub enum RangeError {
+    #[fail(display = "Range is too large to iterate")]
+    RangeTooLarge,
 }
 
 #[derive(Debug)]
@@ -0,0 +1,33 @@
+use std::fmt;
+
+/// Sequence number of transactions sent from the same address.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Nonce(pub u64);
+
+impl Nonce {
+    pub fn new(nonce: u64) -> Self {
+        Nonce(nonce)
+    }
+
+    /// Returns the nonce which should follow this one.
+    pub fn next(self) -> Self {
+        Nonce(self.0 + 1)
+    }
+}
+
+impl fmt::Display for Nonce {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Nonce;
+
+    #[test]
+    fn test_next() {
+        assert_eq!(Nonce::default().next(), Nonce::new(1));
+        assert!(Nonce::new(1) < Nonce::new(2));
+    }
+}
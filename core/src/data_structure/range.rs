@@ -1,8 +1,12 @@
+use super::error::{Error, ErrorKind, ParseRangeError, RangeError};
 use abi_utils::{Decodable, Encodable, Error as AbiError, ErrorKind as AbiErrorKind};
 use ethabi::Token;
 use std::cmp::{max, min};
+use std::fmt;
+use std::str::FromStr;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Range {
     start: u64,
     end: u64,
@@ -21,6 +25,58 @@ impl Range {
     pub fn get_amount(&self) -> u64 {
         self.end - self.start
     }
+    /// Returns random non-empty range where `0 <= start < end <= max_end`.
+    /// Panics if `max_end` is 0.
+    #[cfg(any(test, feature = "test-proptest"))]
+    pub fn random(max_end: u64) -> Range {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+        let start = rng.gen_range(0, max_end);
+        let end = rng.gen_range(start, max_end) + 1;
+        Range::new(start, end)
+    }
+    /// Returns the empty range `[0, 0)`.
+    pub fn empty() -> Self {
+        Range::new(0, 0)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+    /// Returns the overlapping part of two ranges, or None if they don't overlap.
+    pub fn intersection(&self, other: &Range) -> Option<Range> {
+        let start = max(self.start, other.start);
+        let end = min(self.end, other.end);
+        if start < end {
+            Some(Range::new(start, end))
+        } else {
+            None
+        }
+    }
+    /// Trims the range to fit within `outer`. Returns `Range::empty()` if no overlap.
+    pub fn clamp(&self, outer: Range) -> Range {
+        self.intersection(&outer).unwrap_or_else(Range::empty)
+    }
+    /// Returns the fraction of this range covered by `other`, from 0.0 to 1.0.
+    /// Returns 0.0 if this range is empty.
+    pub fn percentage_overlap(&self, other: Range) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        self.intersection(&other).map_or(0.0, |overlap| {
+            overlap.get_amount() as f64 / self.get_amount() as f64
+        })
+    }
+    /// Yields every value from `start` to `end - 1`.
+    pub fn iter(&self) -> impl Iterator<Item = u64> {
+        self.start..self.end
+    }
+    /// Same as `iter` but fails if the range has more than `max_items` values.
+    pub fn iter_safe(&self, max_items: usize) -> Result<impl Iterator<Item = u64>, RangeError> {
+        if !self.is_empty() && self.end - self.start > max_items as u64 {
+            return Err(RangeError::RangeTooLarge);
+        }
+        Ok(self.iter())
+    }
     pub fn get_overlapping_range(&self, b: &Range) -> Range {
         if self.start < b.start && b.start <= self.end {
             Range::new(b.start, self.end)
@@ -35,6 +91,17 @@ impl Range {
         let over2 = range.start < self.start && self.start <= range.end;
         over1 || over2
     }
+    /// Splits the range at `split_point` into `[start, split_point)` and `[split_point, end)`.
+    /// `split_point` must be strictly inside of the range.
+    pub fn split(&self, split_point: u64) -> Result<(Range, Range), Error> {
+        if split_point <= self.start || split_point >= self.end {
+            return Err(Error::from(ErrorKind::InvalidSplitPoint));
+        }
+        Ok((
+            Range::new(self.start, split_point),
+            Range::new(split_point, self.end),
+        ))
+    }
     pub fn is_subrange(&self, b: &Range) -> bool {
         self.get_start() <= b.get_start() && self.get_end() >= b.get_end()
     }
@@ -42,6 +109,23 @@ impl Range {
         let merged_ranges = Range::merge_ranges(ranges);
         merged_ranges.iter().any(|range| range.is_subrange(self))
     }
+    /// Merges overlapping and adjacent ranges into the minimal set of ranges sorted by start.
+    /// Empty ranges are dropped.
+    pub fn coalesce(mut ranges: Vec<Range>) -> Vec<Range> {
+        ranges.retain(|r| !r.is_empty());
+        ranges.sort_by_key(|r| r.start);
+        let mut coalesced: Vec<Range> = vec![];
+        for range in ranges {
+            if let Some(last) = coalesced.last_mut() {
+                if range.start <= last.end {
+                    last.end = max(last.end, range.end);
+                    continue;
+                }
+            }
+            coalesced.push(range);
+        }
+        coalesced
+    }
     pub fn merge_range(range1: &Range, range2: &Range) -> Option<Range> {
         if range1.overlap(range2) {
             let start = min(range1.start, range2.start);
@@ -75,6 +159,39 @@ impl Range {
     }
 }
 
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+/// Parses Range from `"start..end"` or `"start-end"`.
+impl FromStr for Range {
+    type Err = ParseRangeError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (start, end) = if let Some(pos) = s.find("..") {
+            (&s[..pos], &s[pos + 2..])
+        } else if let Some(pos) = s.find('-') {
+            (&s[..pos], &s[pos + 1..])
+        } else {
+            return Err(ParseRangeError::InvalidFormat);
+        };
+        let start = start
+            .trim()
+            .parse::<u64>()
+            .map_err(|_| ParseRangeError::InvalidNumber)?;
+        let end = end
+            .trim()
+            .parse::<u64>()
+            .map_err(|_| ParseRangeError::InvalidNumber)?;
+        if start >= end {
+            return Err(ParseRangeError::InvalidBounds);
+        }
+        Ok(Range::new(start, end))
+    }
+}
+
 impl Encodable for Range {
     fn to_tuple(&self) -> Vec<Token> {
         vec![Token::Uint(self.start.into()), Token::Uint(self.end.into())]
@@ -97,10 +214,34 @@ impl Decodable for Range {
     }
 }
 
+/// proptest strategies generating valid ranges.
+#[cfg(feature = "test-proptest")]
+pub mod strategy {
+    use super::Range;
+    use proptest::prelude::*;
+
+    /// Generates ranges where `0 <= start < end <= max_end`.
+    pub fn arb_range(max_end: u64) -> impl Strategy<Value = Range> {
+        (0..max_end)
+            .prop_flat_map(move |start| (Just(start), start + 1..=max_end))
+            .prop_map(|(start, end)| Range::new(start, end))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_random() {
+        for _ in 0..100 {
+            let range = Range::random(10);
+            assert!(range.get_start() < range.get_end());
+            assert!(range.get_end() <= 10);
+        }
+        assert_eq!(Range::random(1), Range::new(0, 1));
+    }
+
     #[test]
     fn test_is_subrange() {
         let range1 = Range::new(0, 10);
@@ -109,6 +250,107 @@ mod tests {
         assert!(range1.is_subrange(&range2));
     }
 
+    #[test]
+    fn test_split() {
+        let range = Range::new(0, 100);
+        assert_eq!(
+            range.split(40).unwrap(),
+            (Range::new(0, 40), Range::new(40, 100))
+        );
+        assert_eq!(
+            range.split(1).unwrap(),
+            (Range::new(0, 1), Range::new(1, 100))
+        );
+        assert_eq!(
+            range.split(99).unwrap(),
+            (Range::new(0, 99), Range::new(99, 100))
+        );
+    }
+
+    #[test]
+    fn test_split_with_invalid_point() {
+        let range = Range::new(10, 100);
+        assert!(range.split(0).is_err());
+        assert!(range.split(10).is_err());
+        assert!(range.split(100).is_err());
+        assert!(range.split(120).is_err());
+    }
+
+    #[test]
+    fn test_iter() {
+        let range = Range::new(5, 8);
+        assert_eq!(range.iter().collect::<Vec<u64>>(), vec![5, 6, 7]);
+        assert_eq!(Range::empty().iter().count(), 0);
+        assert_eq!(range.iter_safe(3).unwrap().count(), 3);
+        assert_eq!(range.iter_safe(2).err(), Some(RangeError::RangeTooLarge));
+    }
+
+    #[test]
+    fn test_coalesce() {
+        let ranges = vec![
+            Range::new(50, 60),
+            Range::new(0, 10),
+            Range::new(10, 20),
+            Range::new(15, 30),
+            Range::new(40, 40),
+            Range::new(55, 58),
+        ];
+        assert_eq!(
+            Range::coalesce(ranges),
+            vec![Range::new(0, 30), Range::new(50, 60)]
+        );
+        assert!(Range::coalesce(vec![]).is_empty());
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("0..100".parse::<Range>().unwrap(), Range::new(0, 100));
+        assert_eq!("10-20".parse::<Range>().unwrap(), Range::new(10, 20));
+        assert_eq!("100".parse::<Range>(), Err(ParseRangeError::InvalidFormat));
+        assert_eq!(
+            "a..100".parse::<Range>(),
+            Err(ParseRangeError::InvalidNumber)
+        );
+        assert_eq!(
+            "100..100".parse::<Range>(),
+            Err(ParseRangeError::InvalidBounds)
+        );
+        assert_eq!(
+            "100-10".parse::<Range>(),
+            Err(ParseRangeError::InvalidBounds)
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let range = Range::new(5, 80);
+        assert_eq!(range.to_string(), "5..80");
+        assert_eq!(range.to_string().parse::<Range>().unwrap(), range);
+    }
+
+    #[test]
+    fn test_intersection_and_clamp() {
+        let range = Range::new(10, 50);
+        assert_eq!(
+            range.intersection(&Range::new(30, 100)),
+            Some(Range::new(30, 50))
+        );
+        assert_eq!(range.intersection(&Range::new(50, 100)), None);
+        assert_eq!(range.clamp(Range::new(0, 20)), Range::new(10, 20));
+        assert_eq!(range.clamp(Range::new(0, 100)), range);
+        assert_eq!(range.clamp(Range::new(60, 100)), Range::empty());
+        assert!(range.clamp(Range::new(60, 100)).is_empty());
+    }
+
+    #[test]
+    fn test_percentage_overlap() {
+        let range = Range::new(0, 100);
+        assert_eq!(range.percentage_overlap(Range::new(50, 200)), 0.5);
+        assert_eq!(range.percentage_overlap(Range::new(0, 100)), 1.0);
+        assert_eq!(range.percentage_overlap(Range::new(100, 200)), 0.0);
+        assert_eq!(Range::new(10, 10).percentage_overlap(range), 0.0);
+    }
+
     #[test]
     fn test_merge_ranges() {
         let ranges = vec![Range::new(1, 2), Range::new(2, 3), Range::new(3, 12)];
@@ -0,0 +1,65 @@
+extern crate ethabi;
+
+use abi_derive::{AbiDecodable, AbiEncodable};
+use abi_utils::Integer;
+use bytes::Bytes;
+use ethabi::{ParamType, Token};
+use ethereum_types::H256;
+
+/// Proof that a transaction was included in a plasma block.
+/// `inclusion_proof` proves the state update made by the transaction under `state_root`.
+#[derive(Clone, Debug, PartialEq, Eq, AbiDecodable, AbiEncodable)]
+pub struct Receipt {
+    transaction_hash: H256,
+    block_number: Integer,
+    inclusion_proof: Bytes,
+    state_root: H256,
+}
+
+impl Receipt {
+    pub fn new(
+        transaction_hash: H256,
+        block_number: Integer,
+        inclusion_proof: Bytes,
+        state_root: H256,
+    ) -> Self {
+        Receipt {
+            transaction_hash,
+            block_number,
+            inclusion_proof,
+            state_root,
+        }
+    }
+    pub fn get_transaction_hash(&self) -> H256 {
+        self.transaction_hash
+    }
+    pub fn get_block_number(&self) -> Integer {
+        self.block_number
+    }
+    pub fn get_inclusion_proof(&self) -> &Bytes {
+        &self.inclusion_proof
+    }
+    pub fn get_state_root(&self) -> H256 {
+        self.state_root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Receipt;
+    use abi_utils::{Decodable, Encodable, Integer};
+    use bytes::Bytes;
+    use ethereum_types::H256;
+
+    #[test]
+    fn test_abi_encode() {
+        let receipt = Receipt::new(
+            H256::from_low_u64_be(1),
+            Integer(2),
+            Bytes::from(&b"proof"[..]),
+            H256::from_low_u64_be(3),
+        );
+        let decoded = Receipt::from_abi(&receipt.to_abi()).unwrap();
+        assert_eq!(decoded, receipt);
+    }
+}
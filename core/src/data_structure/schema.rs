@@ -0,0 +1,135 @@
+//! JSON schemas of data structures served by REST API.
+use super::{Metadata, Range, Transaction};
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{
+    ArrayValidation, InstanceType, Metadata as SchemaMetadata, ObjectValidation, Schema,
+    SchemaObject, StringValidation,
+};
+use schemars::JsonSchema;
+
+/// Schema of `0x` prefixed hex string. `byte_length` is None for variable length bytes.
+pub fn hex_schema(description: &str, byte_length: Option<usize>) -> Schema {
+    let pattern = match byte_length {
+        Some(len) => format!("^0x[0-9a-fA-F]{{{}}}$", len * 2),
+        None => "^0x([0-9a-fA-F]{2})*$".to_owned(),
+    };
+    SchemaObject {
+        metadata: Some(Box::new(SchemaMetadata {
+            description: Some(description.to_owned()),
+            ..Default::default()
+        })),
+        instance_type: Some(InstanceType::String.into()),
+        string: Some(Box::new(StringValidation {
+            pattern: Some(pattern),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+    .into()
+}
+
+pub fn address_schema() -> Schema {
+    hex_schema("20 bytes hex-encoded address", Some(20))
+}
+
+/// Schema of object whose properties are all required.
+pub fn object_schema(properties: Vec<(&str, Schema)>) -> Schema {
+    let mut object = ObjectValidation::default();
+    for (name, schema) in properties {
+        object.required.insert(name.to_owned());
+        object.properties.insert(name.to_owned(), schema);
+    }
+    SchemaObject {
+        instance_type: Some(InstanceType::Object.into()),
+        object: Some(Box::new(object)),
+        ..Default::default()
+    }
+    .into()
+}
+
+pub fn array_schema(items: Schema) -> Schema {
+    SchemaObject {
+        instance_type: Some(InstanceType::Array.into()),
+        array: Some(Box::new(ArrayValidation {
+            items: Some(items.into()),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+    .into()
+}
+
+impl JsonSchema for Metadata {
+    fn schema_name() -> String {
+        "Metadata".to_owned()
+    }
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        object_schema(vec![("from", address_schema()), ("to", address_schema())])
+    }
+}
+
+impl JsonSchema for Transaction {
+    fn schema_name() -> String {
+        "Transaction".to_owned()
+    }
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        object_schema(vec![
+            ("deposit_contract_address", address_schema()),
+            ("range", gen.subschema_for::<Range>()),
+            ("parameters", hex_schema("hex-encoded ABI parameters", None)),
+            (
+                "signature",
+                hex_schema("65 bytes hex-encoded signature", Some(65)),
+            ),
+            ("metadata", gen.subschema_for::<Metadata>()),
+            ("nonce", gen.subschema_for::<u64>()),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schemars::schema_for;
+    use serde_json::json;
+
+    #[test]
+    fn test_range_schema() {
+        let schema = schema_for!(Range);
+        assert_eq!(
+            serde_json::to_value(&schema).unwrap(),
+            json!({
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "title": "Range",
+                "type": "object",
+                "required": ["end", "start"],
+                "properties": {
+                    "start": {
+                        "type": "integer",
+                        "format": "uint64",
+                        "minimum": 0.0
+                    },
+                    "end": {
+                        "type": "integer",
+                        "format": "uint64",
+                        "minimum": 0.0
+                    }
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_transaction_schema() {
+        let schema = serde_json::to_value(&schema_for!(Transaction)).unwrap();
+        assert_eq!(
+            schema["properties"]["signature"]["pattern"],
+            json!("^0x[0-9a-fA-F]{130}$")
+        );
+        assert_eq!(
+            schema["properties"]["range"]["$ref"],
+            json!("#/definitions/Range")
+        );
+        assert_eq!(schema["properties"]["nonce"]["format"], json!("uint64"));
+    }
+}
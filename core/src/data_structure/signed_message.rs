@@ -0,0 +1,140 @@
+use abi_utils::{Decodable, Encodable, Error as AbiError, ErrorKind as AbiErrorKind};
+use bytes::Bytes;
+use ethabi::{ParamType, Token};
+use ethereum_types::Address;
+use ethsign::Signature;
+use tiny_keccak::Keccak;
+
+/// Generic off-chain payload signed by `signer`.
+/// The signature isn't verified when decoded, call `verify_signature` to check it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignedMessage<T: Encodable> {
+    payload: T,
+    signature: Bytes,
+    signer: Address,
+}
+
+impl<T: Encodable> SignedMessage<T> {
+    pub fn new(payload: T, signature: Bytes, signer: Address) -> Self {
+        SignedMessage {
+            payload,
+            signature,
+            signer,
+        }
+    }
+    pub fn get_payload(&self) -> &T {
+        &self.payload
+    }
+    pub fn get_signature(&self) -> &Bytes {
+        &self.signature
+    }
+    pub fn get_signer(&self) -> Address {
+        self.signer
+    }
+    /// Returns true if the signature of payload is made by signer.
+    /// The signature is `v ++ r ++ s` of keccak256 hash of ABI encoded payload.
+    pub fn verify_signature(&self) -> bool {
+        let mut sha3 = Keccak::new_sha3_256();
+        sha3.update(&self.payload.to_abi());
+        let mut hash: [u8; 32] = [0; 32];
+        sha3.finalize(&mut hash);
+        recover_signer(&self.signature, &hash) == Some(self.signer)
+    }
+}
+
+/// Recovers signer address from `signature` in `v ++ r ++ s` format of `hash`.
+/// Returns None if the signature is malformed.
+pub(crate) fn recover_signer(signature: &Bytes, hash: &[u8; 32]) -> Option<Address> {
+    if signature.len() != 65 {
+        return None;
+    }
+    let mut r = [0u8; 32];
+    let mut s = [0u8; 32];
+    r.copy_from_slice(&signature[1..33]);
+    s.copy_from_slice(&signature[33..65]);
+    let signature = Signature {
+        v: signature[0],
+        r,
+        s,
+    };
+    signature
+        .recover(hash)
+        .ok()
+        .map(|public_key| Address::from_slice(public_key.address()))
+}
+
+impl<T: Encodable> Encodable for SignedMessage<T> {
+    fn to_tuple(&self) -> Vec<Token> {
+        vec![
+            Token::Tuple(self.payload.to_tuple()),
+            Token::Bytes(self.signature.to_vec()),
+            Token::Address(self.signer),
+        ]
+    }
+}
+
+impl<T> Decodable for SignedMessage<T>
+where
+    T: Encodable + Decodable<Ok = T>,
+{
+    type Ok = Self;
+    fn from_tuple(tuple: &[Token]) -> Result<Self, AbiError> {
+        let payload = tuple[0].clone().to_tuple();
+        let signature = tuple[1].clone().to_bytes();
+        let signer = tuple[2].clone().to_address();
+        if let (Some(payload), Some(signature), Some(signer)) = (payload, signature, signer) {
+            Ok(SignedMessage::new(
+                T::from_tuple(&payload)?,
+                Bytes::from(signature),
+                signer,
+            ))
+        } else {
+            Err(AbiError::from(AbiErrorKind::AbiDecode))
+        }
+    }
+    fn get_param_types() -> Vec<ParamType> {
+        vec![
+            ParamType::Tuple(T::get_param_types()),
+            ParamType::Bytes,
+            ParamType::Address,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SignedMessage;
+    use crate::data_structure::{Range, TransactionParams};
+    use abi_utils::{Decodable, Encodable};
+    use bytes::Bytes;
+    use ethereum_types::Address;
+    use ethsign::SecretKey;
+    use tiny_keccak::Keccak;
+
+    fn sign(secret_key: &SecretKey, message: &[u8]) -> Bytes {
+        let mut sha3 = Keccak::new_sha3_256();
+        sha3.update(message);
+        let mut hash: [u8; 32] = [0; 32];
+        sha3.finalize(&mut hash);
+        let signature = secret_key.sign(&hash).unwrap();
+        let mut bytes = vec![signature.v];
+        bytes.extend([signature.r, signature.s].concat());
+        Bytes::from(bytes)
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let secret_key = SecretKey::from_raw(&[1u8; 32]).unwrap();
+        let signer = Address::from_slice(secret_key.public().address());
+        let payload = TransactionParams::new(Address::zero(), Range::new(0, 10), Bytes::new());
+        let signature = sign(&secret_key, &payload.to_abi());
+        let signed_message = SignedMessage::new(payload.clone(), signature.clone(), signer);
+        assert!(signed_message.verify_signature());
+        let decoded: SignedMessage<TransactionParams> =
+            SignedMessage::from_abi(&signed_message.to_abi()).unwrap();
+        assert_eq!(decoded, signed_message);
+
+        let wrong_signer = SignedMessage::new(payload, signature, Address::zero());
+        assert!(!wrong_signer.verify_signature());
+    }
+}
@@ -1,20 +1,105 @@
 extern crate ethereum_types;
 extern crate tiny_keccak;
 
-use super::{Metadata, Range};
-use abi_derive::{AbiDecodable, AbiEncodable};
-use abi_utils::abi::Encodable;
+use super::{Metadata, Nonce, Range};
+use abi_utils::abi::{Decodable, Encodable};
+use abi_utils::{Error as AbiError, ErrorKind as AbiErrorKind};
 use bytes::Bytes;
 use ethabi::{ParamType, Token};
-use ethereum_types::Address;
+use ethereum_types::{Address, H256, U256};
+use std::fmt;
+use std::sync::RwLock;
 use tiny_keccak::Keccak;
 
-#[derive(Clone, Debug, PartialEq, Eq, AbiEncodable, AbiDecodable)]
+/// Selector of `transfer(address,uint256)`.
+pub const TRANSFER_SELECTOR: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+/// Selector of `transferFrom(address,address,uint256)`.
+pub const TRANSFER_FROM_SELECTOR: [u8; 4] = [0x23, 0xb8, 0x72, 0xdd];
+/// Selector of `approve(address,uint256)`.
+pub const APPROVE_SELECTOR: [u8; 4] = [0x09, 0x5e, 0xa7, 0xb3];
+/// Selector of `deposit(uint256)`.
+pub const DEPOSIT_SELECTOR: [u8; 4] = [0xb6, 0xb5, 0x5f, 0x25];
+/// Selector of `withdraw(uint256)`.
+pub const WITHDRAW_SELECTOR: [u8; 4] = [0x2e, 0x1a, 0x7d, 0x4d];
+/// Selector of `exit(uint256)`.
+pub const EXIT_SELECTOR: [u8; 4] = [0x7f, 0x86, 0x61, 0xa1];
+
+/// Cache of decoded `parameters`. It's ignored by comparison.
+#[derive(Default)]
+struct DecodedParameters(RwLock<Option<(Vec<ParamType>, Vec<Token>)>>);
+
+impl Clone for DecodedParameters {
+    fn clone(&self) -> Self {
+        DecodedParameters(RwLock::new(self.0.read().unwrap().clone()))
+    }
+}
+
+impl PartialEq for DecodedParameters {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for DecodedParameters {}
+
+impl DecodedParameters {
+    /// Decodes `parameters` with `param_types`, reusing the last result for the same `param_types`.
+    fn decode(
+        &self,
+        parameters: &Bytes,
+        param_types: &[ParamType],
+    ) -> Result<Vec<Token>, AbiError> {
+        if let Some((cached_types, tokens)) = &*self.0.read().unwrap() {
+            if cached_types.as_slice() == param_types {
+                return Ok(tokens.clone());
+            }
+        }
+        let tokens = ethabi::decode(param_types, parameters)?;
+        *self.0.write().unwrap() = Some((param_types.to_vec(), tokens.clone()));
+        Ok(tokens)
+    }
+}
+
+impl fmt::Debug for DecodedParameters {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DecodedParameters")
+    }
+}
+
+/// Cache of the hash of ABI encoded params. It's ignored by comparison.
+#[derive(Default)]
+struct ParametersHash(RwLock<Option<H256>>);
+
+impl Clone for ParametersHash {
+    fn clone(&self) -> Self {
+        ParametersHash(RwLock::new(*self.0.read().unwrap()))
+    }
+}
+
+impl PartialEq for ParametersHash {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for ParametersHash {}
+
+impl fmt::Debug for ParametersHash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ParametersHash")
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 /// Transaction without signature
 pub struct TransactionParams {
     deposit_contract_address: Address,
     range: Range,
     parameters: Bytes,
+    /// Nonce of the sender. It's a part of the signed body.
+    nonce: Nonce,
+    decoded_parameters: DecodedParameters,
+    parameters_hash: ParametersHash,
 }
 
 impl TransactionParams {
@@ -23,11 +108,131 @@ impl TransactionParams {
             deposit_contract_address,
             range,
             parameters,
+            nonce: Nonce::default(),
+            decoded_parameters: Default::default(),
+            parameters_hash: Default::default(),
+        }
+    }
+    pub fn with_nonce(mut self, nonce: Nonce) -> Self {
+        self.nonce = nonce;
+        self
+    }
+    pub fn get_nonce(&self) -> Nonce {
+        self.nonce
+    }
+    /// Creates TransactionParams whose parameters are ABI encoded `param_values`.
+    /// Returns error if `param_values` don't match `param_types`.
+    pub fn with_parameters_typed(
+        deposit_contract_address: Address,
+        range: Range,
+        param_types: &[ParamType],
+        param_values: &[Token],
+    ) -> Result<Self, AbiError> {
+        let parameters = ethabi::encode(param_values);
+        // encoded bytes decode to the same tokens only if the types match
+        if param_types.len() != param_values.len()
+            || ethabi::decode(param_types, &parameters)?.as_slice() != param_values
+        {
+            return Err(AbiError::from(AbiErrorKind::AbiDecode));
+        }
+        let params =
+            TransactionParams::new(deposit_contract_address, range, Bytes::from(parameters));
+        *params.decoded_parameters.0.write().unwrap() =
+            Some((param_types.to_vec(), param_values.to_vec()));
+        Ok(params)
+    }
+    /// Returns hash of `to_abi()` to be used as cache key.
+    /// It's computed at the first call and cached because fields are never mutated.
+    pub fn parameters_hash(&self) -> H256 {
+        if let Some(hash) = *self.parameters_hash.0.read().unwrap() {
+            return hash;
+        }
+        let mut hasher = Keccak::new_sha3_256();
+        hasher.update(&self.to_abi());
+        let mut result: [u8; 32] = [0; 32];
+        hasher.finalize(&mut result);
+        let hash = H256::from(result);
+        *self.parameters_hash.0.write().unwrap() = Some(hash);
+        hash
+    }
+    /// Returns the first 4 bytes of keccak256 of `operation_name`
+    /// such as `transfer(address,uint256)`.
+    pub fn method_selector(operation_name: &str) -> [u8; 4] {
+        let mut hasher = Keccak::new_keccak256();
+        hasher.update(operation_name.as_bytes());
+        let mut result: [u8; 32] = [0; 32];
+        hasher.finalize(&mut result);
+        let mut selector = [0u8; 4];
+        selector.copy_from_slice(&result[..4]);
+        selector
+    }
+    /// Returns the first 4 bytes of `parameters`, padded with zeros if `parameters` are shorter.
+    pub fn get_method_selector(&self) -> [u8; 4] {
+        let mut selector = [0u8; 4];
+        let len = self.parameters.len().min(4);
+        selector[..len].copy_from_slice(&self.parameters[..len]);
+        selector
+    }
+    /// Decodes `parameters` with `param_types`.
+    /// The result is cached while the same `param_types` are given.
+    pub fn decode_parameters(&self, param_types: &[ParamType]) -> Result<Vec<Token>, AbiError> {
+        self.decoded_parameters
+            .decode(&self.parameters, param_types)
+    }
+}
+
+impl Encodable for TransactionParams {
+    fn to_tuple(&self) -> Vec<Token> {
+        vec![
+            Token::Address(self.deposit_contract_address),
+            Token::Tuple(self.range.to_tuple()),
+            Token::Bytes(self.parameters.to_vec()),
+            Token::Uint(U256::from(self.nonce.0)),
+        ]
+    }
+}
+
+/// Returns error instead of truncating nonce larger than `u64`.
+fn decode_nonce(nonce: U256) -> Result<Nonce, AbiError> {
+    if nonce > U256::from(u64::max_value()) {
+        return Err(AbiError::from(AbiErrorKind::AbiDecode));
+    }
+    Ok(Nonce(nonce.as_u64()))
+}
+
+impl Decodable for TransactionParams {
+    type Ok = TransactionParams;
+    fn from_tuple(tuple: &[Token]) -> Result<Self, AbiError> {
+        if tuple.len() != 4 {
+            return Err(AbiError::from(AbiErrorKind::AbiDecode));
+        }
+        if let (Some(deposit_contract_address), Some(range), Some(parameters), Some(nonce)) = (
+            tuple[0].clone().to_address(),
+            tuple[1].clone().to_tuple(),
+            tuple[2].clone().to_bytes(),
+            tuple[3].clone().to_uint(),
+        ) {
+            Ok(TransactionParams::new(
+                deposit_contract_address,
+                Range::from_tuple(&range)?,
+                Bytes::from(parameters),
+            )
+            .with_nonce(decode_nonce(nonce)?))
+        } else {
+            Err(AbiError::from(AbiErrorKind::AbiDecode))
         }
     }
+    fn get_param_types() -> Vec<ParamType> {
+        vec![
+            ParamType::Address,
+            ParamType::Tuple(Range::get_param_types()),
+            ParamType::Bytes,
+            ParamType::Uint(64),
+        ]
+    }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, AbiEncodable, AbiDecodable)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 /// ## struct Transaction
 /// - has a `deposit_contract_address`
 /// - has a `start` (A range element)
@@ -36,6 +241,7 @@ impl TransactionParams {
 /// - has many `parameters`
 /// - has a `signature` (for now)
 /// - has a `metadata`
+/// - has a `nonce`
 /// - Traits
 ///   - Encodable
 ///   - Decodable
@@ -45,6 +251,8 @@ pub struct Transaction {
     parameters: Bytes,
     signature: Bytes,
     metadata: Metadata,
+    nonce: Nonce,
+    decoded_parameters: DecodedParameters,
 }
 
 impl Transaction {
@@ -66,9 +274,16 @@ impl Transaction {
             parameters,
             signature,
             metadata,
+            nonce: Nonce::default(),
+            decoded_parameters: Default::default(),
         }
     }
 
+    pub fn with_nonce(mut self, nonce: Nonce) -> Self {
+        self.nonce = nonce;
+        self
+    }
+
     pub fn from_params(
         transaction_params: TransactionParams,
         signature: Bytes,
@@ -81,6 +296,56 @@ impl Transaction {
             signature,
             metadata,
         )
+        .with_nonce(transaction_params.nonce)
+    }
+
+    /// ### Transaction.from_abi_lenient()
+    /// Decodes current ABI, legacy ABI which doesn't have `metadata` nor `nonce`,
+    /// and legacy ABI which doesn't have `nonce`.
+    /// `Metadata::default()` and `Nonce::default()` are used for missing fields.
+    /// This is only for transition period and will be deprecated.
+    pub fn from_abi_lenient(data: &[u8]) -> Result<Transaction, AbiError> {
+        // Offset of `parameters` points right after the head, whose size differs by ABI version.
+        let head_size = if data.len() >= 128 {
+            U256::from_big_endian(&data[96..128])
+        } else {
+            U256::zero()
+        };
+        if head_size == U256::from(7 * 32) {
+            let mut param_types = Transaction::get_param_types();
+            param_types.pop();
+            let mut tuple = ethabi::decode(&param_types, data)?;
+            tuple.push(Token::Uint(U256::zero()));
+            return Transaction::from_tuple(&tuple);
+        }
+        if head_size != U256::from(5 * 32) {
+            return Transaction::from_abi(data);
+        }
+        let tuple = ethabi::decode(
+            &[
+                ParamType::Address,
+                ParamType::Tuple(Range::get_param_types()),
+                ParamType::Bytes,
+                ParamType::Bytes,
+            ],
+            data,
+        )?;
+        if let (Some(deposit_contract_address), Some(range), Some(parameters), Some(signature)) = (
+            tuple[0].clone().to_address(),
+            tuple[1].clone().to_tuple(),
+            tuple[2].clone().to_bytes(),
+            tuple[3].clone().to_bytes(),
+        ) {
+            Ok(Transaction::new(
+                deposit_contract_address,
+                Range::from_tuple(&range)?,
+                Bytes::from(parameters),
+                Bytes::from(signature),
+                Metadata::default(),
+            ))
+        } else {
+            Err(AbiError::from(AbiErrorKind::AbiDecode))
+        }
     }
 
     /// ### tx.to_body_abi()
@@ -93,6 +358,7 @@ impl Transaction {
             Token::Address(self.deposit_contract_address),
             Token::Tuple(self.range.to_tuple()),
             Token::Bytes(self.parameters.to_vec()),
+            Token::Uint(U256::from(self.nonce.0)),
         ])
     }
     /// ### Transaction.create_method_id()
@@ -127,14 +393,137 @@ impl Transaction {
     pub fn get_metadata(&self) -> &Metadata {
         &self.metadata
     }
+    pub fn get_nonce(&self) -> Nonce {
+        self.nonce
+    }
+    /// ### tx.get_parameters_as_tokens()
+    /// A function to decode `parameters` with `param_types`.
+    /// The result is cached while the same `param_types` are given.
+    /// ```ignore
+    /// let tokens = tx.get_parameters_as_tokens(&[ParamType::Address])?;
+    /// ```
+    pub fn get_parameters_as_tokens(
+        &self,
+        param_types: &[ParamType],
+    ) -> Result<Vec<Token>, AbiError> {
+        self.decoded_parameters
+            .decode(&self.parameters, param_types)
+    }
+    /// ### tx.to_pretty_hex()
+    /// A function to show each field of `to_tuple()` in a line for debugging
+    /// ```ignore
+    /// println!("{}", tx.to_pretty_hex());
+    /// ```
+    pub fn to_pretty_hex(&self) -> String {
+        let labels = [
+            "plasma_contract",
+            "range",
+            "parameters (hex)",
+            "signature (hex)",
+            "metadata",
+            "nonce",
+        ];
+        labels
+            .iter()
+            .zip(self.to_tuple().iter())
+            .map(|(label, token)| format!("{}: {}", label, pretty_token(token)))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+impl Encodable for Transaction {
+    fn to_tuple(&self) -> Vec<Token> {
+        vec![
+            Token::Address(self.deposit_contract_address),
+            Token::Tuple(self.range.to_tuple()),
+            Token::Bytes(self.parameters.to_vec()),
+            Token::Bytes(self.signature.to_vec()),
+            Token::Tuple(self.metadata.to_tuple()),
+            Token::Uint(U256::from(self.nonce.0)),
+        ]
+    }
+}
+
+impl Decodable for Transaction {
+    type Ok = Transaction;
+    fn from_tuple(tuple: &[Token]) -> Result<Self, AbiError> {
+        if tuple.len() != 6 {
+            return Err(AbiError::from(AbiErrorKind::AbiDecode));
+        }
+        if let (
+            Some(deposit_contract_address),
+            Some(range),
+            Some(parameters),
+            Some(signature),
+            Some(metadata),
+            Some(nonce),
+        ) = (
+            tuple[0].clone().to_address(),
+            tuple[1].clone().to_tuple(),
+            tuple[2].clone().to_bytes(),
+            tuple[3].clone().to_bytes(),
+            tuple[4].clone().to_tuple(),
+            tuple[5].clone().to_uint(),
+        ) {
+            Ok(Transaction::new(
+                deposit_contract_address,
+                Range::from_tuple(&range)?,
+                Bytes::from(parameters),
+                Bytes::from(signature),
+                Metadata::from_tuple(&metadata)?,
+            )
+            .with_nonce(decode_nonce(nonce)?))
+        } else {
+            Err(AbiError::from(AbiErrorKind::AbiDecode))
+        }
+    }
+    fn get_param_types() -> Vec<ParamType> {
+        vec![
+            ParamType::Address,
+            ParamType::Tuple(Range::get_param_types()),
+            ParamType::Bytes,
+            ParamType::Bytes,
+            ParamType::Tuple(Metadata::get_param_types()),
+            ParamType::Uint(64),
+        ]
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("0x{}", hex)
+}
+
+fn pretty_token(token: &Token) -> String {
+    match token {
+        Token::Address(address) => to_hex(address.as_bytes()),
+        Token::Bytes(bytes) => to_hex(bytes),
+        Token::Uint(n) => n.to_string(),
+        Token::Tuple(tokens) => {
+            if let Ok(range) = Range::from_tuple(tokens) {
+                range.to_string()
+            } else {
+                tokens
+                    .iter()
+                    .map(pretty_token)
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            }
+        }
+        _ => format!("{:?}", token),
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Metadata, Range, Transaction};
+    use super::{
+        Metadata, Nonce, Range, Transaction, TransactionParams, EXIT_SELECTOR, TRANSFER_SELECTOR,
+    };
     use abi_utils::{Decodable, Encodable};
     use bytes::Bytes;
-    use ethereum_types::Address;
+    use ethabi::Token;
+    use ethereum_types::{Address, U256};
 
     #[test]
     fn test_abi_encode() {
@@ -155,4 +544,225 @@ mod tests {
             transaction.get_range().get_start()
         );
     }
+
+    #[test]
+    fn test_from_abi_lenient_with_legacy_abi() {
+        let range = Range::new(0, 100);
+        let legacy_encoded = ethabi::encode(&[
+            Token::Address(Address::zero()),
+            Token::Tuple(range.to_tuple()),
+            Token::Bytes(b"parameters".to_vec()),
+            Token::Bytes(b"signature".to_vec()),
+        ]);
+        let decoded = Transaction::from_abi_lenient(&legacy_encoded).unwrap();
+        assert_eq!(decoded.get_range(), range);
+        assert_eq!(decoded.get_parameters(), &Bytes::from(&b"parameters"[..]));
+        assert_eq!(decoded.get_signature(), &Bytes::from(&b"signature"[..]));
+        assert_eq!(decoded.get_metadata(), &Metadata::default());
+    }
+
+    #[test]
+    fn test_from_abi_lenient_with_current_abi() {
+        let metadata = Metadata::new(Address::zero(), Address::from_low_u64_be(1));
+        let transaction = Transaction::new(
+            Address::zero(),
+            Range::new(0, 100),
+            Bytes::from(&b"parameters"[..]),
+            Bytes::from(&b"signature"[..]),
+            metadata,
+        );
+        let decoded = Transaction::from_abi_lenient(&transaction.to_abi()).unwrap();
+        assert_eq!(decoded, transaction);
+    }
+
+    #[test]
+    fn test_from_abi_lenient_without_nonce() {
+        let metadata = Metadata::new(Address::zero(), Address::from_low_u64_be(1));
+        let range = Range::new(0, 100);
+        let encoded = ethabi::encode(&[
+            Token::Address(Address::zero()),
+            Token::Tuple(range.to_tuple()),
+            Token::Bytes(b"parameters".to_vec()),
+            Token::Bytes(b"signature".to_vec()),
+            Token::Tuple(metadata.to_tuple()),
+        ]);
+        let decoded = Transaction::from_abi_lenient(&encoded).unwrap();
+        assert_eq!(decoded.get_range(), range);
+        assert_eq!(decoded.get_metadata(), &metadata);
+        assert_eq!(decoded.get_nonce(), Nonce::default());
+    }
+
+    #[test]
+    fn test_nonce_is_signed() {
+        let params = TransactionParams::new(Address::zero(), Range::new(0, 100), Bytes::new())
+            .with_nonce(Nonce(3));
+        let decoded = TransactionParams::from_abi(&params.to_abi()).unwrap();
+        assert_eq!(decoded.get_nonce(), Nonce(3));
+        let transaction =
+            Transaction::from_params(params.clone(), Bytes::new(), Metadata::default());
+        assert_eq!(transaction.get_nonce(), Nonce(3));
+        assert_eq!(transaction.to_body_abi(), params.to_abi());
+        assert_ne!(
+            transaction.to_body_abi(),
+            transaction.clone().with_nonce(Nonce(4)).to_body_abi()
+        );
+        let decoded = Transaction::from_abi(&transaction.to_abi()).unwrap();
+        assert_eq!(decoded, transaction);
+        assert_eq!(
+            Transaction::from_abi_lenient(&transaction.to_abi()).unwrap(),
+            transaction
+        );
+    }
+
+    #[test]
+    fn test_decode_nonce_larger_than_u64() {
+        let params = TransactionParams::new(Address::zero(), Range::new(0, 100), Bytes::new());
+        let mut tuple = params.to_tuple();
+        tuple[3] = Token::Uint(U256::from(u64::max_value()) + 1);
+        assert!(TransactionParams::from_abi(&ethabi::encode(&tuple)).is_err());
+        let transaction = Transaction::from_params(params, Bytes::new(), Metadata::default());
+        let mut tuple = transaction.to_tuple();
+        tuple[5] = Token::Uint(U256::max_value());
+        assert!(Transaction::from_abi(&ethabi::encode(&tuple)).is_err());
+    }
+
+    #[test]
+    fn test_to_pretty_hex() {
+        let transaction = Transaction::new(
+            Address::zero(),
+            Range::new(0, 100),
+            Bytes::from(&b"\x01\xab"[..]),
+            Bytes::from(&b"\xff"[..]),
+            Metadata::default(),
+        );
+        let lines: Vec<String> = transaction
+            .to_pretty_hex()
+            .lines()
+            .map(|l| l.to_string())
+            .collect();
+        assert_eq!(
+            lines[0],
+            "plasma_contract: 0x0000000000000000000000000000000000000000"
+        );
+        assert_eq!(lines[1], "range: 0..100");
+        assert_eq!(lines[2], "parameters (hex): 0x01ab");
+        assert_eq!(lines[3], "signature (hex): 0xff");
+        assert_eq!(lines[5], "nonce: 0");
+        assert_eq!(lines.len(), 6);
+    }
+
+    #[test]
+    fn test_decode_parameters() {
+        let parameters = ethabi::encode(&[Token::Uint(10.into()), Token::Address(Address::zero())]);
+        let params =
+            TransactionParams::new(Address::zero(), Range::new(0, 100), Bytes::from(parameters));
+        let param_types = [ethabi::ParamType::Uint(256), ethabi::ParamType::Address];
+        let tokens = params.decode_parameters(&param_types).unwrap();
+        assert_eq!(tokens[0], Token::Uint(10.into()));
+        assert_eq!(tokens[1], Token::Address(Address::zero()));
+        // cached result
+        assert_eq!(params.decode_parameters(&param_types).unwrap(), tokens);
+        let decoded = TransactionParams::from_abi(&params.to_abi()).unwrap();
+        assert_eq!(decoded, params);
+    }
+
+    #[test]
+    fn test_get_parameters_as_tokens() {
+        let parameters = ethabi::encode(&[Token::Uint(10.into()), Token::Address(Address::zero())]);
+        let transaction = Transaction::new(
+            Address::zero(),
+            Range::new(0, 100),
+            Bytes::from(parameters),
+            Bytes::default(),
+            Metadata::default(),
+        );
+        let param_types = [ethabi::ParamType::Uint(256), ethabi::ParamType::Address];
+        let tokens = transaction.get_parameters_as_tokens(&param_types).unwrap();
+        assert_eq!(
+            tokens,
+            vec![Token::Uint(10.into()), Token::Address(Address::zero())]
+        );
+        // cached result
+        assert_eq!(
+            transaction.get_parameters_as_tokens(&param_types).unwrap(),
+            tokens
+        );
+        assert!(transaction
+            .get_parameters_as_tokens(&[ethabi::ParamType::Bytes])
+            .is_err());
+        let decoded = Transaction::from_abi(&transaction.to_abi()).unwrap();
+        assert_eq!(decoded, transaction);
+    }
+
+    #[test]
+    fn test_parameters_hash() {
+        let params = TransactionParams::new(Address::zero(), Range::new(0, 100), Bytes::new());
+        let hash = params.parameters_hash();
+        assert_eq!(params.parameters_hash(), hash);
+        assert_eq!(params.clone().parameters_hash(), hash);
+        assert_ne!(
+            TransactionParams::new(Address::zero(), Range::new(0, 99), Bytes::new())
+                .parameters_hash(),
+            hash
+        );
+    }
+
+    #[test]
+    fn test_with_parameters_typed() {
+        let param_types = [ethabi::ParamType::Uint(256), ethabi::ParamType::Address];
+        let param_values = [Token::Uint(10.into()), Token::Address(Address::zero())];
+        let params = TransactionParams::with_parameters_typed(
+            Address::zero(),
+            Range::new(0, 100),
+            &param_types,
+            &param_values,
+        )
+        .unwrap();
+        assert_eq!(
+            params,
+            TransactionParams::new(
+                Address::zero(),
+                Range::new(0, 100),
+                Bytes::from(ethabi::encode(&param_values))
+            )
+        );
+        assert_eq!(
+            params.decode_parameters(&param_types).unwrap(),
+            param_values
+        );
+        assert!(TransactionParams::with_parameters_typed(
+            Address::zero(),
+            Range::new(0, 100),
+            &param_types[..1],
+            &param_values,
+        )
+        .is_err());
+        assert!(TransactionParams::with_parameters_typed(
+            Address::zero(),
+            Range::new(0, 100),
+            &[ethabi::ParamType::Bool],
+            &[Token::Bytes(vec![1, 2, 3])],
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_method_selector() {
+        assert_eq!(
+            TransactionParams::method_selector("transfer(address,uint256)"),
+            TRANSFER_SELECTOR
+        );
+        assert_eq!(
+            TransactionParams::method_selector("exit(uint256)"),
+            EXIT_SELECTOR
+        );
+        let mut parameters = TRANSFER_SELECTOR.to_vec();
+        parameters.extend_from_slice(&[0u8; 64]);
+        let params =
+            TransactionParams::new(Address::zero(), Range::new(0, 100), Bytes::from(parameters));
+        assert_eq!(params.get_method_selector(), TRANSFER_SELECTOR);
+        let short =
+            TransactionParams::new(Address::zero(), Range::new(0, 100), Bytes::from(&[1u8][..]));
+        assert_eq!(short.get_method_selector(), [1, 0, 0, 0]);
+    }
 }
@@ -0,0 +1,142 @@
+use super::signed_message::recover_signer;
+use super::Transaction;
+use abi_utils::{Decodable, Encodable, Error as AbiError, ErrorKind as AbiErrorKind};
+use bytes::Bytes;
+use ethabi::{ParamType, Token};
+use ethereum_types::{Address, H256};
+use tiny_keccak::Keccak;
+
+fn hash(message: &[u8]) -> [u8; 32] {
+    let mut sha3 = Keccak::new_sha3_256();
+    sha3.update(message);
+    let mut res: [u8; 32] = [0; 32];
+    sha3.finalize(&mut res);
+    res
+}
+
+/// Transactions which must be applied all together or not at all.
+/// `batch_signature` is the signature of `get_batch_hash()` by the sender.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransactionBatch {
+    transactions: Vec<Transaction>,
+    batch_signature: Bytes,
+}
+
+impl TransactionBatch {
+    pub fn new(transactions: Vec<Transaction>, batch_signature: Bytes) -> Self {
+        TransactionBatch {
+            transactions,
+            batch_signature,
+        }
+    }
+    pub fn get_transactions(&self) -> &[Transaction] {
+        &self.transactions
+    }
+    pub fn get_batch_signature(&self) -> &Bytes {
+        &self.batch_signature
+    }
+    /// Returns the hash of concatenated hashes of ABI encoded transactions.
+    pub fn get_batch_hash(&self) -> H256 {
+        let hashes: Vec<u8> = self
+            .transactions
+            .iter()
+            .flat_map(|tx| hash(&tx.to_abi()).to_vec())
+            .collect();
+        H256::from(hash(&hashes))
+    }
+    /// Returns true if `batch_signature` is made by `sender`.
+    pub fn verify_batch_signature(&self, sender: Address) -> bool {
+        let batch_hash: [u8; 32] = self.get_batch_hash().into();
+        recover_signer(&self.batch_signature, &batch_hash) == Some(sender)
+    }
+}
+
+impl Encodable for TransactionBatch {
+    fn to_tuple(&self) -> Vec<Token> {
+        vec![
+            Token::Array(
+                self.transactions
+                    .iter()
+                    .map(|tx| Token::Tuple(tx.to_tuple()))
+                    .collect(),
+            ),
+            Token::Bytes(self.batch_signature.to_vec()),
+        ]
+    }
+}
+
+impl Decodable for TransactionBatch {
+    type Ok = TransactionBatch;
+    fn from_tuple(tuple: &[Token]) -> Result<Self, AbiError> {
+        if tuple.len() != 2 {
+            return Err(AbiError::from(AbiErrorKind::AbiDecode));
+        }
+        let transactions = tuple[0].clone().to_array();
+        let batch_signature = tuple[1].clone().to_bytes();
+        if let (Some(transactions), Some(batch_signature)) = (transactions, batch_signature) {
+            let transactions = transactions
+                .into_iter()
+                .map(|tx| {
+                    tx.to_tuple()
+                        .ok_or_else(|| AbiError::from(AbiErrorKind::AbiDecode))
+                        .and_then(|tuple| Transaction::from_tuple(&tuple))
+                })
+                .collect::<Result<Vec<_>, AbiError>>()?;
+            Ok(TransactionBatch::new(
+                transactions,
+                Bytes::from(batch_signature),
+            ))
+        } else {
+            Err(AbiError::from(AbiErrorKind::AbiDecode))
+        }
+    }
+    fn get_param_types() -> Vec<ParamType> {
+        vec![
+            ParamType::Array(Box::new(ParamType::Tuple(Transaction::get_param_types()))),
+            ParamType::Bytes,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TransactionBatch;
+    use crate::data_structure::{Metadata, Range, Transaction};
+    use abi_utils::{Decodable, Encodable};
+    use bytes::Bytes;
+    use ethereum_types::Address;
+    use ethsign::SecretKey;
+
+    fn create_tx(start: u64, end: u64) -> Transaction {
+        Transaction::new(
+            Address::zero(),
+            Range::new(start, end),
+            Bytes::from(&b"parameters"[..]),
+            Bytes::from(&b"signature"[..]),
+            Metadata::default(),
+        )
+    }
+
+    #[test]
+    fn test_verify_batch_signature() {
+        let secret_key = SecretKey::from_raw(&[1u8; 32]).unwrap();
+        let sender = Address::from_slice(secret_key.public().address());
+        let unsigned =
+            TransactionBatch::new(vec![create_tx(0, 10), create_tx(10, 20)], Bytes::new());
+        let signature = secret_key
+            .sign(unsigned.get_batch_hash().as_bytes())
+            .unwrap();
+        let mut signature_bytes = vec![signature.v];
+        signature_bytes.extend([signature.r, signature.s].concat());
+        let batch = TransactionBatch::new(
+            unsigned.get_transactions().to_vec(),
+            Bytes::from(signature_bytes),
+        );
+        assert!(!unsigned.verify_batch_signature(sender));
+        assert!(batch.verify_batch_signature(sender));
+        assert!(!batch.verify_batch_signature(Address::zero()));
+        let decoded = TransactionBatch::from_abi(&batch.to_abi()).unwrap();
+        assert_eq!(decoded, batch);
+        assert!(decoded.verify_batch_signature(sender));
+    }
+}
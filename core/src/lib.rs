@@ -1,4 +1,8 @@
 extern crate abi_derive;
+#[macro_use]
+extern crate lazy_static;
+#[cfg(feature = "schemars")]
+pub extern crate schemars;
 
 pub mod data_structure;
 
@@ -14,6 +14,10 @@ pub enum ErrorKind {
     Dammy,
     #[fail(display = "LevelDb error")]
     LevelDb,
+    #[fail(display = "Unsupported dump version")]
+    UnsupportedDumpVersion,
+    #[fail(display = "Overlapping ranges")]
+    OverlappingRanges,
 }
 
 #[derive(Debug)]
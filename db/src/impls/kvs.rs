@@ -4,6 +4,7 @@ pub mod global_memory;
 pub mod leveldb;
 /// Memory implementation for key value store
 pub mod memory;
+mod undo_log;
 
 pub use self::global_memory::GlobalMemoryDb;
 #[cfg(feature = "require-leveldb")]
@@ -1,3 +1,4 @@
+use super::undo_log::UndoLog;
 use crate::error::Error;
 use crate::traits::db::DatabaseTrait;
 use crate::traits::kvs::{BaseDbKey, Batch, Bucket, KeyValue, KeyValueStore};
@@ -6,6 +7,7 @@ use std::collections::BTreeMap;
 
 lazy_static! {
     static ref GLOBAL_DB: RwLock<BTreeMap<BaseDbKey, Vec<u8>>> = RwLock::new(BTreeMap::new());
+    static ref UNDO_LOG: UndoLog = UndoLog::default();
 }
 
 pub struct GlobalMemoryDb {
@@ -25,6 +27,13 @@ impl DatabaseTrait for GlobalMemoryDb {
         }
     }
     fn close(&self) {}
+    fn transaction<F, T, E>(&self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&Self) -> Result<T, E>,
+        E: From<Error>,
+    {
+        UNDO_LOG.transaction(&GLOBAL_DB, || f(self))
+    }
 }
 
 impl KeyValueStore for GlobalMemoryDb {
@@ -32,24 +41,39 @@ impl KeyValueStore for GlobalMemoryDb {
         Ok(GLOBAL_DB.read().get(&self.get_key(key)).map(|v| v.to_vec()))
     }
     fn put(&self, key: &BaseDbKey, value: &[u8]) -> Result<(), Error> {
-        GLOBAL_DB.write().insert(self.get_key(key), value.to_vec());
+        let key = self.get_key(key);
+        let _writer = UNDO_LOG.lock();
+        let mut write_lock = GLOBAL_DB.write();
+        UNDO_LOG.record(&write_lock, &key);
+        write_lock.insert(key, value.to_vec());
         Ok(())
     }
     fn del(&self, key: &BaseDbKey) -> Result<(), Error> {
-        GLOBAL_DB.write().remove(&self.get_key(key));
+        let key = self.get_key(key);
+        let _writer = UNDO_LOG.lock();
+        let mut write_lock = GLOBAL_DB.write();
+        UNDO_LOG.record(&write_lock, &key);
+        write_lock.remove(&key);
         Ok(())
     }
     fn has(&self, _key: &BaseDbKey) -> Result<bool, Error> {
         Ok(true)
     }
     fn batch(&self, operations: &[Batch]) -> Result<(), Error> {
+        let _writer = UNDO_LOG.lock();
         let mut write_lock = GLOBAL_DB.write();
         for op in operations.iter() {
             match op {
                 Batch::BatchPut { key, value } => {
-                    write_lock.insert(self.get_key(key), value.clone())
+                    let key = self.get_key(key);
+                    UNDO_LOG.record(&write_lock, &key);
+                    write_lock.insert(key, value.clone())
+                }
+                Batch::BatchDel { key } => {
+                    let key = self.get_key(key);
+                    UNDO_LOG.record(&write_lock, &key);
+                    write_lock.remove(&key)
                 }
-                Batch::BatchDel { key } => write_lock.remove(&self.get_key(key)),
             };
         }
         Ok(())
@@ -105,6 +129,7 @@ impl KeyValueStore for GlobalMemoryDb {
 #[cfg(test)]
 mod tests {
     use super::GlobalMemoryDb;
+    use crate::error::{Error, ErrorKind};
     use crate::traits::db::DatabaseTrait;
     use crate::traits::kvs::{Bucket, KeyValueStore};
 
@@ -135,4 +160,18 @@ mod tests {
         let result = bucket_a.iter_all(&"".into(), Box::new(move |_k, _v| true));
         assert_eq!(result.len(), 2);
     }
+
+    #[test]
+    fn test_rollback_keeps_other_databases() {
+        let kvs = GlobalMemoryDb::open("rollback");
+        let kvs2 = GlobalMemoryDb::open("rollback2");
+        assert!(kvs2.put(&"a".into(), &b"value"[..]).is_ok());
+        let result: Result<(), Error> = kvs.transaction(|db| {
+            db.put(&"a".into(), &b"value"[..])?;
+            Err(Error::from(ErrorKind::Dammy))
+        });
+        assert!(result.is_err());
+        assert_eq!(kvs.get(&"a".into()).unwrap(), None);
+        assert_eq!(kvs2.get(&"a".into()).unwrap(), Some(b"value".to_vec()));
+    }
 }
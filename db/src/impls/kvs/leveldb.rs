@@ -3,13 +3,18 @@ use crate::traits::db::DatabaseTrait;
 use crate::traits::kvs::{BaseDbKey, Batch, Bucket, KeyValue, KeyValueStore};
 use db_key::Key;
 use leveldb::batch::Batch as LevelBatch;
+use leveldb::compaction::Compaction;
 use leveldb::database::kv::KV;
 use leveldb::database::{batch::Writebatch, Database};
 use leveldb::iterator::Iterable;
 use leveldb::iterator::LevelDBIterator;
 use leveldb::options::{Options, ReadOptions, WriteOptions};
-use parking_lot::RwLock;
+use parking_lot::{ReentrantMutex, RwLock};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::ops::Bound::{Excluded, Unbounded};
 use std::path::Path;
+use std::thread::{self, ThreadId};
 use tempdir::TempDir;
 
 impl Key for BaseDbKey {
@@ -24,6 +29,12 @@ impl Key for BaseDbKey {
 
 pub struct CoreDb {
     db: RwLock<Database<BaseDbKey>>,
+    /// Held by every write and by a transaction until it ends,
+    /// so that writes from other threads wait instead of being buffered into the transaction.
+    writer: ReentrantMutex<()>,
+    /// Writes made in current transaction, which are written by a single WriteBatch at commit.
+    /// `None` value means deletion. Only visible from the thread which opened the transaction.
+    pending: RwLock<Option<(ThreadId, BTreeMap<BaseDbKey, Option<Vec<u8>>>)>>,
 }
 
 impl CoreDb {
@@ -36,8 +47,75 @@ impl CoreDb {
         leveldb_options.create_if_missing = true;
         Self {
             db: RwLock::new(Database::open(path, leveldb_options).unwrap()),
+            writer: ReentrantMutex::new(()),
+            pending: RwLock::new(None),
         }
     }
+    /// Buffers `operations` if a transaction is open and returns true.
+    /// Caller must hold `writer`, so an open transaction belongs to current thread.
+    fn buffer(&self, operations: &[Batch]) -> bool {
+        if let Some((_, pending)) = self.pending.write().as_mut() {
+            for op in operations.iter() {
+                match op {
+                    Batch::BatchPut { key, value } => {
+                        pending.insert(key.clone(), Some(value.clone()))
+                    }
+                    Batch::BatchDel { key } => pending.insert(key.clone(), None),
+                };
+            }
+            true
+        } else {
+            false
+        }
+    }
+    /// Iterates keys after `start` with writes of current transaction applied,
+    /// while `f` returns true.
+    fn iter_from(
+        &self,
+        start: &BaseDbKey,
+        mut f: impl FnMut(&BaseDbKey, &Vec<u8>) -> bool,
+    ) -> Vec<KeyValue> {
+        let read_lock = self.db.read();
+        let iter = read_lock.iter(ReadOptions::new());
+        iter.seek(start);
+        let mut stored = iter.filter(|(k, _)| k != start).peekable();
+        let pending_lock = self.pending.read();
+        let no_pending = BTreeMap::new();
+        let mut pending = match pending_lock.as_ref() {
+            Some((owner, pending)) if *owner == thread::current().id() => pending,
+            _ => &no_pending,
+        }
+        .range((Excluded(start.clone()), Unbounded))
+        .peekable();
+        let mut result = vec![];
+        loop {
+            let order = match (stored.peek(), pending.peek()) {
+                (None, None) => break,
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some((stored_key, _)), Some((pending_key, _))) => stored_key.cmp(pending_key),
+            };
+            if order == Ordering::Equal {
+                // pending write overrides stored value
+                stored.next();
+            }
+            let (key, value) = if order == Ordering::Less {
+                let (key, value) = stored.next().unwrap();
+                (key, Some(value))
+            } else {
+                let (key, value) = pending.next().unwrap();
+                (key.clone(), value.clone())
+            };
+            if let Some(value) = value {
+                if f(&key, &value) {
+                    result.push(KeyValue::new(key, value));
+                } else {
+                    break;
+                }
+            }
+        }
+        result
+    }
 }
 
 impl DatabaseTrait for CoreDb {
@@ -46,10 +124,44 @@ impl DatabaseTrait for CoreDb {
         Self::open_with_path(path.as_path())
     }
     fn close(&self) {}
+    fn transaction<F, T, E>(&self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&Self) -> Result<T, E>,
+        E: From<Error>,
+    {
+        let _writer = self.writer.lock();
+        // nested transaction is merged into the outer one
+        if self.pending.read().is_some() {
+            return f(self);
+        }
+        *self.pending.write() = Some((thread::current().id(), BTreeMap::new()));
+        let result = f(self);
+        let (_, pending) = self.pending.write().take().unwrap();
+        let value = result?;
+        let mut batch: Writebatch<BaseDbKey> = Writebatch::new();
+        for (key, value) in pending.iter() {
+            match value {
+                Some(value) => batch.put(key.clone(), value),
+                None => batch.delete(key.clone()),
+            }
+        }
+        self.db
+            .write()
+            .write(WriteOptions::new(), &batch)
+            .map_err(Error::from)?;
+        Ok(value)
+    }
 }
 
 impl KeyValueStore for CoreDb {
     fn get(&self, key: &BaseDbKey) -> Result<Option<Vec<u8>>, Error> {
+        if let Some((owner, pending)) = self.pending.read().as_ref() {
+            if *owner == thread::current().id() {
+                if let Some(value) = pending.get(key) {
+                    return Ok(value.clone());
+                }
+            }
+        }
         let read_opts = ReadOptions::new();
         self.db
             .read()
@@ -58,6 +170,10 @@ impl KeyValueStore for CoreDb {
             .map(|v| v.map(|v| v.to_vec()))
     }
     fn put(&self, key: &BaseDbKey, value: &[u8]) -> Result<(), Error> {
+        let _writer = self.writer.lock();
+        if self.buffer(&[Batch::new_put(key.clone(), value)]) {
+            return Ok(());
+        }
         let write_opts = WriteOptions::new();
         self.db
             .write()
@@ -65,6 +181,10 @@ impl KeyValueStore for CoreDb {
             .map_err(Into::into)
     }
     fn del(&self, key: &BaseDbKey) -> Result<(), Error> {
+        let _writer = self.writer.lock();
+        if self.buffer(&[Batch::new_del(key.clone())]) {
+            return Ok(());
+        }
         let write_opts = WriteOptions::new();
         self.db.write().delete(write_opts, key).map_err(Into::into)
     }
@@ -72,6 +192,10 @@ impl KeyValueStore for CoreDb {
         Ok(true)
     }
     fn batch(&self, operations: &[Batch]) -> Result<(), Error> {
+        let _writer = self.writer.lock();
+        if self.buffer(operations) {
+            return Ok(());
+        }
         let mut batch: Writebatch<BaseDbKey> = Writebatch::new();
         for op in operations.iter() {
             match op {
@@ -92,54 +216,48 @@ impl KeyValueStore for CoreDb {
         start: &BaseDbKey,
         mut f: Box<dyn FnMut(&BaseDbKey, &Vec<u8>) -> bool>,
     ) -> Vec<KeyValue> {
-        let read_lock = self.db.read();
-        let iter = read_lock.iter(ReadOptions::new());
-        let mut result = vec![];
-        iter.seek(&prefix.concat(start));
-        for (k, v) in iter {
-            if prefix.concat(start) == k {
-                continue;
-            }
-            if k.0.starts_with(&prefix.0) && f(&k, &v) {
-                result.push(KeyValue::new(k.clone(), v.clone()));
-                continue;
-            } else {
-                break;
-            }
-        }
-        result
+        self.iter_from(&prefix.concat(start), |k, v| {
+            k.0.starts_with(&prefix.0) && f(k, v)
+        })
     }
     fn iter_all(
         &self,
         start: &BaseDbKey,
-        mut f: Box<dyn FnMut(&BaseDbKey, &Vec<u8>) -> bool>,
+        f: Box<dyn FnMut(&BaseDbKey, &Vec<u8>) -> bool>,
     ) -> Vec<KeyValue> {
-        let read_lock = self.db.read();
-        let iter = read_lock.iter(ReadOptions::new());
-        let mut result = vec![];
-        iter.seek(start);
-        for (k, v) in iter {
-            if *start == k {
-                continue;
-            }
-            if f(&k, &v) {
-                result.push(KeyValue::new(k.clone(), v.clone()));
-                continue;
-            } else {
-                break;
-            }
-        }
-        result
+        self.iter_from(start, f)
     }
     fn bucket<'a>(&'a self, prefix: &BaseDbKey) -> Bucket<'a> {
         Bucket::new(prefix.clone(), self)
     }
+    /// Compacts all keys. Keys of this crate never start with 256 bytes of `0xff`.
+    fn compact(&self) -> Result<(), Error> {
+        self.db
+            .read()
+            .compact(&BaseDbKey::new(vec![]), &BaseDbKey::new(vec![0xff; 256]));
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::CoreDb;
+    use crate::error::{Error, ErrorKind};
+    use crate::traits::db::DatabaseTrait;
     use crate::traits::kvs::{Bucket, KeyValueStore};
+    use std::fs;
+    use std::path::Path;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+    use tempdir::TempDir;
+
+    fn dir_size(path: &Path) -> u64 {
+        fs::read_dir(path)
+            .unwrap()
+            .map(|entry| entry.unwrap().metadata().unwrap().len())
+            .sum()
+    }
 
     #[test]
     fn test_bucket() {
@@ -164,4 +282,72 @@ mod tests {
         let result = bucket_a.iter_all(&"".into(), Box::new(move |_k, _v| true));
         assert_eq!(result.len(), 2);
     }
+
+    #[test]
+    fn test_transaction() {
+        let core_db = CoreDb::open_with_tempdir("test");
+        let root: Bucket = core_db.root();
+        assert!(root.put(&"a".into(), &b"value"[..]).is_ok());
+        let result: Result<(), Error> = core_db.transaction(|db| {
+            db.put(&"a".into(), &b"updated"[..])?;
+            db.put(&"b".into(), &b"value"[..])?;
+            // writes are visible inside the transaction
+            assert_eq!(db.get(&"a".into())?, Some(b"updated".to_vec()));
+            assert_eq!(db.iter_all(&"".into(), Box::new(|_k, _v| true)).len(), 2);
+            Err(Error::from(ErrorKind::Dammy))
+        });
+        assert!(result.is_err());
+        assert_eq!(root.get(&"a".into()).unwrap(), Some(b"value".to_vec()));
+        assert_eq!(root.get(&"b".into()).unwrap(), None);
+        let result: Result<(), Error> = core_db.transaction(|db| {
+            db.del(&"a".into())?;
+            db.put(&"b".into(), &b"value"[..])?;
+            assert_eq!(db.iter_all(&"".into(), Box::new(|_k, _v| true)).len(), 1);
+            Ok(())
+        });
+        assert!(result.is_ok());
+        assert_eq!(root.get(&"a".into()).unwrap(), None);
+        assert_eq!(root.get(&"b".into()).unwrap(), Some(b"value".to_vec()));
+    }
+
+    #[test]
+    fn test_transaction_is_isolated_from_other_threads() {
+        let tempdir = TempDir::new("test").unwrap();
+        let core_db = Arc::new(CoreDb::open_with_path(tempdir.path()));
+        let other_db = core_db.clone();
+        let mut handle = None;
+        let result: Result<(), Error> = core_db.transaction(|db| {
+            db.put(&"a".into(), &b"value"[..])?;
+            handle = Some(thread::spawn(move || {
+                // uncommitted writes aren't visible and the write waits until the transaction ends
+                assert_eq!(other_db.get(&"a".into()).unwrap(), None);
+                other_db.put(&"b".into(), &b"value"[..]).unwrap();
+            }));
+            thread::sleep(Duration::from_millis(50));
+            assert_eq!(db.get(&"b".into())?, None);
+            Err(Error::from(ErrorKind::Dammy))
+        });
+        assert!(result.is_err());
+        handle.unwrap().join().unwrap();
+        assert_eq!(core_db.get(&"a".into()).unwrap(), None);
+        assert_eq!(core_db.get(&"b".into()).unwrap(), Some(b"value".to_vec()));
+    }
+
+    #[test]
+    fn test_compact() {
+        let tempdir = TempDir::new("test").unwrap();
+        let core_db = CoreDb::open_with_path(tempdir.path());
+        let root: Bucket = core_db.root();
+        let value = vec![1u8; 1024];
+        for i in 0..10000u64 {
+            assert!(root.put(&i.into(), &value).is_ok());
+        }
+        for i in 0..10000u64 {
+            assert!(root.del(&i.into()).is_ok());
+        }
+        let size_before = dir_size(tempdir.path());
+        assert!(core_db.compact().is_ok());
+        assert!(dir_size(tempdir.path()) < size_before);
+        assert_eq!(root.iter_all(&"".into(), Box::new(|_k, _v| true)).len(), 0);
+    }
 }
@@ -1,3 +1,4 @@
+use super::undo_log::UndoLog;
 use crate::error::Error;
 use crate::traits::db::DatabaseTrait;
 use crate::traits::kvs::{BaseDbKey, Batch, Bucket, KeyValue, KeyValueStore};
@@ -6,15 +7,24 @@ use std::collections::BTreeMap;
 
 pub struct CoreDbMemoryImpl {
     db: RwLock<BTreeMap<BaseDbKey, Vec<u8>>>,
+    undo_log: UndoLog,
 }
 
 impl DatabaseTrait for CoreDbMemoryImpl {
     fn open(_dbname: &str) -> Self {
         Self {
             db: RwLock::new(BTreeMap::new()),
+            undo_log: UndoLog::default(),
         }
     }
     fn close(&self) {}
+    fn transaction<F, T, E>(&self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&Self) -> Result<T, E>,
+        E: From<Error>,
+    {
+        self.undo_log.transaction(&self.db, || f(self))
+    }
 }
 
 impl KeyValueStore for CoreDbMemoryImpl {
@@ -22,22 +32,35 @@ impl KeyValueStore for CoreDbMemoryImpl {
         Ok(self.db.read().get(key).map(|v| v.to_vec()))
     }
     fn put(&self, key: &BaseDbKey, value: &[u8]) -> Result<(), Error> {
-        self.db.write().insert(key.clone(), value.to_vec());
+        let _writer = self.undo_log.lock();
+        let mut write_lock = self.db.write();
+        self.undo_log.record(&write_lock, key);
+        write_lock.insert(key.clone(), value.to_vec());
         Ok(())
     }
     fn del(&self, key: &BaseDbKey) -> Result<(), Error> {
-        self.db.write().remove(key);
+        let _writer = self.undo_log.lock();
+        let mut write_lock = self.db.write();
+        self.undo_log.record(&write_lock, key);
+        write_lock.remove(key);
         Ok(())
     }
     fn has(&self, _key: &BaseDbKey) -> Result<bool, Error> {
         Ok(true)
     }
     fn batch(&self, operations: &[Batch]) -> Result<(), Error> {
+        let _writer = self.undo_log.lock();
         let mut write_lock = self.db.write();
         for op in operations.iter() {
             match op {
-                Batch::BatchPut { key, value } => write_lock.insert(key.clone(), value.clone()),
-                Batch::BatchDel { key } => write_lock.remove(key),
+                Batch::BatchPut { key, value } => {
+                    self.undo_log.record(&write_lock, key);
+                    write_lock.insert(key.clone(), value.clone())
+                }
+                Batch::BatchDel { key } => {
+                    self.undo_log.record(&write_lock, key);
+                    write_lock.remove(key)
+                }
             };
         }
         Ok(())
@@ -86,13 +109,24 @@ impl KeyValueStore for CoreDbMemoryImpl {
     fn bucket<'a>(&'a self, prefix: &BaseDbKey) -> Bucket<'a> {
         Bucket::new(prefix.clone(), self)
     }
+    /// Recreates the map so that nodes left by deletions are released.
+    fn compact(&self) -> Result<(), Error> {
+        let mut write_lock = self.db.write();
+        let entries = std::mem::replace(&mut *write_lock, BTreeMap::new());
+        *write_lock = entries.into_iter().collect();
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::CoreDbMemoryImpl;
+    use crate::error::{Error, ErrorKind};
     use crate::traits::db::DatabaseTrait;
     use crate::traits::kvs::{Bucket, KeyValueStore};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
 
     #[test]
     fn test_bucket() {
@@ -117,4 +151,65 @@ mod tests {
         let result = bucket_a.iter_all(&"".into(), Box::new(move |_k, _v| true));
         assert_eq!(result.len(), 2);
     }
+
+    #[test]
+    fn test_transaction() {
+        let core_db = CoreDbMemoryImpl::open("test");
+        let root: Bucket = core_db.root();
+        assert!(root.put(&"a".into(), &b"value"[..]).is_ok());
+        let result: Result<(), Error> = core_db.transaction(|db| {
+            db.put(&"a".into(), &b"updated"[..]).unwrap();
+            db.put(&"b".into(), &b"value"[..]).unwrap();
+            Err(Error::from(ErrorKind::Dammy))
+        });
+        assert!(result.is_err());
+        assert_eq!(root.get(&"a".into()).unwrap(), Some(b"value".to_vec()));
+        assert_eq!(root.get(&"b".into()).unwrap(), None);
+        let result: Result<(), Error> = core_db.transaction(|db| {
+            db.put(&"b".into(), &b"value"[..]).unwrap();
+            Ok(())
+        });
+        assert!(result.is_ok());
+        assert_eq!(root.get(&"b".into()).unwrap(), Some(b"value".to_vec()));
+    }
+
+    #[test]
+    fn test_nested_transaction() {
+        let core_db = CoreDbMemoryImpl::open("test");
+        let result: Result<(), Error> = core_db.transaction(|db| {
+            db.put(&"a".into(), &b"value"[..])?;
+            let inner: Result<(), Error> = db.transaction(|db| {
+                db.put(&"a".into(), &b"updated"[..])?;
+                db.put(&"b".into(), &b"value"[..])?;
+                Err(Error::from(ErrorKind::Dammy))
+            });
+            assert!(inner.is_err());
+            // only writes of the inner transaction are rolled back
+            assert_eq!(db.get(&"a".into())?, Some(b"value".to_vec()));
+            assert_eq!(db.get(&"b".into())?, None);
+            Ok(())
+        });
+        assert!(result.is_ok());
+        assert_eq!(core_db.get(&"a".into()).unwrap(), Some(b"value".to_vec()));
+    }
+
+    #[test]
+    fn test_rollback_keeps_writes_of_other_threads() {
+        let core_db = Arc::new(CoreDbMemoryImpl::open("test"));
+        let other_db = core_db.clone();
+        let mut handle = None;
+        let result: Result<(), Error> = core_db.transaction(|db| {
+            db.put(&"a".into(), &b"value"[..])?;
+            handle = Some(thread::spawn(move || {
+                // waits until the transaction ends
+                other_db.put(&"b".into(), &b"value"[..]).unwrap();
+            }));
+            thread::sleep(Duration::from_millis(50));
+            Err(Error::from(ErrorKind::Dammy))
+        });
+        assert!(result.is_err());
+        handle.unwrap().join().unwrap();
+        assert_eq!(core_db.get(&"a".into()).unwrap(), None);
+        assert_eq!(core_db.get(&"b".into()).unwrap(), Some(b"value".to_vec()));
+    }
 }
@@ -0,0 +1,70 @@
+use crate::traits::kvs::BaseDbKey;
+use parking_lot::{Mutex, ReentrantMutex, ReentrantMutexGuard, RwLock};
+use std::collections::BTreeMap;
+
+/// Rolls back transactions of the memory stores.
+/// A transaction holds the writer lock until it ends, so writes from other threads wait
+/// instead of being rolled back with it.
+pub struct UndoLog {
+    writer: ReentrantMutex<()>,
+    /// Previous values of keys written in the open transaction. `None` value means the key
+    /// didn't exist.
+    entries: Mutex<Option<Vec<(BaseDbKey, Option<Vec<u8>>)>>>,
+}
+
+impl Default for UndoLog {
+    fn default() -> Self {
+        Self {
+            writer: ReentrantMutex::new(()),
+            entries: Mutex::new(None),
+        }
+    }
+}
+
+impl UndoLog {
+    /// Locks writer. Every write to the store must hold it.
+    pub fn lock(&self) -> ReentrantMutexGuard<()> {
+        self.writer.lock()
+    }
+    /// Records previous value of `key` if a transaction is open.
+    pub fn record(&self, map: &BTreeMap<BaseDbKey, Vec<u8>>, key: &BaseDbKey) {
+        if let Some(entries) = self.entries.lock().as_mut() {
+            entries.push((key.clone(), map.get(key).cloned()));
+        }
+    }
+    /// Runs `f` and restores keys written by `f` if it returns `Err`.
+    /// Nested transaction only restores its own writes.
+    pub fn transaction<T, E>(
+        &self,
+        map: &RwLock<BTreeMap<BaseDbKey, Vec<u8>>>,
+        f: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, E> {
+        let _writer = self.lock();
+        let (start, is_root) = {
+            let mut entries = self.entries.lock();
+            match entries.as_ref() {
+                Some(entries) => (entries.len(), false),
+                None => {
+                    *entries = Some(vec![]);
+                    (0, true)
+                }
+            }
+        };
+        let result = f();
+        let mut entries = self.entries.lock();
+        if result.is_err() {
+            let undo = entries.as_mut().unwrap().split_off(start);
+            let mut write_lock = map.write();
+            for (key, value) in undo.into_iter().rev() {
+                match value {
+                    Some(value) => write_lock.insert(key, value),
+                    None => write_lock.remove(&key),
+                };
+            }
+        }
+        if is_root {
+            *entries = None;
+        }
+        result
+    }
+}
@@ -3,6 +3,52 @@ use crate::range::Range;
 use crate::traits::kvs::{BaseDbKey, Batch, Bucket, KeyValueStore};
 use crate::traits::rangestore::RangeStore;
 use bytes::Bytes;
+#[cfg(feature = "reactive")]
+use futures::sync::mpsc::{unbounded, UnboundedSender};
+#[cfg(feature = "reactive")]
+use futures::Stream;
+#[cfg(feature = "reactive")]
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+#[cfg(feature = "reactive")]
+use std::sync::Arc;
+
+pub const DB_DUMP_VERSION: u32 = 2;
+
+/// Serializable snapshot of RangeDbImpl.
+/// `buckets` has ranges of each bucket as `(start, end, value, modified_at)` and
+/// `entries` has key values which aren't ranges like blocks of BlockDb.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DbDump {
+    pub version: u32,
+    pub buckets: HashMap<Vec<u8>, Vec<(u64, u64, Bytes, u64)>>,
+    pub entries: Vec<(Vec<u8>, Bytes)>,
+}
+
+/// Change of ranges notified to the stream of `RangeDbImpl::watch`.
+#[cfg(feature = "reactive")]
+#[derive(Clone, Debug, PartialEq)]
+pub enum RangeChangeEvent {
+    Insert(Range),
+    /// `start` and `end` of the deleted range.
+    Delete(u64, u64),
+}
+
+/// Senders of watching streams with the full prefix of watched bucket.
+#[cfg(feature = "reactive")]
+type Watchers = Arc<Mutex<Vec<(Vec<u8>, UnboundedSender<RangeChangeEvent>)>>>;
+
+/// Storage statistics of RangeDbImpl.
+/// Buckets are the ones having ranges, and `largest_bucket` is the prefix and the number of ranges
+/// of the bucket with the most ranges.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DbStats {
+    pub total_entries: u64,
+    pub total_bytes: u64,
+    pub bucket_count: usize,
+    pub largest_bucket: (Vec<u8>, u64),
+}
 
 /// Range DB implementation using key value store.
 /// ```rust
@@ -15,6 +61,11 @@ use bytes::Bytes;
 #[derive(Clone)]
 pub struct RangeDbImpl<KVS> {
     db: KVS,
+    /// Prefix of this bucket from the root db.
+    #[cfg(feature = "reactive")]
+    prefix: Vec<u8>,
+    #[cfg(feature = "reactive")]
+    watchers: Watchers,
 }
 
 impl<KVS> RangeDbImpl<KVS>
@@ -24,9 +75,37 @@ where
     pub fn bucket<'a>(&'a self, key: &Bytes) -> RangeDbImpl<Bucket<'a>> {
         RangeDbImpl {
             db: self.db.bucket(&BaseDbKey::from(key.clone())),
+            #[cfg(feature = "reactive")]
+            prefix: [&self.prefix[..], &key[..]].concat(),
+            #[cfg(feature = "reactive")]
+            watchers: self.watchers.clone(),
         }
     }
 
+    /// Returns stream of changes of ranges in `bucket`.
+    /// Only changes made through RangeDbImpl are notified, and nested buckets aren't watched.
+    #[cfg(feature = "reactive")]
+    pub fn watch(&self, bucket: &[u8]) -> impl Stream<Item = RangeChangeEvent, Error = ()> {
+        let (sender, receiver) = unbounded();
+        self.watchers
+            .lock()
+            .push(([&self.prefix[..], bucket].concat(), sender));
+        receiver
+    }
+
+    /// Sends `events` to the streams watching this bucket and drops closed streams.
+    #[cfg(feature = "reactive")]
+    fn notify(&self, events: Vec<RangeChangeEvent>) {
+        self.watchers.lock().retain(|(prefix, sender)| {
+            if prefix != &self.prefix {
+                return true;
+            }
+            events
+                .iter()
+                .all(|event| sender.unbounded_send(event.clone()).is_ok())
+        });
+    }
+
     pub fn get_db(&self) -> &KVS {
         &self.db
     }
@@ -41,6 +120,13 @@ where
             batch.push(Batch::new_del(BaseDbKey::from(range.get_end())));
         }
         if self.db.batch(&batch).is_ok() {
+            #[cfg(feature = "reactive")]
+            self.notify(
+                ranges
+                    .iter()
+                    .map(|range| RangeChangeEvent::Delete(range.get_start(), range.get_end()))
+                    .collect(),
+            );
             Ok(ranges)
         } else {
             Err(Error::from(ErrorKind::LevelDb))
@@ -55,11 +141,200 @@ where
             ));
         }
         if self.db.batch(&batch).is_ok() {
+            #[cfg(feature = "reactive")]
+            self.notify(
+                ranges
+                    .iter()
+                    .cloned()
+                    .map(RangeChangeEvent::Insert)
+                    .collect(),
+            );
             Ok(())
         } else {
             Err(Error::from(ErrorKind::LevelDb))
         }
     }
+
+    /// Returns all ranges in `bucket` sorted by end.
+    /// Ranges in nested buckets aren't included.
+    pub fn get_all_in_bucket(&self, bucket: &[u8]) -> Result<Vec<Range>, Error> {
+        let ranges = self
+            .db
+            .bucket(&BaseDbKey::new(bucket.to_vec()))
+            .iter_all(&BaseDbKey::new(vec![]), Box::new(|_k, _v| true))
+            .iter()
+            .filter(|kv| kv.get_key().as_bytes().len() == 8)
+            .filter_map(|kv| {
+                rlp::decode::<Range>(kv.get_value())
+                    .ok()
+                    .filter(|range| kv.get_key() == &BaseDbKey::from(range.get_end()))
+            })
+            .collect();
+        Ok(ranges)
+    }
+
+    /// Copies all ranges in bucket `from` to bucket `to` and returns the number of copied ranges.
+    /// Ranges in `to` overlapping with copied ones are overwritten.
+    pub fn clone_bucket(&self, from: &[u8], to: &[u8]) -> Result<usize, Error> {
+        let ranges = self.get_all_in_bucket(from)?;
+        let to_bucket = self.bucket(&Bytes::from(to));
+        for range in ranges.iter() {
+            to_bucket.put(range.get_start(), range.get_end(), range.get_value())?;
+        }
+        Ok(ranges.len())
+    }
+
+    /// Reclaims storage of deleted ranges in the underlying key value store.
+    /// It should be called after deleting many ranges.
+    pub fn compact(&self) -> Result<(), Error> {
+        self.db.compact()
+    }
+
+    /// Returns the latest `modified_at` of ranges in `bucket` which intersect with `start` to `end`.
+    /// Returns None if there are no such ranges.
+    pub fn get_last_modified_in_range(
+        &self,
+        bucket: &[u8],
+        start: u64,
+        end: u64,
+    ) -> Result<Option<u64>, Error> {
+        let ranges = self.bucket(&Bytes::from(bucket)).get(start, end)?;
+        Ok(ranges.iter().map(Range::get_modified_at).max())
+    }
+
+    /// Returns the sum of widths of all ranges in `bucket`.
+    /// Returns error if any ranges overlap.
+    pub fn get_range_coverage(&self, bucket: &[u8]) -> Result<u64, Error> {
+        let ranges = self.get_all_in_bucket(bucket)?;
+        let mut coverage = 0;
+        for (i, range) in ranges.iter().enumerate() {
+            if i > 0 && ranges[i - 1].get_end() > range.get_start() {
+                return Err(Error::from(ErrorKind::OverlappingRanges));
+            }
+            coverage += range.get_end() - range.get_start();
+        }
+        Ok(coverage)
+    }
+
+    /// Returns pairs of ranges of `bucket_a` and `bucket_b` which overlap each other within `start` to `end`.
+    pub fn get_intersection(
+        &self,
+        bucket_a: &[u8],
+        bucket_b: &[u8],
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<(Range, Range)>, Error> {
+        let ranges_a = self.bucket(&Bytes::from(bucket_a)).get(start, end)?;
+        let ranges_b = self.bucket(&Bytes::from(bucket_b)).get(start, end)?;
+        let mut pairs = vec![];
+        for a in ranges_a.iter() {
+            for b in ranges_b.iter() {
+                if a.intersect(b.get_start(), b.get_end()) {
+                    pairs.push((a.clone(), b.clone()));
+                }
+            }
+        }
+        Ok(pairs)
+    }
+
+    /// Counts entries, bytes of keys and values, and ranges of each bucket.
+    pub fn stats(&self) -> DbStats {
+        let mut bucket_sizes: HashMap<Vec<u8>, u64> = HashMap::new();
+        let mut stats = DbStats::default();
+        for kv in self
+            .db
+            .iter_all(&BaseDbKey::new(vec![]), Box::new(|_k, _v| true))
+            .iter()
+        {
+            let key = kv.get_key().as_bytes();
+            stats.total_entries += 1;
+            stats.total_bytes += (key.len() + kv.get_value().len()) as u64;
+            let is_range = rlp::decode::<Range>(kv.get_value())
+                .map(|range| key.ends_with(BaseDbKey::from(range.get_end()).as_bytes()))
+                .unwrap_or(false);
+            if is_range {
+                *bucket_sizes
+                    .entry(key[..key.len() - 8].to_vec())
+                    .or_insert(0) += 1;
+            }
+        }
+        stats.bucket_count = bucket_sizes.len();
+        if let Some((prefix, size)) = bucket_sizes
+            .into_iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+        {
+            stats.largest_bucket = (prefix, size);
+        }
+        stats
+    }
+
+    /// Dumps all ranges and key values in the database.
+    pub fn dump(&self) -> Result<DbDump, Error> {
+        let mut buckets: HashMap<Vec<u8>, Vec<(u64, u64, Bytes, u64)>> = HashMap::new();
+        let mut entries = vec![];
+        for kv in self
+            .db
+            .iter_all(&BaseDbKey::new(vec![]), Box::new(|_k, _v| true))
+            .iter()
+        {
+            let key = kv.get_key().as_bytes();
+            let range = rlp::decode::<Range>(kv.get_value())
+                .ok()
+                .filter(|range| key.ends_with(BaseDbKey::from(range.get_end()).as_bytes()));
+            if let Some(range) = range {
+                buckets
+                    .entry(key[..key.len() - 8].to_vec())
+                    .or_insert_with(|| vec![])
+                    .push((
+                        range.get_start(),
+                        range.get_end(),
+                        Bytes::from(range.get_value()),
+                        range.get_modified_at(),
+                    ));
+            } else {
+                entries.push((key.to_vec(), Bytes::from(kv.get_value().clone())));
+            }
+        }
+        Ok(DbDump {
+            version: DB_DUMP_VERSION,
+            buckets,
+            entries,
+        })
+    }
+
+    /// Creates RangeDbImpl with `kvs` and restores `dump` to it.
+    pub fn load(kvs: KVS, dump: &DbDump) -> Result<Self, Error> {
+        let db = Self::from(kvs);
+        db.restore(dump)?;
+        Ok(db)
+    }
+
+    /// Replaces all ranges and key values in the database with those of `dump`.
+    pub fn restore(&self, dump: &DbDump) -> Result<(), Error> {
+        if dump.version != DB_DUMP_VERSION {
+            return Err(Error::from(ErrorKind::UnsupportedDumpVersion));
+        }
+        // existing data is deleted in the same batch so that nothing stale is left
+        let mut batch: Vec<Batch> = self
+            .db
+            .iter_all(&BaseDbKey::new(vec![]), Box::new(|_k, _v| true))
+            .iter()
+            .map(|kv| Batch::new_del(kv.get_key().clone()))
+            .collect();
+        for (bucket, ranges) in dump.buckets.iter() {
+            let prefix = BaseDbKey::new(bucket.clone());
+            for (start, end, value, modified_at) in ranges.iter() {
+                batch.push(Batch::new_put(
+                    prefix.concat(&BaseDbKey::from(*end)),
+                    &rlp::encode(&Range::new(*start, *end, value).with_modified_at(*modified_at)),
+                ));
+            }
+        }
+        for (key, value) in dump.entries.iter() {
+            batch.push(Batch::new_put(BaseDbKey::new(key.clone()), value));
+        }
+        self.db.batch(&batch)
+    }
 }
 
 impl<KVS> From<KVS> for RangeDbImpl<KVS>
@@ -67,7 +342,13 @@ where
     KVS: KeyValueStore,
 {
     fn from(kvs: KVS) -> Self {
-        Self { db: kvs }
+        Self {
+            db: kvs,
+            #[cfg(feature = "reactive")]
+            prefix: vec![],
+            #[cfg(feature = "reactive")]
+            watchers: Default::default(),
+        }
     }
 }
 
@@ -100,23 +381,25 @@ where
             return Err(Error::from(ErrorKind::Dammy));
         }
         if !input_ranges.is_empty() && input_ranges[0].get_start() < start {
-            output_ranges.push(Range::new(
-                input_ranges[0].get_start(),
-                start,
-                &input_ranges[0].get_value(),
-            ));
+            output_ranges.push(
+                Range::new(
+                    input_ranges[0].get_start(),
+                    start,
+                    &input_ranges[0].get_value(),
+                )
+                .with_modified_at(input_ranges[0].get_modified_at()),
+            );
         }
         if !input_ranges.is_empty() {
             let last_range = &input_ranges[input_ranges.len() - 1];
             if end < last_range.get_end() {
-                output_ranges.push(Range::new(
-                    end,
-                    last_range.get_end(),
-                    &last_range.get_value(),
-                ));
+                output_ranges.push(
+                    Range::new(end, last_range.get_end(), &last_range.get_value())
+                        .with_modified_at(last_range.get_modified_at()),
+                );
             }
         }
-        output_ranges.push(Range::new(start, end, value));
+        output_ranges.push(Range::new(start, end, value).touch());
         if self.put_batch(&output_ranges).is_ok() {
             Ok(())
         } else {
@@ -127,11 +410,14 @@ where
 
 #[cfg(test)]
 mod tests {
-    use super::RangeDbImpl;
+    use super::{DbDump, RangeDbImpl, DB_DUMP_VERSION};
+    use crate::error::ErrorKind;
     use crate::impls::kvs::memory::CoreDbMemoryImpl;
     use crate::traits::db::DatabaseTrait;
+    use crate::traits::kvs::{BaseDbKey, KeyValueStore};
     use crate::traits::rangestore::RangeStore;
     use bytes::Bytes;
+    use std::collections::HashMap;
 
     #[test]
     fn test_get_same_range() {
@@ -282,6 +568,178 @@ mod tests {
         assert_eq!(result[1].get_value(), b"Alice is owner");
     }
 
+    #[test]
+    fn test_dump_and_load() {
+        let db = RangeDbImpl::from(CoreDbMemoryImpl::open("test"));
+        let _ = db
+            .bucket(&Bytes::from("aaa"))
+            .put(0, 100, b"Alice is owner");
+        let _ = db.bucket(&Bytes::from("bbb")).put(10, 20, b"Bob is owner");
+        let _ = db.get_db().put(&BaseDbKey::from("block"), b"block data");
+        let dump = db.dump().unwrap();
+        assert_eq!(dump.buckets.len(), 2);
+        assert_eq!(dump.entries.len(), 1);
+
+        let loaded = RangeDbImpl::load(CoreDbMemoryImpl::open("test"), &dump).unwrap();
+        let result = loaded.bucket(&Bytes::from("bbb")).get(0, 100).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].get_start(), 10);
+        assert_eq!(result[0].get_value(), b"Bob is owner");
+        assert_eq!(
+            result[0].get_modified_at(),
+            db.bucket(&Bytes::from("bbb")).get(0, 100).unwrap()[0].get_modified_at()
+        );
+        assert!(result[0].get_modified_at() > 0);
+        assert_eq!(
+            loaded.bucket(&Bytes::from("aaa")).get(0, 100).unwrap()[0].get_value(),
+            b"Alice is owner"
+        );
+        assert_eq!(
+            loaded.get_db().get(&BaseDbKey::from("block")).unwrap(),
+            Some(b"block data".to_vec())
+        );
+        assert_eq!(loaded.dump().unwrap(), dump);
+    }
+
+    #[test]
+    fn test_restore_clears_existing_data() {
+        let db = RangeDbImpl::from(CoreDbMemoryImpl::open("test"));
+        let _ = db
+            .bucket(&Bytes::from("aaa"))
+            .put(0, 100, b"Alice is owner");
+        let dump = db.dump().unwrap();
+
+        let _ = db
+            .bucket(&Bytes::from("aaa"))
+            .put(100, 200, b"Bob is owner");
+        let _ = db.bucket(&Bytes::from("ccc")).put(0, 10, b"Carol is owner");
+        let _ = db.get_db().put(&BaseDbKey::from("block"), b"block data");
+        db.restore(&dump).unwrap();
+        assert_eq!(db.bucket(&Bytes::from("aaa")).get(0, 200).unwrap().len(), 1);
+        assert!(db
+            .bucket(&Bytes::from("ccc"))
+            .get(0, 10)
+            .unwrap()
+            .is_empty());
+        assert_eq!(db.get_db().get(&BaseDbKey::from("block")).unwrap(), None);
+        assert_eq!(db.dump().unwrap(), dump);
+    }
+
+    #[test]
+    fn test_get_range_coverage() {
+        let db = RangeDbImpl::from(CoreDbMemoryImpl::open("test"));
+        let bucket = db.bucket(&Bytes::from("aaa"));
+        let _ = bucket.put(0, 100, b"Alice is owner");
+        let _ = bucket.put(150, 200, b"Bob is owner");
+        let _ = bucket.put(50, 60, b"Carol is owner");
+        assert_eq!(db.get_range_coverage(b"aaa").unwrap(), 150);
+        assert_eq!(db.get_range_coverage(b"bbb").unwrap(), 0);
+    }
+
+    #[test]
+    fn test_get_range_coverage_with_overlapping_ranges() {
+        // `put` never makes overlapping ranges, so they are written through a dump.
+        let mut buckets = HashMap::new();
+        buckets.insert(
+            b"aaa".to_vec(),
+            vec![
+                (0, 100, Bytes::from("Alice is owner"), 0),
+                (50, 150, Bytes::from("Bob is owner"), 0),
+            ],
+        );
+        let dump = DbDump {
+            version: DB_DUMP_VERSION,
+            buckets,
+            entries: vec![],
+        };
+        let db = RangeDbImpl::load(CoreDbMemoryImpl::open("test"), &dump).unwrap();
+        match db.get_range_coverage(b"aaa") {
+            Err(e) => match e.kind() {
+                ErrorKind::OverlappingRanges => {}
+                _ => panic!("unexpected error kind"),
+            },
+            Ok(_) => panic!("overlapping ranges must be an error"),
+        }
+    }
+
+    #[test]
+    fn test_get_intersection() {
+        let db = RangeDbImpl::from(CoreDbMemoryImpl::open("test"));
+        let bucket_a = db.bucket(&Bytes::from("aaa"));
+        let _ = bucket_a.put(0, 100, b"Alice is owner");
+        let _ = bucket_a.put(100, 200, b"Bob is owner");
+        let bucket_b = db.bucket(&Bytes::from("bbb"));
+        let _ = bucket_b.put(50, 150, b"exit");
+        let _ = bucket_b.put(300, 400, b"exit");
+        let pairs = db.get_intersection(b"aaa", b"bbb", 0, 500).unwrap();
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(pairs[0].0.get_value(), b"Alice is owner");
+        assert_eq!(pairs[1].1.get_start(), 50);
+        assert!(db
+            .get_intersection(b"aaa", b"bbb", 150, 200)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_get_all_in_bucket() {
+        let db = RangeDbImpl::from(CoreDbMemoryImpl::open("test"));
+        let bucket = db.bucket(&Bytes::from("aaa"));
+        let _ = bucket.put(100, 200, b"Bob is owner");
+        let _ = bucket.put(0, 100, b"Alice is owner");
+        let _ = bucket.put(std::u64::MAX - 1, std::u64::MAX, b"Carol is owner");
+        let _ = bucket
+            .bucket(&Bytes::from("bbb"))
+            .put(0, 10, b"Dave is owner");
+        let ranges = db.get_all_in_bucket(b"aaa").unwrap();
+        assert_eq!(ranges.len(), 3);
+        assert_eq!(ranges[0].get_value(), b"Alice is owner");
+        assert_eq!(ranges[2].get_end(), std::u64::MAX);
+        assert!(db.get_all_in_bucket(b"ccc").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_clone_bucket() {
+        let db = RangeDbImpl::from(CoreDbMemoryImpl::open("test"));
+        let from = db.bucket(&Bytes::from("from"));
+        let _ = from.put(0, 100, b"Alice is owner");
+        let _ = from.put(100, 200, b"Bob is owner");
+        let to = db.bucket(&Bytes::from("to"));
+        let _ = to.put(50, 150, b"Carol is owner");
+        let _ = to.put(300, 400, b"Dave is owner");
+        assert_eq!(db.clone_bucket(b"from", b"to").unwrap(), 2);
+        let ranges = db.get_all_in_bucket(b"to").unwrap();
+        assert_eq!(ranges.len(), 3);
+        assert_eq!(ranges[0].get_value(), b"Alice is owner");
+        assert_eq!(ranges[1].get_value(), b"Bob is owner");
+        assert_eq!(ranges[2].get_value(), b"Dave is owner");
+        assert_eq!(db.get_all_in_bucket(b"from").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_get_last_modified_in_range() {
+        let db = RangeDbImpl::from(CoreDbMemoryImpl::open("test"));
+        let bucket = db.bucket(&Bytes::from("aaa"));
+        assert_eq!(db.get_last_modified_in_range(b"aaa", 0, 100).unwrap(), None);
+        let _ = bucket.put(0, 100, b"Alice is owner");
+        let modified_at = db
+            .get_last_modified_in_range(b"aaa", 0, 100)
+            .unwrap()
+            .unwrap();
+        assert!(modified_at > 0);
+        assert_eq!(
+            db.get_last_modified_in_range(b"aaa", 100, 200).unwrap(),
+            None
+        );
+        assert!(bucket
+            .put_batch(&[Range::new(100, 200, b"Bob is owner").with_modified_at(modified_at + 10)])
+            .is_ok());
+        assert_eq!(
+            db.get_last_modified_in_range(b"aaa", 50, 150).unwrap(),
+            Some(modified_at + 10)
+        );
+    }
+
     #[test]
     fn test_put_covering_range() {
         let base_db = CoreDbMemoryImpl::open("test");
@@ -294,4 +752,60 @@ mod tests {
         assert_eq!(result[0].get_end(), 50);
         assert_eq!(result[0].get_value(), b"Bob is owner");
     }
+
+    #[test]
+    fn test_stats() {
+        let base_db = CoreDbMemoryImpl::open("test");
+        let db = RangeDbImpl::from(base_db);
+        assert_eq!(db.stats(), Default::default());
+        let bucket1 = db.bucket(&Bytes::from(&b"b1"[..]));
+        let bucket2 = db.bucket(&Bytes::from(&b"b2"[..]));
+        assert!(bucket1.put(0, 10, b"a").is_ok());
+        assert!(bucket2.put(0, 10, b"b").is_ok());
+        assert!(bucket2.put(10, 20, b"c").is_ok());
+        let stats = db.stats();
+        assert_eq!(stats.total_entries, 3);
+        assert_eq!(stats.bucket_count, 2);
+        assert_eq!(stats.largest_bucket, (b"b2".to_vec(), 2));
+        assert!(stats.total_bytes > 30);
+    }
+
+    #[test]
+    fn test_compact_keeps_remaining_ranges() {
+        let base_db = CoreDbMemoryImpl::open("test");
+        let db = RangeDbImpl::from(base_db);
+        for i in 0..1000 {
+            assert!(db.put(i * 10, (i + 1) * 10, b"value").is_ok());
+        }
+        assert!(db.del(0, 5000).is_ok());
+        assert!(db.compact().is_ok());
+        assert_eq!(db.stats().total_entries, 500);
+        assert_eq!(db.get(5000, 10000).unwrap().len(), 500);
+    }
+
+    #[cfg(feature = "reactive")]
+    #[test]
+    fn test_watch() {
+        use super::RangeChangeEvent;
+        use crate::range::Range;
+        use futures::{Future, Stream};
+
+        let base_db = CoreDbMemoryImpl::open("test");
+        let db = RangeDbImpl::from(base_db);
+        let stream = db.watch(b"bucket");
+        let bucket = db.bucket(&Bytes::from(&b"bucket"[..]));
+        assert!(bucket.put(0, 100, b"Alice").is_ok());
+        assert!(db.put(0, 100, b"not watched").is_ok());
+        assert!(bucket.put(50, 100, b"Bob").is_ok());
+        let events = stream.take(4).collect().wait().unwrap();
+        assert_eq!(
+            events,
+            vec![
+                RangeChangeEvent::Insert(Range::new(0, 100, b"Alice")),
+                RangeChangeEvent::Delete(0, 100),
+                RangeChangeEvent::Insert(Range::new(0, 50, b"Alice")),
+                RangeChangeEvent::Insert(Range::new(50, 100, b"Bob")),
+            ]
+        );
+    }
 }
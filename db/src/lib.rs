@@ -6,5 +6,7 @@ pub mod traits;
 #[macro_use]
 extern crate lazy_static;
 
-pub use impls::rangedb::RangeDbImpl;
+#[cfg(feature = "reactive")]
+pub use impls::rangedb::RangeChangeEvent;
+pub use impls::rangedb::{DbDump, DbStats, RangeDbImpl};
 pub use traits::kvs::BaseDbKey;
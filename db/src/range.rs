@@ -1,13 +1,25 @@
 use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
 use std::cmp::{max, min, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug)]
 pub struct Range {
     start: u64,
     end: u64,
     value: Vec<u8>,
+    /// Unix timestamp in seconds when the range was written. 0 if unknown.
+    modified_at: u64,
 }
 
+/// `modified_at` is ignored by comparison.
+impl PartialEq for Range {
+    fn eq(&self, other: &Range) -> bool {
+        self.start == other.start && self.end == other.end && self.value == other.value
+    }
+}
+
+impl Eq for Range {}
+
 impl Ord for Range {
     fn cmp(&self, other: &Range) -> Ordering {
         self.end.cmp(&other.end)
@@ -26,8 +38,21 @@ impl Range {
             start,
             end,
             value: value.to_vec(),
+            modified_at: 0,
         }
     }
+    /// Returns the range with `modified_at` set to the current time.
+    pub fn touch(self) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.with_modified_at(now)
+    }
+    pub fn with_modified_at(mut self, modified_at: u64) -> Self {
+        self.modified_at = modified_at;
+        self
+    }
     pub fn get_start(&self) -> u64 {
         self.start
     }
@@ -37,6 +62,9 @@ impl Range {
     pub fn get_value(&self) -> &[u8] {
         &self.value
     }
+    pub fn get_modified_at(&self) -> u64 {
+        self.modified_at
+    }
     pub fn validate(&self) -> bool {
         self.start < self.end
     }
@@ -54,6 +82,7 @@ impl Range {
                 start: intersection_start,
                 end: intersection_end,
                 value: self.value.clone(),
+                modified_at: self.modified_at,
             })
         } else {
             None
@@ -63,19 +92,26 @@ impl Range {
 
 impl Encodable for Range {
     fn rlp_append(&self, s: &mut RlpStream) {
-        s.begin_list(3);
+        s.begin_list(4);
         s.append(&self.start);
         s.append(&self.end);
         s.append(&self.value);
+        s.append(&self.modified_at);
     }
 }
 
+/// Ranges written without `modified_at` are decoded with 0.
 impl Decodable for Range {
     fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
         let start: u64 = rlp.val_at(0)?;
         let end: u64 = rlp.val_at(1)?;
         let value: Vec<u8> = rlp.val_at(2)?;
-        Ok(Range::new(start, end, &value))
+        let modified_at: u64 = if rlp.item_count()? > 3 {
+            rlp.val_at(3)?
+        } else {
+            0
+        };
+        Ok(Range::new(start, end, &value).with_modified_at(modified_at))
     }
 }
 
@@ -90,6 +126,21 @@ mod tests {
         assert_eq!(range.intersect(100, 200), false);
     }
 
+    #[test]
+    fn test_decode_modified_at() {
+        let range = Range::new(0, 100, b"aaa").with_modified_at(10);
+        let decoded: Range = rlp::decode(&rlp::encode(&range)).unwrap();
+        assert_eq!(decoded.get_modified_at(), 10);
+        let mut legacy = rlp::RlpStream::new_list(3);
+        legacy
+            .append(&0u64)
+            .append(&100u64)
+            .append(&b"aaa".to_vec());
+        let decoded: Range = rlp::decode(&legacy.out()).unwrap();
+        assert_eq!(decoded, Range::new(0, 100, b"aaa"));
+        assert_eq!(decoded.get_modified_at(), 0);
+    }
+
     #[test]
     fn test_get_intersection() {
         let range = Range::new(0, 100, b"aaa");
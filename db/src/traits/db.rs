@@ -1,4 +1,13 @@
+use crate::error::Error;
+
 pub trait DatabaseTrait {
     fn open(dbname: &str) -> Self;
     fn close(&self);
+    /// Runs `f` atomically. All writes made in `f` are rolled back if it returns `Err`.
+    /// Writes from other threads wait until the transaction ends.
+    fn transaction<F, T, E>(&self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&Self) -> Result<T, E>,
+        E: From<Error>,
+        Self: Sized;
 }
@@ -130,6 +130,10 @@ pub trait KeyValueStore {
         f: Box<dyn FnMut(&BaseDbKey, &Vec<u8>) -> bool>,
     ) -> Vec<KeyValue>;
     fn bucket<'a>(&'a self, prefix: &BaseDbKey) -> Bucket<'a>;
+    /// Reclaims storage of deleted entries. Backends which free storage on deletion do nothing.
+    fn compact(&self) -> Result<(), Error> {
+        Ok(())
+    }
     fn root(&self) -> Bucket {
         self.bucket(&b""[..].into())
     }
@@ -183,4 +187,8 @@ impl<'a> KeyValueStore for Bucket<'a> {
     fn bucket<'b>(&'b self, prefix: &BaseDbKey) -> Bucket<'b> {
         self.store.bucket(&self.prefix.concat(prefix))
     }
+    /// Compacts the whole store, not only this bucket.
+    fn compact(&self) -> Result<(), Error> {
+        self.store.compact()
+    }
 }
@@ -1,13 +1,29 @@
 use ethabi::Hash;
 use plasma_db::traits::kvs::{BaseDbKey, KeyValueStore};
+use web3::types::Log as RawLog;
 
-pub trait EventDb {
+/// EventDb is shared with EventWatcher which is spawned on tokio runtime,
+/// so implementors must be `Send + Sync`.
+pub trait EventDb: Send + Sync {
     fn get_last_logged_block(&self, topic_hash: Hash) -> Option<u64>;
     fn set_last_logged_block(&mut self, topic_hash: Hash, block_number: u64);
     fn get_event_seen(&self, event_hash: Hash) -> bool;
     fn set_event_seen(&mut self, event_hash: Hash);
+    /// Returns all `(event_signature, last_logged_block)` pairs.
+    fn get_tracked_event_signatures(&self) -> Vec<(Hash, u64)>;
+    /// Stores fetched log so that it can be replayed.
+    fn put_log(&mut self, log: &RawLog);
+    /// Returns all stored logs in the order they were put.
+    fn get_logs(&self) -> Vec<RawLog>;
 }
 
+/// Key of the concatenated signatures whose last logged block is stored.
+const TRACKED_EVENT_SIGNATURES_KEY: &str = "tracked_event_signatures";
+/// Key of the number of stored logs.
+const LOG_COUNT_KEY: &str = "log_count";
+/// Bucket of stored logs whose keys are sequential numbers.
+const LOGS_BUCKET: &str = "logs";
+
 pub struct EventDbImpl<KVS> {
     db: KVS,
 }
@@ -23,7 +39,7 @@ where
 
 impl<KVS> EventDb for EventDbImpl<KVS>
 where
-    KVS: KeyValueStore,
+    KVS: KeyValueStore + Send + Sync,
 {
     fn get_last_logged_block(&self, topic_hash: Hash) -> Option<u64> {
         match self.db.get(&BaseDbKey::new(topic_hash.0.to_vec())) {
@@ -34,6 +50,12 @@ where
     }
 
     fn set_last_logged_block(&mut self, topic_hash: Hash, block_number: u64) {
+        if self.get_last_logged_block(topic_hash).is_none() {
+            let key = BaseDbKey::from(TRACKED_EVENT_SIGNATURES_KEY);
+            let mut signatures = self.db.get(&key).ok().and_then(|v| v).unwrap_or_default();
+            signatures.extend_from_slice(&topic_hash.0);
+            let _ = self.db.put(&key, &signatures);
+        }
         let _ = self.db.put(
             &BaseDbKey::new(topic_hash.0.to_vec()),
             &rlp::encode(&block_number),
@@ -53,6 +75,45 @@ where
             .db
             .put(&BaseDbKey::new(event_hash.0.to_vec()), &rlp::encode(&true));
     }
+
+    fn get_tracked_event_signatures(&self) -> Vec<(Hash, u64)> {
+        let signatures = match self.db.get(&BaseDbKey::from(TRACKED_EVENT_SIGNATURES_KEY)) {
+            Ok(Some(v)) => v,
+            _ => return vec![],
+        };
+        signatures
+            .chunks(32)
+            .map(Hash::from_slice)
+            .filter_map(|signature| {
+                self.get_last_logged_block(signature)
+                    .map(|block_number| (signature, block_number))
+            })
+            .collect()
+    }
+
+    fn put_log(&mut self, log: &RawLog) {
+        let count_key = BaseDbKey::from(LOG_COUNT_KEY);
+        let count: u64 = match self.db.get(&count_key) {
+            Ok(Some(v)) => rlp::decode(&v[..]).unwrap(),
+            _ => 0,
+        };
+        if let Ok(encoded) = serde_json::to_vec(log) {
+            let _ = self
+                .db
+                .bucket(&BaseDbKey::from(LOGS_BUCKET))
+                .put(&count.into(), &encoded);
+            let _ = self.db.put(&count_key, &rlp::encode(&(count + 1)));
+        }
+    }
+
+    fn get_logs(&self) -> Vec<RawLog> {
+        self.db
+            .bucket(&BaseDbKey::from(LOGS_BUCKET))
+            .iter_all(&BaseDbKey::new(vec![]), Box::new(|_k, _v| true))
+            .iter()
+            .filter_map(|kv| serde_json::from_slice(kv.get_value()).ok())
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -80,4 +141,35 @@ mod tests {
         db.set_event_seen(k);
         assert_eq!(db.get_event_seen(k), true);
     }
+
+    #[test]
+    fn test_tracked_event_signatures() {
+        let kvs = CoreDbMemoryImpl::open("kvs");
+        let mut db = EventDbImpl::from(kvs);
+        let k1 = Hash::random();
+        let k2 = Hash::random();
+        assert!(db.get_tracked_event_signatures().is_empty());
+        db.set_last_logged_block(k1, 1);
+        db.set_last_logged_block(k2, 5);
+        db.set_last_logged_block(k1, 3);
+        assert_eq!(db.get_tracked_event_signatures(), vec![(k1, 3), (k2, 5)]);
+    }
+
+    #[test]
+    fn test_put_and_get_logs() {
+        let kvs = CoreDbMemoryImpl::open("kvs");
+        let mut db = EventDbImpl::from(kvs);
+        assert!(db.get_logs().is_empty());
+        for i in 0..3 {
+            let log: RawLog = serde_json::from_str(&format!(
+                r#"{{"address":"0x0000000000000000000000000000000000000000","topics":[],"data":"0x0{}"}}"#,
+                i
+            ))
+            .unwrap();
+            db.put_log(&log);
+        }
+        let logs = db.get_logs();
+        assert_eq!(logs.len(), 3);
+        assert_eq!(logs[2].data.0, vec![2]);
+    }
 }
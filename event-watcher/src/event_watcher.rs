@@ -2,51 +2,201 @@ use super::event_db::EventDb;
 use ethabi::{decode, Error, ErrorKind, Event, EventParam, ParamType, Token, Topic, TopicFilter};
 use ethereum_types::{Address, H256};
 use futures::{Async, Future, Poll, Stream};
-use std::time::Duration;
+use std::fmt;
+use std::time::{Duration, Instant};
 use tokio::timer::Interval;
+#[cfg(feature = "websocket")]
+use web3::api::SubscriptionStream;
 use web3::types::{BlockNumber, FilterBuilder, Log as RawLog};
-use web3::{transports, Web3};
+use web3::{transports, Transport, Web3};
 
-pub struct EventFetcher<T>
+#[derive(Debug)]
+pub enum BackfillError {
+    InvalidBlockRange,
+    Web3(web3::Error),
+    Decode(Error),
+}
+
+impl fmt::Display for BackfillError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BackfillError::InvalidBlockRange => write!(f, "Invalid block range"),
+            BackfillError::Web3(e) => write!(f, "Web3 error: {}", e),
+            BackfillError::Decode(e) => write!(f, "Decode error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for BackfillError {}
+
+/// Fetches logs by polling with HTTP transport, or by subscription with WebSocket transport.
+pub struct EventFetcher<T, Tr = transports::Http>
 where
-    T: EventDb,
+    T: EventDb + Send + Sync,
+    Tr: Transport,
 {
     interval: Interval,
-    web3: Web3<transports::Http>,
+    /// Interval set by `set_interval`, applied at the next poll.
+    pending_interval: Option<Duration>,
+    web3: Web3<Tr>,
     address: Address,
     abi: Vec<Event>,
     db: T,
+    /// Stores fetched logs in db so that they can be replayed.
+    replay_enabled: bool,
+    /// Logs pushed by `eth_subscribe`. Only used with WebSocket transport.
+    #[cfg(feature = "websocket")]
+    subscription: Option<SubscriptionStream<transports::WebSocket, RawLog>>,
 }
 
-impl<T> EventFetcher<T>
+impl<T, Tr> EventFetcher<T, Tr>
 where
-    T: EventDb,
+    T: EventDb + Send + Sync,
+    Tr: Transport,
 {
-    pub fn new(web3: Web3<transports::Http>, address: Address, abi: Vec<Event>, db: T) -> Self {
+    pub fn new(web3: Web3<Tr>, address: Address, abi: Vec<Event>, db: T) -> Self {
         EventFetcher {
             interval: Interval::new_interval(Duration::from_secs(1)),
+            pending_interval: None,
             address,
             abi,
             web3,
             db,
+            replay_enabled: false,
+            #[cfg(feature = "websocket")]
+            subscription: None,
         }
     }
 
-    fn filter_logs(&self, event: &Event, logs: Vec<RawLog>) -> Vec<RawLog> {
-        if let Some(last_logged_block) = self.db.get_last_logged_block(event.signature()) {
-            logs.iter()
-                .filter(|&log| {
-                    if let Some(n) = log.block_number {
-                        n.low_u64() > last_logged_block
-                    } else {
-                        false
-                    }
+    pub fn set_replay_enabled(&mut self, replay_enabled: bool) {
+        self.replay_enabled = replay_enabled;
+    }
+
+    /// Changes polling interval. The next poll waits `new_interval` from the time it's applied.
+    pub fn set_interval(&mut self, new_interval: Duration) {
+        self.pending_interval = Some(new_interval);
+    }
+
+    /// Decodes all logs stored in db with the subscribed events.
+    pub fn get_stored_logs(&self) -> Vec<Log> {
+        self.db
+            .get_logs()
+            .iter()
+            .filter_map(|raw_log| {
+                let event = self
+                    .abi
+                    .iter()
+                    .find(|e| raw_log.topics.first() == Some(&e.signature()))?;
+                self.decode_logs(event, &[raw_log.clone()])
+                    .ok()
+                    .and_then(|mut logs| logs.pop())
+            })
+            .collect()
+    }
+
+    /// Starts fetching logs of `event` if it isn't fetched yet.
+    pub fn subscribe(&mut self, event: Event) {
+        if !self.abi.iter().any(|e| e.signature() == event.signature()) {
+            self.abi.push(event);
+        }
+    }
+
+    /// Returns the first block which isn't fetched yet by any of subscribed events.
+    pub fn get_backfill_from_block(&self) -> u64 {
+        self.abi
+            .iter()
+            .map(
+                |event| match self.db.get_last_logged_block(event.signature()) {
+                    Some(n) => n + 1,
+                    None => 0,
+                },
+            )
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// Gets the latest block number of the node synchronously.
+    pub fn get_block_number(&self) -> Result<u64, BackfillError> {
+        let block_number = self
+            .web3
+            .eth()
+            .block_number()
+            .wait()
+            .map_err(BackfillError::Web3)?;
+        Ok(block_number.low_u64())
+    }
+
+    /// Fetches all logs from `from_block` to `to_block` synchronously
+    /// and updates last logged block so that the stream doesn't fetch them again.
+    pub fn backfill(&mut self, from_block: u64, to_block: u64) -> Result<Vec<Log>, BackfillError> {
+        if from_block > to_block {
+            return Err(BackfillError::InvalidBlockRange);
+        }
+        let mut all_logs: Vec<Log> = vec![];
+        for event in self.abi.iter() {
+            let filter = FilterBuilder::default()
+                .address(vec![self.address])
+                .from_block(BlockNumber::Number(from_block))
+                .to_block(BlockNumber::Number(to_block))
+                .topic_filter(TopicFilter {
+                    topic0: Topic::This(event.signature()),
+                    topic1: Topic::Any,
+                    topic2: Topic::Any,
+                    topic3: Topic::Any,
                 })
-                .cloned()
-                .collect()
-        } else {
-            logs.clone()
+                .build();
+            let raw_logs = self
+                .web3
+                .eth()
+                .logs(filter)
+                .wait()
+                .map_err(BackfillError::Web3)?;
+            let logs = self
+                .decode_logs(event, &raw_logs)
+                .map_err(BackfillError::Decode)?;
+            all_logs.extend_from_slice(&logs);
         }
+        if self.replay_enabled {
+            for log in all_logs.iter() {
+                self.db.put_log(&log.log);
+            }
+        }
+        for event in self.abi.iter() {
+            let sig = event.signature();
+            match self.db.get_last_logged_block(sig) {
+                Some(n) if n >= to_block => {}
+                _ => self.db.set_last_logged_block(sig, to_block),
+            }
+        }
+        Ok(all_logs)
+    }
+
+    /// Updates last logged block of `sig` and stores logs for replay.
+    #[cfg(feature = "websocket")]
+    fn store_fetched_logs(&mut self, sig: H256, logs: &[Log]) {
+        if let Some(last_log) = logs.last() {
+            if let Some(block_num) = last_log.log.block_number {
+                self.db.set_last_logged_block(sig, block_num.low_u64());
+            };
+        };
+        if self.replay_enabled {
+            for log in logs.iter() {
+                self.db.put_log(&log.log);
+            }
+        }
+    }
+
+    fn decode_logs(&self, event: &Event, raw_logs: &[RawLog]) -> Result<Vec<Log>, Error> {
+        raw_logs
+            .iter()
+            .map(|raw_log| -> Result<Log, Error> {
+                Ok(Log {
+                    log: raw_log.clone(),
+                    event_signature: event.signature(),
+                    params: self.decode_params(event, raw_log)?,
+                })
+            })
+            .collect()
     }
 
     fn decode_params(&self, event: &Event, log: &RawLog) -> Result<Vec<DecodedParam>, Error> {
@@ -86,6 +236,38 @@ where
     }
 }
 
+#[cfg(feature = "websocket")]
+impl<T> EventFetcher<T, transports::WebSocket>
+where
+    T: EventDb + Send + Sync,
+{
+    /// Connects to `url` and subscribes logs of `abi` events.
+    /// Events subscribed later by `subscribe` aren't pushed.
+    /// The returned event loop handle must be kept while the fetcher is used.
+    pub fn with_websocket(
+        url: &str,
+        address: Address,
+        abi: Vec<Event>,
+        db: T,
+    ) -> Result<(transports::EventLoopHandle, Self), web3::Error> {
+        let (eloop, transport) = transports::WebSocket::new(url)?;
+        let web3 = Web3::new(transport);
+        let filter = FilterBuilder::default()
+            .address(vec![address])
+            .topic_filter(TopicFilter {
+                topic0: Topic::OneOf(abi.iter().map(|event| event.signature()).collect()),
+                topic1: Topic::Any,
+                topic2: Topic::Any,
+                topic3: Topic::Any,
+            })
+            .build();
+        let subscription = web3.eth_subscribe().subscribe_logs(filter).wait()?;
+        let mut fetcher = Self::new(web3, address, abi, db);
+        fetcher.subscription = Some(subscription);
+        Ok((eloop, fetcher))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Log {
     pub log: RawLog,
@@ -99,21 +281,25 @@ pub struct DecodedParam {
     pub token: Token,
 }
 
-impl<T> Stream for EventFetcher<T>
+impl<T> Stream for EventFetcher<T, transports::Http>
 where
-    T: EventDb,
+    T: EventDb + Send + Sync,
 {
     type Item = Vec<Log>;
     type Error = ();
 
     fn poll(&mut self) -> Poll<Option<Vec<Log>>, ()> {
+        if let Some(new_interval) = self.pending_interval.take() {
+            self.interval = Interval::new(Instant::now() + new_interval, new_interval);
+        }
         try_ready!(self.interval.poll().map_err(|_| ()));
         let mut all_logs: Vec<Log> = vec![];
 
         for event in self.abi.iter() {
             let sig = event.signature();
+            // logs in the last logged block are already handled
             let from_block: u64 = match self.db.get_last_logged_block(sig) {
-                Some(n) => n,
+                Some(n) => n + 1,
                 None => 0,
             };
             let filter = FilterBuilder::default()
@@ -129,20 +315,7 @@ where
 
             match self.web3.eth().logs(filter).wait().map_err(|e| e) {
                 Ok(v) => {
-                    let decoded: Result<Vec<Log>, Error> = self
-                        .filter_logs(event, v)
-                        .iter()
-                        .map(|raw_log| -> Result<Log, Error> {
-                            match self.decode_params(event, raw_log) {
-                                Ok(decoded_params) => Ok(Log {
-                                    log: raw_log.clone(),
-                                    event_signature: event.signature(),
-                                    params: decoded_params,
-                                }),
-                                Err(e) => Err(e),
-                            }
-                        })
-                        .collect();
+                    let decoded = self.decode_logs(event, &v);
 
                     match decoded {
                         Ok(logs) => {
@@ -151,6 +324,11 @@ where
                                     self.db.set_last_logged_block(sig, block_num.low_u64());
                                 };
                             };
+                            if self.replay_enabled {
+                                for log in logs.iter() {
+                                    self.db.put_log(&log.log);
+                                }
+                            }
 
                             all_logs.extend_from_slice(&logs);
                         }
@@ -169,41 +347,148 @@ where
     }
 }
 
+/// Yields logs as soon as they are pushed by the node.
+#[cfg(feature = "websocket")]
+impl<T> Stream for EventFetcher<T, transports::WebSocket>
+where
+    T: EventDb + Send + Sync,
+{
+    type Item = Vec<Log>;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<Vec<Log>>, ()> {
+        let raw_log = match &mut self.subscription {
+            Some(subscription) => try_ready!(subscription.poll().map_err(|e| println!("{}", e))),
+            None => None,
+        };
+        let raw_log = match raw_log {
+            Some(raw_log) => raw_log,
+            None => return Ok(Async::Ready(None)),
+        };
+        let event = match self
+            .abi
+            .iter()
+            .find(|e| raw_log.topics.first() == Some(&e.signature()))
+        {
+            Some(event) => event.clone(),
+            None => return Ok(Async::Ready(Some(vec![]))),
+        };
+        match self.decode_logs(&event, &[raw_log]) {
+            Ok(logs) => {
+                self.store_fetched_logs(event.signature(), &logs);
+                Ok(Async::Ready(Some(logs)))
+            }
+            Err(e) => {
+                println!("{}", e);
+                Ok(Async::Ready(Some(vec![])))
+            }
+        }
+    }
+}
+
 pub trait EventHandler {
     fn on_event(&self, log: &Log);
 }
 
+/// Index of listener registered by `EventWatcher::add_listener`.
+pub type ListenerId = usize;
+
 pub struct EventWatcher<T, E>
 where
-    T: EventDb,
+    T: EventDb + Send + Sync,
     E: EventHandler,
 {
     stream: EventFetcher<T>,
     handler: E,
+    listeners: Vec<Box<dyn EventHandler + Send>>,
     _eloop: transports::EventLoopHandle,
 }
 
 impl<T, E> EventWatcher<T, E>
 where
-    T: EventDb,
+    T: EventDb + Send + Sync,
     E: EventHandler,
 {
     pub fn new(url: &str, address: Address, abi: Vec<Event>, db: T, handler: E) -> Self {
+        for (signature, last_logged_block) in db.get_tracked_event_signatures() {
+            println!(
+                "tracking event {:?} from block {}",
+                signature, last_logged_block
+            );
+        }
         let (eloop, transport) = web3::transports::Http::new(url).unwrap();
         let web3 = web3::Web3::new(transport);
         let stream = EventFetcher::new(web3, address, abi, db);
 
-        EventWatcher {
+        let mut watcher = EventWatcher {
             _eloop: eloop,
             stream,
             handler,
+            listeners: vec![],
+        };
+        // historical events are handled before watching new ones
+        if let Err(e) = watcher.backfill_to_latest() {
+            println!("failed to backfill events: {}", e);
+        }
+        watcher
+    }
+
+    /// Stores all fetched logs so that `replay` can re-deliver them.
+    /// It increases storage as every log is stored.
+    pub fn set_replay_enabled(&mut self, replay_enabled: bool) {
+        self.stream.set_replay_enabled(replay_enabled);
+    }
+
+    /// Registers listener which receives events in addition to the handler.
+    pub fn add_listener(&mut self, listener: Box<dyn EventHandler + Send>) -> ListenerId {
+        self.listeners.push(listener);
+        self.listeners.len() - 1
+    }
+
+    /// Re-delivers all stored events to the listener of `listener_id`.
+    /// Only events fetched while replay is enabled are stored.
+    pub fn replay(&mut self, listener_id: ListenerId) {
+        if let Some(listener) = self.listeners.get(listener_id) {
+            for log in self.stream.get_stored_logs().iter() {
+                listener.on_event(log);
+            }
         }
     }
+
+    fn dispatch(&self, logs: &[Log]) {
+        for log in logs.iter() {
+            self.handler.on_event(log);
+            for listener in self.listeners.iter() {
+                listener.on_event(log);
+            }
+        }
+    }
+
+    /// Handles all events from `from_block` to `to_block` before starting to watch.
+    pub fn backfill(&mut self, from_block: u64, to_block: u64) -> Result<(), BackfillError> {
+        let logs = self.stream.backfill(from_block, to_block)?;
+        self.dispatch(&logs);
+        Ok(())
+    }
+
+    /// Handles all events which aren't fetched yet up to the latest block.
+    pub fn backfill_to_latest(&mut self) -> Result<(), BackfillError> {
+        let from_block = self.stream.get_backfill_from_block();
+        let to_block = self.stream.get_block_number()?;
+        if from_block > to_block {
+            return Ok(());
+        }
+        self.backfill(from_block, to_block)
+    }
+
+    pub fn subscribe(&mut self, event: Event) {
+        self.stream.subscribe(event)
+    }
 }
 
 impl<T, E> Future for EventWatcher<T, E>
 where
-    T: EventDb,
+    T: EventDb + Send + Sync,
     E: EventHandler,
 {
     type Item = ();
@@ -216,9 +501,126 @@ where
                 None => continue,
             };
 
-            for log in logs.iter() {
-                self.handler.on_event(&log);
-            }
+            self.dispatch(&logs);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BackfillError, EventFetcher, EventHandler, EventWatcher, Log};
+    use crate::event_db::{EventDb, EventDbImpl};
+    use ethabi::Event;
+    use ethereum_types::Address;
+    use ethereum_types::H256;
+    use futures::{Future, Stream};
+    use plasma_db::impls::kvs::memory::CoreDbMemoryImpl;
+    use plasma_db::traits::DatabaseTrait;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+    use tokio::runtime::current_thread::Runtime;
+    use web3::{transports, Web3};
+
+    fn create_event(name: &str) -> Event {
+        Event {
+            name: name.to_owned(),
+            inputs: vec![],
+            anonymous: false,
         }
     }
+
+    /// Records signatures of received events.
+    #[derive(Clone, Default)]
+    struct RecordingHandler {
+        signatures: Arc<Mutex<Vec<H256>>>,
+    }
+
+    impl EventHandler for RecordingHandler {
+        fn on_event(&self, log: &Log) {
+            self.signatures.lock().unwrap().push(log.event_signature);
+        }
+    }
+
+    #[test]
+    fn test_event_watcher_is_spawnable() {
+        // fails to compile if EventWatcher can't be moved into tokio::spawn
+        fn assert_spawnable<F: Future<Item = (), Error = ()> + Send + 'static>() {}
+        assert_spawnable::<EventWatcher<EventDbImpl<CoreDbMemoryImpl>, RecordingHandler>>();
+    }
+
+    #[test]
+    fn test_replay() {
+        let event = create_event("A");
+        let raw_log = serde_json::from_value(serde_json::json!({
+            "address": format!("{:?}", Address::zero()),
+            "topics": [format!("{:?}", event.signature())],
+            "data": "0x",
+        }))
+        .unwrap();
+        let mut db = EventDbImpl::from(CoreDbMemoryImpl::open("kvs"));
+        db.put_log(&raw_log);
+        // the node is unreachable, so nothing is backfilled
+        let handler = RecordingHandler::default();
+        let mut watcher = EventWatcher::new(
+            "http://127.0.0.1:1",
+            Address::zero(),
+            vec![event.clone()],
+            db,
+            handler.clone(),
+        );
+        let listener = RecordingHandler::default();
+        let listener_id = watcher.add_listener(Box::new(listener.clone()));
+        watcher.replay(listener_id);
+        assert_eq!(
+            *listener.signatures.lock().unwrap(),
+            vec![event.signature()]
+        );
+        assert!(handler.signatures.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_backfill() {
+        // no events are subscribed, so the node isn't requested
+        let (_eloop, transport) = transports::Http::new("http://localhost:8545").unwrap();
+        let db = EventDbImpl::from(CoreDbMemoryImpl::open("kvs"));
+        let mut fetcher = EventFetcher::new(Web3::new(transport), Address::zero(), vec![], db);
+        match fetcher.backfill(10, 5) {
+            Err(BackfillError::InvalidBlockRange) => {}
+            _ => panic!("block range should be invalid"),
+        }
+        assert!(fetcher.backfill(0, 5).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_get_backfill_from_block() {
+        let (_eloop, transport) = transports::Http::new("http://localhost:8545").unwrap();
+        let mut db = EventDbImpl::from(CoreDbMemoryImpl::open("kvs"));
+        db.set_last_logged_block(create_event("A").signature(), 10);
+        db.set_last_logged_block(create_event("B").signature(), 5);
+        let mut fetcher = EventFetcher::new(
+            Web3::new(transport),
+            Address::zero(),
+            vec![create_event("A"), create_event("B")],
+            db,
+        );
+        assert_eq!(fetcher.get_backfill_from_block(), 6);
+        fetcher.subscribe(create_event("C"));
+        assert_eq!(fetcher.get_backfill_from_block(), 0);
+    }
+
+    #[test]
+    fn test_set_interval() {
+        // no events are subscribed, so the node isn't requested
+        let (_eloop, transport) = transports::Http::new("http://localhost:8545").unwrap();
+        let db = EventDbImpl::from(CoreDbMemoryImpl::open("kvs"));
+        let mut fetcher = EventFetcher::new(Web3::new(transport), Address::zero(), vec![], db);
+        let mut runtime = Runtime::new().unwrap();
+        // replaces the default interval of 1 second before the first tick
+        fetcher.set_interval(Duration::from_millis(50));
+        let started_at = Instant::now();
+        assert!(runtime.block_on(fetcher.by_ref().take(3).collect()).is_ok());
+        let elapsed = started_at.elapsed();
+        assert!(elapsed >= Duration::from_millis(150));
+        assert!(elapsed < Duration::from_secs(1));
+    }
 }
@@ -3,6 +3,7 @@ extern crate futures;
 extern crate ethabi;
 extern crate plasma_db;
 extern crate rlp;
+extern crate serde_json;
 extern crate tokio;
 extern crate web3;
 
@@ -10,4 +11,4 @@ pub mod event_db;
 pub mod event_watcher;
 
 pub use self::event_db::EventDbImpl;
-pub use self::event_watcher::{EventHandler, EventWatcher, Log};
+pub use self::event_watcher::{BackfillError, EventHandler, EventWatcher, ListenerId, Log};
@@ -42,6 +42,8 @@ pub struct DoubleLayerTreeLeaf {
 pub struct DoubleLayerTree {
     tree: AddressTree,
     interval_trees: HashMap<Address, MerkleIntervalTree<u64>>,
+    /// Leaves in the order given to `generate`.
+    leaves: Vec<DoubleLayerTreeLeaf>,
 }
 
 impl From<&DoubleLayerTreeLeaf> for MerkleIntervalNode<u64> {
@@ -80,11 +82,25 @@ impl DoubleLayerTree {
         Self {
             tree: address_tree,
             interval_trees,
+            leaves: double_layer_tree_leaves.to_vec(),
         }
     }
     pub fn get_root(&self) -> Bytes {
         self.tree.get_root()
     }
+    pub fn get_total_leaf_count(&self) -> usize {
+        self.leaves.len()
+    }
+    /// Returns leaf at `index` in the order given to `generate`.
+    pub fn get_leaf_by_index(&self, index: usize) -> Option<&DoubleLayerTreeLeaf> {
+        self.leaves.get(index)
+    }
+    pub fn get_leaves_for_address(&self, address: Address) -> Vec<&DoubleLayerTreeLeaf> {
+        self.leaves
+            .iter()
+            .filter(|leaf| leaf.address == address)
+            .collect()
+    }
     pub fn get_address_index(&self, address: Address) -> usize {
         self.tree.get_index(address)
     }
@@ -165,5 +181,10 @@ mod tests {
             inclusion_proof,
             &root
         ));
+        assert_eq!(tree.get_total_leaf_count(), 200);
+        assert_eq!(tree.get_leaf_by_index(100).unwrap().address, address2);
+        assert!(tree.get_leaf_by_index(200).is_none());
+        assert_eq!(tree.get_leaves_for_address(address1).len(), 100);
+        assert!(tree.get_leaves_for_address(Address::zero()).is_empty());
     }
 }
@@ -0,0 +1,26 @@
+#[macro_use]
+extern crate criterion;
+
+use bytes::Bytes;
+use criterion::Criterion;
+use ethsign::SecretKey;
+use ovm::deciders::SignVerifier;
+
+fn sign_batch_benchmark(c: &mut Criterion) {
+    let raw_key =
+        hex::decode("c87509a1c067bbde78beb793e6fa76530b6382a4c0241e5e4a9ec0a0f44dc0d3").unwrap();
+    let secret_key = SecretKey::from_raw(&raw_key).unwrap();
+    c.bench_function_over_inputs(
+        "sign_batch",
+        move |b, &&size| {
+            let messages: Vec<Bytes> = (0..size)
+                .map(|i| Bytes::from(format!("message{}", i)))
+                .collect();
+            b.iter(|| SignVerifier::sign_batch(&secret_key, &messages))
+        },
+        &[1, 10, 100],
+    );
+}
+
+criterion_group!(benches, sign_batch_benchmark);
+criterion_main!(benches);
@@ -9,7 +9,7 @@ pub mod transaction_filter;
 pub use self::channel_db::{Channel, ChannelDb};
 pub use self::hash_preimage_db::HashPreimageDb;
 pub use self::message_db::{Message, MessageDb};
-pub use self::range_at_block_db::{RangeAtBlockDb, RangeAtBlockRecord};
+pub use self::range_at_block_db::{RangeAtBlockDb, RangeAtBlockRecord, RangeAtBlockRecordBuilder};
 pub use self::signed_by_db::SignedByDb;
 pub use self::transaction_db::TransactionDb;
 pub use self::transaction_filter::{TransactionFilter, TransactionFilterBuilder};
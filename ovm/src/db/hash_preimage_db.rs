@@ -44,6 +44,22 @@ impl<'a, KVS: KeyValueStore> HashPreimageDb<'a, KVS> {
         }
         PreimageRecord::from_abi(&result.unwrap()).map_err::<Error, _>(Into::into)
     }
+    /// Returns all known preimages of `hash`.
+    pub fn get_preimages(&self, hash: H256) -> Result<Vec<Bytes>, Error> {
+        let result = self
+            .db
+            .bucket(&BaseDbKey::from(&b"preimage_exists_decider"[..]))
+            .get(&BaseDbKey::from(hash.as_bytes()))
+            .map_err::<Error, _>(Into::into)?;
+        match result {
+            Some(record) => Ok(vec![
+                PreimageRecord::from_abi(&record)
+                    .map_err::<Error, _>(Into::into)?
+                    .preimage,
+            ]),
+            None => Ok(vec![]),
+        }
+    }
 }
 
 // require to implement common interface "handle_message"
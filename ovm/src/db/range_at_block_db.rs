@@ -1,9 +1,10 @@
-use crate::error::Error;
+use crate::error::{BuildError, Error};
 use crate::types::{Integer, PlasmaDataBlock};
 use abi_derive::{AbiDecodable, AbiEncodable};
 use abi_utils::{Decodable, Encodable};
 use bytes::Bytes;
 use ethabi::{ParamType, Token};
+use ethereum_types::{Address, H256};
 use plasma_core::data_structure::Range;
 use plasma_db::traits::kvs::KeyValueStore;
 use plasma_db::traits::rangestore::RangeStore;
@@ -26,6 +27,88 @@ impl RangeAtBlockRecord {
     }
 }
 
+/// Builds RangeAtBlockRecord of `range` at `block_number` without constructing PlasmaDataBlock.
+/// `is_included` is true and `data` is empty unless they are set.
+#[derive(Clone, Debug)]
+pub struct RangeAtBlockRecordBuilder {
+    block_number: Integer,
+    range: Range,
+    deposit_address: Option<Address>,
+    inclusion_proof: Option<Bytes>,
+    is_included: bool,
+    root: Option<H256>,
+    data: Bytes,
+}
+
+impl RangeAtBlockRecordBuilder {
+    pub fn new(block_number: Integer, range: Range) -> Self {
+        Self {
+            block_number,
+            range,
+            deposit_address: None,
+            inclusion_proof: None,
+            is_included: true,
+            root: None,
+            data: Bytes::new(),
+        }
+    }
+
+    pub fn deposit_address(mut self, deposit_address: Address) -> Self {
+        self.deposit_address = Some(deposit_address);
+        self
+    }
+
+    pub fn inclusion_proof(mut self, inclusion_proof: Bytes) -> Self {
+        self.inclusion_proof = Some(inclusion_proof);
+        self
+    }
+
+    pub fn is_included(mut self, is_included: bool) -> Self {
+        self.is_included = is_included;
+        self
+    }
+
+    pub fn root(mut self, root: H256) -> Self {
+        self.root = Some(root);
+        self
+    }
+
+    pub fn data(mut self, data: Bytes) -> Self {
+        self.data = data;
+        self
+    }
+
+    /// Returns error if deposit address, inclusion proof or root is missing, or range is empty.
+    pub fn build(self) -> Result<RangeAtBlockRecord, BuildError> {
+        let deposit_address = self
+            .deposit_address
+            .ok_or(BuildError::MissingField("deposit_address"))?;
+        let inclusion_proof = self
+            .inclusion_proof
+            .ok_or(BuildError::MissingField("inclusion_proof"))?;
+        let root = Bytes::from(
+            self.root
+                .ok_or(BuildError::MissingField("root"))?
+                .as_bytes(),
+        );
+        if self.range.is_empty() {
+            return Err(BuildError::EmptyRange);
+        }
+        Ok(RangeAtBlockRecord::new(
+            root.clone(),
+            inclusion_proof,
+            PlasmaDataBlock::new(
+                deposit_address,
+                self.range,
+                root,
+                self.is_included,
+                self.block_number,
+                self.data,
+            ),
+        ))
+    }
+}
+
 pub struct RangeAtBlockDb<'a, KVS: KeyValueStore> {
     db: &'a RangeDbImpl<KVS>,
 }
@@ -68,4 +151,74 @@ impl<'a, KVS: KeyValueStore> RangeAtBlockDb<'a, KVS> {
         }
         RangeAtBlockRecord::from_abi(&result[0].get_value()).map_err::<Error, _>(Into::into)
     }
+    /// Returns all records of `block_number` which intersect `coin_range`.
+    pub fn get_witnesses(
+        &self,
+        block_number: Integer,
+        coin_range: Range,
+    ) -> Result<Vec<RangeAtBlockRecord>, Error> {
+        let result = self
+            .db
+            .bucket(&Bytes::from(&b"range_at_block"[..]))
+            .bucket(&block_number.into())
+            .get(coin_range.get_start(), coin_range.get_end())
+            .map_err::<Error, _>(Into::into)?;
+        result
+            .iter()
+            .map(|r| RangeAtBlockRecord::from_abi(&r.get_value()).map_err::<Error, _>(Into::into))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plasma_db::impls::kvs::CoreDbMemoryImpl;
+    use plasma_db::traits::db::DatabaseTrait;
+
+    #[test]
+    fn test_range_at_block_record_builder() {
+        let builder = RangeAtBlockRecordBuilder::new(Integer::new(1), Range::new(0, 100))
+            .deposit_address(Address::zero())
+            .inclusion_proof(Bytes::from(&b"proof"[..]))
+            .root(H256::from_low_u64_be(1));
+        assert!(!builder
+            .clone()
+            .is_included(false)
+            .build()
+            .unwrap()
+            .plasma_data_block
+            .get_is_included());
+        assert_eq!(
+            RangeAtBlockRecordBuilder::new(Integer::new(1), Range::new(0, 100))
+                .build()
+                .unwrap_err(),
+            BuildError::MissingField("deposit_address")
+        );
+        assert_eq!(
+            RangeAtBlockRecordBuilder::new(Integer::new(1), Range::new(10, 10))
+                .deposit_address(Address::zero())
+                .inclusion_proof(Bytes::new())
+                .root(H256::zero())
+                .build()
+                .unwrap_err(),
+            BuildError::EmptyRange
+        );
+
+        let record = builder.build().unwrap();
+        let db = RangeDbImpl::from(CoreDbMemoryImpl::open("test"));
+        let range_at_block_db = RangeAtBlockDb::new(&db);
+        assert!(range_at_block_db
+            .store_witness(
+                record.root.clone(),
+                record.inclusion_proof.clone(),
+                record.plasma_data_block.clone(),
+            )
+            .is_ok());
+        let stored = range_at_block_db
+            .get_witness(Integer::new(1), Range::new(0, 100))
+            .unwrap();
+        assert_eq!(stored.inclusion_proof, Bytes::from(&b"proof"[..]));
+        assert!(stored.plasma_data_block.get_is_included());
+    }
 }
@@ -1,4 +1,5 @@
 pub mod and_decider;
+pub mod equal_decider;
 pub mod for_all_such_that_decider;
 pub mod has_lower_nonce;
 pub mod included_at_block_decider;
@@ -9,8 +10,10 @@ pub mod ownership_decider;
 pub mod preimage_exists_decider;
 pub mod signed_by_decider;
 pub mod there_exists_such_that_decider;
+pub mod time_decider;
 
 pub use self::and_decider::AndDecider;
+pub use self::equal_decider::EqualDecider;
 pub use self::for_all_such_that_decider::ForAllSuchThatDecider;
 pub use self::has_lower_nonce::HasLowerNonceDecider;
 pub use self::included_at_block_decider::IncludedAtBlockDecider;
@@ -21,3 +24,4 @@ pub use self::ownership_decider::OwnershipDecider;
 pub use self::preimage_exists_decider::PreimageExistsDecider;
 pub use self::signed_by_decider::{SignedByDecider, Verifier as SignVerifier};
 pub use self::there_exists_such_that_decider::ThereExistsSuchThatDecider;
+pub use self::time_decider::TimeDecider;
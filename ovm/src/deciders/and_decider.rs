@@ -1,5 +1,5 @@
 use crate::error::Error;
-use crate::property_executor::PropertyExecutor;
+use crate::property_executor::{DecisionContext, PropertyExecutor};
 use crate::types::{Decider, Decision, PropertyInput};
 use crate::DecideMixin;
 use plasma_db::traits::kvs::KeyValueStore;
@@ -22,11 +22,12 @@ impl Decider for AndDecider {
     fn decide<T: KeyValueStore>(
         decider: &PropertyExecutor<T>,
         inputs: &[PropertyInput],
+        context: &DecisionContext,
     ) -> Result<Decision, Error> {
         let left = decider.get_variable(&inputs[0]).to_property();
         let right = decider.get_variable(&inputs[1]).to_property();
-        let left_decision = left.decide(decider)?;
-        let right_decision = right.decide(decider)?;
+        let left_decision = left.decide(decider, context)?;
+        let right_decision = right.decide(decider, context)?;
         if !left_decision.get_outcome() {
             return Ok(left_decision);
         }
@@ -0,0 +1,85 @@
+use crate::error::Error;
+use crate::property_executor::{DecisionContext, PropertyExecutor};
+use crate::types::{
+    Decider, Decision, ImplicationProofElement, PropertyInput, QuantifierResultItem,
+};
+use crate::DeciderManager;
+use abi_utils::Encodable;
+use ethabi::Token;
+use plasma_db::traits::kvs::KeyValueStore;
+
+/// EqualDecider decides true if ABI encoded `inputs[0]` and `inputs[1]` are the same bytes.
+pub struct EqualDecider {}
+
+impl Default for EqualDecider {
+    fn default() -> Self {
+        EqualDecider {}
+    }
+}
+
+fn to_abi(item: &QuantifierResultItem) -> Vec<u8> {
+    match item {
+        QuantifierResultItem::Address(address) => ethabi::encode(&[Token::Address(*address)]),
+        QuantifierResultItem::Integer(integer) => ethabi::encode(&[Token::Uint(integer.0.into())]),
+        QuantifierResultItem::Bytes(bytes) => ethabi::encode(&[Token::Bytes(bytes.to_vec())]),
+        QuantifierResultItem::H256(h256) => {
+            ethabi::encode(&[Token::FixedBytes(h256.as_bytes().to_vec())])
+        }
+        QuantifierResultItem::Message(message) => message.to_abi(),
+        QuantifierResultItem::Property(property) => property.to_abi(),
+        QuantifierResultItem::PlasmaDataBlock(plasma_data_block) => plasma_data_block.to_abi(),
+        QuantifierResultItem::StateUpdate(state_update) => state_update.to_abi(),
+        QuantifierResultItem::Range(range) => range.to_abi(),
+    }
+}
+
+impl Decider for EqualDecider {
+    fn decide<T: KeyValueStore>(
+        decider: &PropertyExecutor<T>,
+        inputs: &[PropertyInput],
+        _context: &DecisionContext,
+    ) -> Result<Decision, Error> {
+        let left = decider.get_variable(&inputs[0]);
+        let right = decider.get_variable(&inputs[1]);
+        if to_abi(&left) == to_abi(&right) {
+            Ok(Decision::new(
+                true,
+                vec![ImplicationProofElement::new(
+                    DeciderManager::equal_decider(inputs.to_vec()),
+                    None,
+                )],
+            ))
+        } else {
+            Ok(Decision::new(false, vec![]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::property_executor::PropertyExecutor;
+    use crate::types::{Integer, PropertyInput, QuantifierResultItem};
+    use crate::DeciderManager;
+    use bytes::Bytes;
+    use plasma_db::impls::kvs::CoreDbMemoryImpl;
+
+    #[test]
+    fn test_decide_equal() {
+        let decider: PropertyExecutor<CoreDbMemoryImpl> = Default::default();
+        let placeholder = Bytes::from("n");
+        decider.set_variable(
+            placeholder.clone(),
+            QuantifierResultItem::Integer(Integer::new(10)),
+        );
+        let equal = DeciderManager::equal_decider(vec![
+            PropertyInput::Placeholder(placeholder.clone()),
+            PropertyInput::ConstantInteger(Integer::new(10)),
+        ]);
+        assert!(decider.decide(&equal).unwrap().get_outcome());
+        let not_equal = DeciderManager::equal_decider(vec![
+            PropertyInput::Placeholder(placeholder),
+            PropertyInput::ConstantInteger(Integer::new(11)),
+        ]);
+        assert!(!decider.decide(&not_equal).unwrap().get_outcome());
+    }
+}
@@ -1,5 +1,5 @@
 use crate::error::{Error, ErrorKind};
-use crate::property_executor::PropertyExecutor;
+use crate::property_executor::{DecisionContext, PropertyExecutor};
 use crate::types::{Decider, Decision, ImplicationProofElement, PropertyInput, QuantifierResult};
 use crate::{DecideMixin, DeciderManager};
 use plasma_db::traits::kvs::KeyValueStore;
@@ -43,19 +43,21 @@ impl Decider for ForAllSuchThatDecider {
     fn decide<T: KeyValueStore>(
         decider: &PropertyExecutor<T>,
         inputs: &[PropertyInput],
+        context: &DecisionContext,
     ) -> Result<Decision, Error> {
         let quantifier = decider.get_variable(&inputs[0]).to_property();
         let placeholder = decider.get_variable(&inputs[1]).to_bytes();
         let property = decider.get_variable(&inputs[2]).to_property();
 
-        let quantifier_result: QuantifierResult = decider.get_all_quantified(&quantifier);
+        let quantifier_result: QuantifierResult =
+            decider.get_all_quantified_with_context(&quantifier, context)?;
 
         let mut any_undecided: bool = false;
         let mut false_decision: Decision = Decision::new(false, vec![]);
         let mut true_decisions: Vec<Decision> = vec![];
         for res in quantifier_result.get_results() {
             decider.set_variable(placeholder.clone(), res.clone());
-            let decision_result = property.decide(decider);
+            let decision_result = property.decide(decider, context);
             if let Ok(decision) = decision_result {
                 if !decision.get_outcome() {
                     false_decision = decision;
@@ -78,11 +80,10 @@ impl Decider for ForAllSuchThatDecider {
 
 #[cfg(test)]
 mod tests {
-    use super::ForAllSuchThatDecider;
     use crate::db::HashPreimageDb;
     use crate::deciders::preimage_exists_decider::Verifier;
     use crate::property_executor::PropertyExecutor;
-    use crate::types::{Decider, Decision, Integer, PropertyInput};
+    use crate::types::{Decision, Integer, PropertyInput};
     use crate::DeciderManager;
     use bytes::Bytes;
     use plasma_core::data_structure::Range;
@@ -109,7 +110,7 @@ mod tests {
                 .store_witness(Verifier::static_hash(&integer.into()), &integer.into())
                 .is_ok());
         }
-        let decided: Decision = ForAllSuchThatDecider::decide(&decider, &property.inputs).unwrap();
+        let decided: Decision = decider.decide(&property).unwrap();
         assert_eq!(decided.get_outcome(), true);
     }
 }
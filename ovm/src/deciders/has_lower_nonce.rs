@@ -1,5 +1,5 @@
 use crate::error::Error;
-use crate::property_executor::PropertyExecutor;
+use crate::property_executor::{DecisionContext, PropertyExecutor};
 use crate::types::{Decider, Decision, ImplicationProofElement, PropertyInput};
 use crate::DeciderManager;
 use plasma_db::traits::kvs::KeyValueStore;
@@ -22,6 +22,7 @@ impl Decider for HasLowerNonceDecider {
     fn decide<T: KeyValueStore>(
         decider: &PropertyExecutor<T>,
         inputs: &[PropertyInput],
+        _context: &DecisionContext,
     ) -> Result<Decision, Error> {
         let message = decider.get_variable(&inputs[0]).to_message();
         let nonce = decider.get_variable(&inputs[1]).to_integer();
@@ -1,10 +1,10 @@
 use crate::db::RangeAtBlockDb;
 use crate::error::{Error, ErrorKind};
-use crate::property_executor::PropertyExecutor;
-use crate::types::{Decider, Decision, ImplicationProofElement, PropertyInput};
+use crate::property_executor::{DecisionContext, PropertyExecutor};
+use crate::types::{
+    Decider, Decision, DoubleLayerTreeLeafExt, ImplicationProofElement, PropertyInput,
+};
 use crate::DeciderManager;
-use abi_utils::abi::Encodable;
-use bytes::Bytes;
 use merkle_interval_tree::{DoubleLayerTree, DoubleLayerTreeLeaf};
 use plasma_db::traits::kvs::KeyValueStore;
 
@@ -21,16 +21,16 @@ impl Decider for IncludedAtBlockDecider {
     fn decide<T: KeyValueStore>(
         decider: &PropertyExecutor<T>,
         inputs: &[PropertyInput],
+        _context: &DecisionContext,
     ) -> Result<Decision, Error> {
         let block_number = decider.get_variable(&inputs[0]).to_integer();
         let state_update = decider.get_variable(&inputs[1]).to_state_update();
         let db: RangeAtBlockDb<T> = RangeAtBlockDb::new(decider.get_range_db());
         let range_at_block_record = db.get_witness(block_number, state_update.get_range())?;
-        let leaf = DoubleLayerTreeLeaf {
-            data: Bytes::from(state_update.to_abi()),
-            end: state_update.get_range().get_end(),
-            address: state_update.get_deposit_contract_address(),
-        };
+        let leaf = DoubleLayerTreeLeaf::from_state_update(
+            &state_update,
+            state_update.get_deposit_contract_address(),
+        );
         let inclusion_bounds_result = DoubleLayerTree::verify(
             &leaf,
             range_at_block_record.inclusion_proof.clone(),
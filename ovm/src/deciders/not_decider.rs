@@ -1,9 +1,11 @@
 use crate::error::Error;
-use crate::property_executor::PropertyExecutor;
+use crate::property_executor::{DecisionContext, PropertyExecutor};
 use crate::types::{Decider, Decision, ImplicationProofElement, PropertyInput};
 use crate::{DecideMixin, DeciderManager};
 use plasma_db::traits::kvs::KeyValueStore;
 
+/// NotDecider decides the sub-property in inputs[0] and returns the negated outcome.
+/// The implication proof of the sub-property is appended as the witness of the negation.
 pub struct NotDecider {}
 
 impl NotDecider {
@@ -22,9 +24,10 @@ impl Decider for NotDecider {
     fn decide<T: KeyValueStore>(
         decider: &PropertyExecutor<T>,
         inputs: &[PropertyInput],
+        context: &DecisionContext,
     ) -> Result<Decision, Error> {
         let property = decider.get_variable(&inputs[0]).to_property();
-        let decision = property.decide(decider)?;
+        let decision = property.decide(decider, context)?;
 
         Ok(Decision::new(
             !decision.get_outcome(),
@@ -39,3 +42,35 @@ impl Decider for NotDecider {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::property_executor::PropertyExecutor;
+    use crate::types::{Decision, Integer, PropertyInput};
+    use crate::DeciderManager;
+    use plasma_db::impls::kvs::CoreDbMemoryImpl;
+
+    #[test]
+    fn test_decide_negation_of_false() {
+        let property = DeciderManager::not_decider(DeciderManager::equal_decider(vec![
+            PropertyInput::ConstantInteger(Integer(1)),
+            PropertyInput::ConstantInteger(Integer(2)),
+        ]));
+        let decider: PropertyExecutor<CoreDbMemoryImpl> = Default::default();
+        let decided: Decision = decider.decide(&property).unwrap();
+        assert_eq!(decided.get_outcome(), true);
+        assert_eq!(decided.get_implication_proof().len(), 1);
+    }
+
+    #[test]
+    fn test_decide_negation_of_true() {
+        let property = DeciderManager::not_decider(DeciderManager::equal_decider(vec![
+            PropertyInput::ConstantInteger(Integer(1)),
+            PropertyInput::ConstantInteger(Integer(1)),
+        ]));
+        let decider: PropertyExecutor<CoreDbMemoryImpl> = Default::default();
+        let decided: Decision = decider.decide(&property).unwrap();
+        assert_eq!(decided.get_outcome(), false);
+        assert_eq!(decided.get_implication_proof().len(), 2);
+    }
+}
@@ -1,7 +1,7 @@
 use crate::db::TransactionDb;
 use crate::deciders::signed_by_decider::Verifier;
 use crate::error::{Error, ErrorKind};
-use crate::property_executor::PropertyExecutor;
+use crate::property_executor::{DecisionContext, PropertyExecutor};
 use crate::types::{Decider, Decision, Integer, Property, PropertyInput, StateUpdate};
 use abi_utils::Decodable;
 use bytes::Bytes;
@@ -34,6 +34,7 @@ impl Decider for OwnershipDecider {
     fn decide<T: KeyValueStore>(
         decider: &PropertyExecutor<T>,
         inputs: &[PropertyInput],
+        _context: &DecisionContext,
     ) -> Result<Decision, Error> {
         let state_update = decider.get_variable(&inputs[0]).to_state_update();
         let owner = decider.get_variable(&inputs[1]).to_address();
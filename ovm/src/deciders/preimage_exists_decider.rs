@@ -1,6 +1,6 @@
 use crate::db::HashPreimageDb;
 use crate::error::{Error, ErrorKind};
-use crate::property_executor::PropertyExecutor;
+use crate::property_executor::{DecisionContext, PropertyExecutor};
 use crate::types::{Decider, Decision, ImplicationProofElement, PropertyInput};
 use crate::DeciderManager;
 use bytes::Bytes;
@@ -37,6 +37,7 @@ impl Decider for PreimageExistsDecider {
     fn decide<T: KeyValueStore>(
         decider: &PropertyExecutor<T>,
         inputs: &[PropertyInput],
+        _context: &DecisionContext,
     ) -> Result<Decision, Error> {
         let hash = decider.get_variable(&inputs[0]).to_h256();
         let key = hash;
@@ -1,12 +1,13 @@
 use crate::db::SignedByDb;
 use crate::error::{Error, ErrorKind};
-use crate::property_executor::PropertyExecutor;
+use crate::property_executor::{DecisionContext, PropertyExecutor};
 use crate::types::{Decider, Decision, ImplicationProofElement, PropertyInput};
 use crate::DeciderManager;
 use bytes::Bytes;
 use ethereum_types::{Address, H256};
 use ethsign::{SecretKey, Signature};
 use plasma_db::traits::kvs::KeyValueStore;
+use rayon::prelude::*;
 use tiny_keccak::Keccak;
 
 pub fn signature_to_bytes(signature: &Signature) -> Bytes {
@@ -49,6 +50,39 @@ impl Verifier {
     pub fn sign(key: &SecretKey, message: &Bytes) -> Bytes {
         signature_to_bytes(&key.sign(hash(message).as_bytes()).unwrap())
     }
+    /// Signs messages in order. Hashes of messages are computed in parallel.
+    pub fn sign_batch(key: &SecretKey, messages: &[Bytes]) -> Vec<Bytes> {
+        let hashes: Vec<H256> = messages.par_iter().map(hash).collect();
+        hashes
+            .iter()
+            .map(|h| signature_to_bytes(&key.sign(h.as_bytes()).unwrap()))
+            .collect()
+    }
+    /// Returns true if `signature` of `message` is made by `signer`.
+    /// Malformed signatures are treated as invalid instead of panicking.
+    pub fn verify(message: &Bytes, signature: &Bytes, signer: Address) -> bool {
+        if signature.len() != 65 {
+            return false;
+        }
+        match bytes_to_signature(signature).recover(hash(message).as_bytes()) {
+            Ok(public_key) => Address::from(public_key.address()) == signer,
+            Err(_) => false,
+        }
+    }
+    /// Verifies `(message, signature, expected_signer)` tuples in parallel.
+    /// Results are in the same order as inputs.
+    pub fn verify_batch(messages_and_signatures: &[(Bytes, Bytes, Address)]) -> Vec<bool> {
+        messages_and_signatures
+            .par_iter()
+            .map(|(message, signature, signer)| Self::verify(message, signature, *signer))
+            .collect()
+    }
+    /// Returns true only if all signatures are valid. Stops at the first invalid one.
+    pub fn verify_all(messages_and_signatures: &[(Bytes, Bytes, Address)]) -> bool {
+        messages_and_signatures
+            .iter()
+            .all(|(message, signature, signer)| Self::verify(message, signature, *signer))
+    }
 }
 
 pub struct SignedByDecider {}
@@ -63,6 +97,7 @@ impl Decider for SignedByDecider {
     fn decide<T: KeyValueStore>(
         decider: &PropertyExecutor<T>,
         inputs: &[PropertyInput],
+        _context: &DecisionContext,
     ) -> Result<Decision, Error> {
         let public_key = decider.get_variable(&inputs[0]).to_address();
         let message = decider.get_variable(&inputs[1]).to_bytes();
@@ -90,6 +125,7 @@ mod tests {
     use crate::types::{Decision, PropertyInput};
     use crate::DeciderManager;
     use bytes::Bytes;
+    use ethereum_types::Address;
     use ethsign::SecretKey;
     use plasma_db::impls::kvs::CoreDbMemoryImpl;
 
@@ -117,4 +153,41 @@ mod tests {
         let decided: Decision = decider.decide(&property).unwrap();
         assert_eq!(decided.get_outcome(), true);
     }
+
+    #[test]
+    fn test_sign_batch() {
+        let raw_key =
+            hex::decode("c87509a1c067bbde78beb793e6fa76530b6382a4c0241e5e4a9ec0a0f44dc0d3")
+                .unwrap();
+        let secret_key = SecretKey::from_raw(&raw_key).unwrap();
+        let messages = vec![Bytes::from("message1"), Bytes::from("message2")];
+        let signatures = Verifier::sign_batch(&secret_key, &messages);
+        assert_eq!(signatures.len(), 2);
+        for (message, signature) in messages.iter().zip(signatures.iter()) {
+            assert_eq!(signature, &Verifier::sign(&secret_key, message));
+        }
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let raw_key =
+            hex::decode("c87509a1c067bbde78beb793e6fa76530b6382a4c0241e5e4a9ec0a0f44dc0d3")
+                .unwrap();
+        let secret_key = SecretKey::from_raw(&raw_key).unwrap();
+        let signer: Address = secret_key.public().address().into();
+        let message = Bytes::from("message");
+        let signature = Verifier::sign(&secret_key, &message);
+        let inputs = vec![
+            (message.clone(), signature.clone(), signer),
+            (Bytes::from("other"), signature.clone(), signer),
+            (message.clone(), Bytes::from("short"), signer),
+            (message.clone(), signature, Address::zero()),
+        ];
+        assert_eq!(
+            Verifier::verify_batch(&inputs),
+            vec![true, false, false, false]
+        );
+        assert!(Verifier::verify_all(&inputs[..1]));
+        assert!(!Verifier::verify_all(&inputs));
+    }
 }
@@ -1,5 +1,5 @@
 use crate::error::{Error, ErrorKind};
-use crate::property_executor::PropertyExecutor;
+use crate::property_executor::{DecisionContext, PropertyExecutor};
 use crate::types::{Decider, Decision, ImplicationProofElement, PropertyInput, QuantifierResult};
 use crate::{DecideMixin, DeciderManager};
 use plasma_db::traits::kvs::KeyValueStore;
@@ -36,17 +36,19 @@ impl Decider for ThereExistsSuchThatDecider {
     fn decide<T: KeyValueStore>(
         decider: &PropertyExecutor<T>,
         inputs: &[PropertyInput],
+        context: &DecisionContext,
     ) -> Result<Decision, Error> {
         let quantifier = decider.get_variable(&inputs[0]).to_property();
         let placeholder = decider.get_variable(&inputs[1]).to_bytes();
         let property = decider.get_variable(&inputs[2]).to_property();
 
-        let quantifier_result: QuantifierResult = decider.get_all_quantified(&quantifier);
+        let quantifier_result: QuantifierResult =
+            decider.get_all_quantified_with_context(&quantifier, context)?;
 
         let mut true_decision: Decision = Decision::new(false, vec![]);
         for res in quantifier_result.get_results() {
             decider.set_variable(placeholder.clone(), res.clone());
-            let decision_result = property.decide(decider);
+            let decision_result = property.decide(decider, context);
             if let Ok(decision) = decision_result {
                 if decision.get_outcome() {
                     true_decision = decision;
@@ -0,0 +1,86 @@
+use crate::error::Error;
+use crate::property_executor::{DecisionContext, PropertyExecutor};
+use crate::types::{Decider, Decision, ImplicationProofElement, PropertyInput};
+use crate::DeciderManager;
+use plasma_db::traits::kvs::KeyValueStore;
+
+/// TimeDecider decides true if current block number is within `[start_block, end_block]`.
+pub struct TimeDecider {}
+
+impl Default for TimeDecider {
+    fn default() -> Self {
+        TimeDecider {}
+    }
+}
+
+impl Decider for TimeDecider {
+    fn decide<T: KeyValueStore>(
+        decider: &PropertyExecutor<T>,
+        inputs: &[PropertyInput],
+        context: &DecisionContext,
+    ) -> Result<Decision, Error> {
+        let start_block = decider.get_variable(&inputs[0]).to_integer();
+        let end_block = decider.get_variable(&inputs[1]).to_integer();
+        let current_block = context.get_block_number();
+        if start_block <= current_block && current_block <= end_block {
+            Ok(Decision::new(
+                true,
+                vec![ImplicationProofElement::new(
+                    DeciderManager::time_decider(inputs.to_vec()),
+                    None,
+                )],
+            ))
+        } else {
+            Ok(Decision::new(false, vec![]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::property_executor::PropertyExecutor;
+    use crate::types::{Integer, PropertyInput};
+    use crate::DeciderManager;
+    use plasma_db::impls::kvs::CoreDbMemoryImpl;
+
+    fn decide_at(current_block: u64) -> bool {
+        let property = DeciderManager::time_decider(vec![
+            PropertyInput::ConstantInteger(Integer::new(10)),
+            PropertyInput::ConstantInteger(Integer::new(20)),
+        ]);
+        let decider: PropertyExecutor<CoreDbMemoryImpl> = Default::default();
+        decider.set_current_block(Integer::new(current_block));
+        decider.decide(&property).unwrap().get_outcome()
+    }
+
+    #[test]
+    fn test_decide_before_window() {
+        assert_eq!(decide_at(9), false);
+    }
+
+    #[test]
+    fn test_decide_in_window() {
+        assert_eq!(decide_at(10), true);
+        assert_eq!(decide_at(15), true);
+        assert_eq!(decide_at(20), true);
+    }
+
+    #[test]
+    fn test_decide_after_window() {
+        assert_eq!(decide_at(21), false);
+    }
+
+    #[test]
+    fn test_with_block_number() {
+        let property = DeciderManager::time_decider(vec![
+            PropertyInput::ConstantInteger(Integer::new(10)),
+            PropertyInput::ConstantInteger(Integer::new(20)),
+        ]);
+        let mut decider =
+            PropertyExecutor::<CoreDbMemoryImpl>::default().with_block_number(Integer::new(15));
+        assert_eq!(decider.get_current_block(), Integer::new(15));
+        assert!(decider.decide(&property).unwrap().get_outcome());
+        decider.update_block_number(Integer::new(30));
+        assert!(!decider.decide(&property).unwrap().get_outcome());
+    }
+}
@@ -26,6 +26,32 @@ pub enum ErrorKind {
     Undecided,
     #[fail(display = "CannotDecide")]
     CannotDecide,
+    #[fail(display = "Storage Corruption")]
+    StorageCorruption,
+    #[fail(display = "Evaluation Timeout")]
+    EvaluationTimeout,
+    #[fail(display = "Property Too Deep")]
+    PropertyTooDeep,
+}
+
+#[derive(Fail, Debug, PartialEq)]
+pub enum StateUpdateError {
+    #[fail(display = "Field {} is missing", _0)]
+    MissingField(&'static str),
+    #[fail(display = "Block number must be greater than 0")]
+    InvalidBlockNumber,
+    #[fail(display = "Range is empty")]
+    EmptyRange,
+    #[fail(display = "Property is empty")]
+    EmptyProperty,
+}
+
+#[derive(Fail, Debug, PartialEq)]
+pub enum BuildError {
+    #[fail(display = "Field {} is missing", _0)]
+    MissingField(&'static str),
+    #[fail(display = "Range is empty")]
+    EmptyRange,
 }
 
 #[derive(Debug)]
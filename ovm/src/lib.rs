@@ -10,7 +10,7 @@ pub mod statements;
 pub mod types;
 pub mod utils;
 
-pub use self::property_executor::{DecideMixin, DeciderManager};
+pub use self::property_executor::{DecideMixin, DeciderManager, DecisionContext};
 
 #[cfg(test)]
 mod tests {
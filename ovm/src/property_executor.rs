@@ -1,29 +1,36 @@
+use crate::deciders::preimage_exists_decider::Verifier;
 use crate::deciders::{
-    AndDecider, ForAllSuchThatDecider, HasLowerNonceDecider, IncludedAtBlockDecider,
+    AndDecider, EqualDecider, ForAllSuchThatDecider, HasLowerNonceDecider, IncludedAtBlockDecider,
     IsDeprecatedDecider, NotDecider, OrDecider, OwnershipDecider, PreimageExistsDecider,
-    SignedByDecider, ThereExistsSuchThatDecider,
+    SignVerifier, SignedByDecider, ThereExistsSuchThatDecider, TimeDecider,
 };
-use crate::error::Error;
+use crate::error::{Error, ErrorKind};
 use crate::quantifiers::{
-    BlockRangeQuantifier, HashQuantifier, IntegerRangeQuantifier,
-    NonnegativeIntegerLessThanQuantifier, PropertyQuantifier, SignedByQuantifier,
-    StateUpdateQuantifier, TxQuantifier,
+    BlockRangeQuantifier, HashQuantifier, HashedPreimageQuantifier, IntegerRangeQuantifier,
+    NonnegativeIntegerLessThanQuantifier, NullQuantifier, PropertyQuantifier, SignedByQuantifier,
+    StateObjectQuantifier, StateUpdateQuantifier, TxQuantifier,
 };
 use crate::types::{
-    Decider, Decision, Property, PropertyInput, QuantifierResult, QuantifierResultItem,
+    Decider, Decision, ImplicationProofElement, Integer, Property, PropertyInput, QuantifierResult,
+    QuantifierResultItem,
 };
 use bytes::Bytes;
 use ethereum_types::Address;
 use plasma_db::traits::db::DatabaseTrait;
-use plasma_db::traits::kvs::KeyValueStore;
+use plasma_db::traits::kvs::{BaseDbKey, KeyValueStore};
 use plasma_db::RangeDbImpl;
+use std::cell::Cell;
 use std::collections::HashMap;
 use std::sync::RwLock;
+use std::time::{Duration, Instant};
 
 fn get_address(address: &str) -> Address {
     Address::from_slice(&hex::decode(address).unwrap())
 }
 
+/// Number of deciders at the head of `DECIDER_LIST`. Quantifiers follow them.
+pub const DECIDER_COUNT: usize = 13;
+
 lazy_static! {
     static ref DECIDER_LIST: Vec<Address> = {
         let mut list = vec![];
@@ -125,6 +132,12 @@ impl DeciderManager {
     pub fn there_exists_such_that(inputs: Vec<PropertyInput>) -> Property {
         Property::new(Self::get_decider_address(10), inputs)
     }
+    pub fn time_decider(inputs: Vec<PropertyInput>) -> Property {
+        Property::new(Self::get_decider_address(11), inputs)
+    }
+    pub fn equal_decider(inputs: Vec<PropertyInput>) -> Property {
+        Property::new(Self::get_decider_address(12), inputs)
+    }
     pub fn q_range(inputs: Vec<PropertyInput>) -> Property {
         Property::new(Self::get_decider_address(20), inputs)
     }
@@ -149,19 +162,72 @@ impl DeciderManager {
     pub fn q_state_update(inputs: Vec<PropertyInput>) -> Property {
         Property::new(Self::get_decider_address(27), inputs)
     }
+    pub fn q_hashed_preimage(inputs: Vec<PropertyInput>) -> Property {
+        Property::new(Self::get_decider_address(28), inputs)
+    }
+    pub fn q_state_object(inputs: Vec<PropertyInput>) -> Property {
+        Property::new(Self::get_decider_address(29), inputs)
+    }
+    /// Quantifier which always returns no results.
+    pub fn q_null(inputs: Vec<PropertyInput>) -> Property {
+        Property::new(Self::get_decider_address(19), inputs)
+    }
 }
 
 /// Mixin for adding decide method to Property
 pub trait DecideMixin<KVS: KeyValueStore> {
-    fn decide(&self, decider: &PropertyExecutor<KVS>) -> Result<Decision, Error>;
+    fn decide(
+        &self,
+        decider: &PropertyExecutor<KVS>,
+        context: &DecisionContext,
+    ) -> Result<Decision, Error>;
 }
 
 impl<KVS> DecideMixin<KVS> for Property
 where
     KVS: KeyValueStore,
 {
-    fn decide(&self, decider: &PropertyExecutor<KVS>) -> Result<Decision, Error> {
-        decider.decide(self)
+    fn decide(
+        &self,
+        decider: &PropertyExecutor<KVS>,
+        context: &DecisionContext,
+    ) -> Result<Decision, Error> {
+        decider.decide_with_context(self, context)
+    }
+}
+
+/// Default maximum nesting depth of properties which can be decided.
+pub const DEFAULT_MAX_PROPERTY_DEPTH: usize = 10;
+
+/// State of a decision which is passed down to its nested decisions.
+pub struct DecisionContext {
+    /// Block number at the start of the decision.
+    block_number: Integer,
+    deadline: Option<Instant>,
+    max_depth: usize,
+    /// Nesting depth of the property being decided.
+    depth: Cell<usize>,
+    /// True if a nested decision exceeded the maximum depth.
+    too_deep: Cell<bool>,
+}
+
+impl DecisionContext {
+    pub fn new(block_number: Integer, deadline: Option<Instant>, max_depth: usize) -> Self {
+        Self {
+            block_number,
+            deadline,
+            max_depth,
+            depth: Cell::new(0),
+            too_deep: Cell::new(false),
+        }
+    }
+    /// Returns block number used by time dependent deciders.
+    pub fn get_block_number(&self) -> Integer {
+        self.block_number
+    }
+    fn is_expired(&self) -> bool {
+        self.deadline
+            .map_or(false, |deadline| Instant::now() > deadline)
     }
 }
 
@@ -170,6 +236,11 @@ pub struct PropertyExecutor<KVS: KeyValueStore> {
     db: KVS,
     range_db: RangeDbImpl<KVS>,
     variables: RwLock<HashMap<Bytes, QuantifierResultItem>>,
+    current_block: RwLock<Integer>,
+    /// Timeout applied to every decision started by `decide`.
+    decision_timeout: Option<Duration>,
+    /// Decisions nested deeper than this are rejected.
+    max_property_depth: usize,
 }
 
 impl<KVS> Default for PropertyExecutor<KVS>
@@ -181,6 +252,9 @@ where
             db: KVS::open("kvs"),
             range_db: RangeDbImpl::from(KVS::open("range")),
             variables: RwLock::new(Default::default()),
+            current_block: RwLock::new(Integer::new(0)),
+            decision_timeout: None,
+            max_property_depth: DEFAULT_MAX_PROPERTY_DEPTH,
         }
     }
 }
@@ -195,6 +269,20 @@ where
     pub fn get_range_db(&self) -> &RangeDbImpl<KVS> {
         &self.range_db
     }
+    pub fn with_block_number(mut self, block_number: Integer) -> Self {
+        self.update_block_number(block_number);
+        self
+    }
+    pub fn update_block_number(&mut self, block_number: Integer) {
+        *self.current_block.get_mut().unwrap() = block_number;
+    }
+    /// Returns current block number. Decisions use the block number at their start.
+    pub fn get_current_block(&self) -> Integer {
+        *self.current_block.read().unwrap()
+    }
+    pub fn set_current_block(&self, block_number: Integer) {
+        *self.current_block.write().unwrap() = block_number;
+    }
     pub fn set_variable(&self, placeholder: Bytes, result: QuantifierResultItem) {
         self.variables.write().unwrap().insert(placeholder, result);
     }
@@ -223,54 +311,543 @@ where
             }
         }
     }
+    /// Clears quantified variables. Stored witnesses are kept.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn reset_cache(&mut self) {
+        self.variables.get_mut().unwrap().clear();
+    }
+    /// Clears the cache and deletes all entries of the databases.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn reset_all(&mut self) -> Result<(), Error> {
+        self.reset_cache();
+        for db in [&self.db, self.range_db.get_db()].iter() {
+            for kv in db.iter_all(&BaseDbKey::new(vec![]), Box::new(|_k, _v| true)) {
+                db.del(kv.get_key())?;
+            }
+        }
+        Ok(())
+    }
+    /// Sets timeout applied to every decision started by `decide`.
+    pub fn set_decision_timeout(&mut self, decision_timeout: Option<Duration>) {
+        self.decision_timeout = decision_timeout;
+    }
+    /// Sets maximum nesting depth of properties which can be decided.
+    pub fn set_max_property_depth(&mut self, max_property_depth: usize) {
+        self.max_property_depth = max_property_depth;
+    }
+    /// Decides property. Nested decisions share the block number of the outermost one.
     pub fn decide(&self, property: &Property) -> Result<Decision, Error> {
+        let deadline = self
+            .decision_timeout
+            .map(|decision_timeout| Instant::now() + decision_timeout);
+        self.decide_until(property, deadline)
+    }
+    /// Decides property and returns `EvaluationTimeout` error if `deadline` is exceeded.
+    /// The deadline is checked at each nested decision.
+    pub fn decide_with_deadline(
+        &self,
+        property: &Property,
+        deadline: Instant,
+    ) -> Result<Decision, Error> {
+        self.decide_until(property, Some(deadline))
+    }
+    /// Nested decisions share the context of the outermost one.
+    fn decide_until(
+        &self,
+        property: &Property,
+        deadline: Option<Instant>,
+    ) -> Result<Decision, Error> {
+        let context = self.new_context(deadline);
+        let result = self.decide_with_context(property, &context);
+        // some deciders treat errors of nested decisions as false or undecided,
+        // so the outcome can't be trusted once any of them exceeded the limits.
+        // the deadline is monotonic, so it is also exceeded here if a nested one was.
+        if context.too_deep.get() {
+            return Err(Error::from(ErrorKind::PropertyTooDeep));
+        }
+        if context.is_expired() {
+            return Err(Error::from(ErrorKind::EvaluationTimeout));
+        }
+        result
+    }
+    fn new_context(&self, deadline: Option<Instant>) -> DecisionContext {
+        DecisionContext::new(self.get_current_block(), deadline, self.max_property_depth)
+    }
+    /// Decides property nested in the decision of `context`.
+    /// Returns `EvaluationTimeout` error if the deadline is exceeded and `PropertyTooDeep`
+    /// error if the nesting depth exceeds the maximum.
+    fn decide_with_context(
+        &self,
+        property: &Property,
+        context: &DecisionContext,
+    ) -> Result<Decision, Error> {
+        if context.is_expired() {
+            return Err(Error::from(ErrorKind::EvaluationTimeout));
+        }
+        let depth = context.depth.get() + 1;
+        if depth > context.max_depth {
+            context.too_deep.set(true);
+            return Err(Error::from(ErrorKind::PropertyTooDeep));
+        }
+        context.depth.set(depth);
+        let result = self.decide_property(property, context);
+        context.depth.set(depth - 1);
+        result
+    }
+    fn decide_property(
+        &self,
+        property: &Property,
+        context: &DecisionContext,
+    ) -> Result<Decision, Error> {
         let decider_id = property.decider;
         if decider_id == DECIDER_LIST[0] {
-            AndDecider::decide(self, &property.inputs)
+            AndDecider::decide(self, &property.inputs, context)
         } else if decider_id == DECIDER_LIST[1] {
-            NotDecider::decide(self, &property.inputs)
+            NotDecider::decide(self, &property.inputs, context)
         } else if decider_id == DECIDER_LIST[2] {
-            PreimageExistsDecider::decide(self, &property.inputs)
+            PreimageExistsDecider::decide(self, &property.inputs, context)
         } else if decider_id == DECIDER_LIST[3] {
-            ForAllSuchThatDecider::decide(self, &property.inputs)
+            ForAllSuchThatDecider::decide(self, &property.inputs, context)
         } else if decider_id == DECIDER_LIST[4] {
-            OrDecider::decide(self, &property.inputs)
+            OrDecider::decide(self, &property.inputs, context)
         } else if decider_id == DECIDER_LIST[5] {
-            SignedByDecider::decide(self, &property.inputs)
+            SignedByDecider::decide(self, &property.inputs, context)
         } else if decider_id == DECIDER_LIST[6] {
-            HasLowerNonceDecider::decide(self, &property.inputs)
+            HasLowerNonceDecider::decide(self, &property.inputs, context)
         } else if decider_id == DECIDER_LIST[7] {
-            IncludedAtBlockDecider::decide(self, &property.inputs)
+            IncludedAtBlockDecider::decide(self, &property.inputs, context)
         } else if decider_id == DECIDER_LIST[8] {
-            IsDeprecatedDecider::decide(self, &property.inputs)
+            IsDeprecatedDecider::decide(self, &property.inputs, context)
         } else if decider_id == DECIDER_LIST[9] {
-            OwnershipDecider::decide(self, &property.inputs)
+            OwnershipDecider::decide(self, &property.inputs, context)
         } else if decider_id == DECIDER_LIST[10] {
-            ThereExistsSuchThatDecider::decide(self, &property.inputs)
+            ThereExistsSuchThatDecider::decide(self, &property.inputs, context)
+        } else if decider_id == DECIDER_LIST[11] {
+            TimeDecider::decide(self, &property.inputs, context)
+        } else if decider_id == DECIDER_LIST[12] {
+            EqualDecider::decide(self, &property.inputs, context)
         } else {
             panic!("unknown decider")
         }
     }
-    pub fn get_all_quantified(&self, property: &Property) -> QuantifierResult {
+    /// Returns true if `proof` shows that `property` is true.
+    /// Each step is checked against the semantics of its parent decider and witnesses of
+    /// preimages and signatures are verified. Atomic decisions which carry no witness,
+    /// e.g. false outcomes, are decided locally.
+    pub fn verify_implication_proof(
+        &self,
+        property: &Property,
+        proof: &[ImplicationProofElement],
+    ) -> bool {
+        let context = self.new_context(None);
+        self.consume_implication_proof(property, true, proof, &context)
+            .map_or(false, |rest| rest.is_empty())
+    }
+    /// Consumes the proof that `property` decides to `outcome` from the head of `proof`
+    /// and returns the rest, or None if the head isn't such a proof.
+    fn consume_implication_proof<'a>(
+        &self,
+        property: &Property,
+        outcome: bool,
+        proof: &'a [ImplicationProofElement],
+        context: &DecisionContext,
+    ) -> Option<&'a [ImplicationProofElement]> {
+        let decider_id = property.decider;
+        if !DECIDER_LIST[..DECIDER_COUNT].contains(&decider_id) {
+            return None;
+        }
+        let sub_property = |i: usize| {
+            property
+                .inputs
+                .get(i)
+                .map(|input| self.get_variable(input).to_property())
+        };
+        if decider_id == DECIDER_LIST[0] {
+            // and has no element of its own, the proofs of both sides are concatenated
+            let (left, right) = (sub_property(0)?, sub_property(1)?);
+            if outcome {
+                let rest = self.consume_implication_proof(&left, true, proof, context)?;
+                self.consume_implication_proof(&right, true, rest, context)
+            } else {
+                self.consume_implication_proof(&left, false, proof, context)
+                    .or_else(|| self.consume_implication_proof(&right, false, proof, context))
+            }
+        } else if decider_id == DECIDER_LIST[4] {
+            let (left, right) = (sub_property(0)?, sub_property(1)?);
+            if outcome {
+                self.consume_implication_proof(&left, true, proof, context)
+                    .or_else(|| self.consume_implication_proof(&right, true, proof, context))
+            } else {
+                let rest = self.consume_implication_proof(&left, false, proof, context)?;
+                self.consume_implication_proof(&right, false, rest, context)
+            }
+        } else if decider_id == DECIDER_LIST[1] {
+            let inner = sub_property(0)?;
+            let rest = Self::consume_element(&DeciderManager::not_decider(inner.clone()), proof)?;
+            self.consume_implication_proof(&inner, !outcome, rest, context)
+        } else if decider_id == DECIDER_LIST[3] || decider_id == DECIDER_LIST[10] {
+            let is_for_all = decider_id == DECIDER_LIST[3];
+            let rest = Self::consume_element(property, proof)?;
+            let quantifier = sub_property(0)?;
+            let placeholder = self.get_variable(property.inputs.get(1)?).to_bytes();
+            let inner = sub_property(2)?;
+            let quantified = self
+                .get_all_quantified_with_context(&quantifier, context)
+                .ok()?;
+            if is_for_all == outcome {
+                // every quantified result must decide to the outcome
+                if !quantified.get_all_results_quantified() {
+                    return None;
+                }
+                let mut rest = rest;
+                for result in quantified.get_results() {
+                    self.set_variable(placeholder.clone(), result.clone());
+                    rest = if is_for_all {
+                        self.consume_implication_proof(&inner, true, rest, context)?
+                    } else {
+                        // false decisions of there exists don't carry proofs of the results
+                        match self.decide_with_context(&inner, context) {
+                            Ok(decision) if !decision.get_outcome() => rest,
+                            _ => return None,
+                        }
+                    };
+                }
+                Some(rest)
+            } else {
+                // some quantified result must decide to the outcome
+                quantified.get_results().iter().find_map(|result| {
+                    self.set_variable(placeholder.clone(), result.clone());
+                    self.consume_implication_proof(&inner, outcome, rest, context)
+                })
+            }
+        } else {
+            self.consume_atomic_proof(property, outcome, proof, context)
+        }
+    }
+    /// Atomic deciders put the property itself with the witness only when it's true,
+    /// except for is deprecated and ownership deciders which never put it.
+    fn consume_atomic_proof<'a>(
+        &self,
+        property: &Property,
+        outcome: bool,
+        proof: &'a [ImplicationProofElement],
+        context: &DecisionContext,
+    ) -> Option<&'a [ImplicationProofElement]> {
+        let decider_id = property.decider;
+        if !outcome || decider_id == DECIDER_LIST[8] || decider_id == DECIDER_LIST[9] {
+            return match self.decide_with_context(property, context) {
+                Ok(decision) if decision.get_outcome() == outcome => Some(proof),
+                _ => None,
+            };
+        }
+        let rest = Self::consume_element(property, proof)?;
+        let witness = proof[0].get_implication_witness();
+        if decider_id == DECIDER_LIST[2] {
+            let hash = self.get_variable(property.inputs.get(0)?).to_h256();
+            match witness {
+                Some(preimage) if Verifier::hash(preimage) == hash => Some(rest),
+                _ => None,
+            }
+        } else if decider_id == DECIDER_LIST[5] {
+            let public_key = self.get_variable(property.inputs.get(0)?).to_address();
+            let message = self.get_variable(property.inputs.get(1)?).to_bytes();
+            match witness {
+                Some(signature) if SignVerifier::verify(&message, signature, public_key) => {
+                    Some(rest)
+                }
+                _ => None,
+            }
+        } else {
+            match self.decide_with_context(property, context) {
+                Ok(decision) if decision.get_outcome() => Some(rest),
+                _ => None,
+            }
+        }
+    }
+    /// Consumes the head of `proof` if it is the element of `implication`.
+    fn consume_element<'a>(
+        implication: &Property,
+        proof: &'a [ImplicationProofElement],
+    ) -> Option<&'a [ImplicationProofElement]> {
+        match proof.first() {
+            Some(element) if element.get_implication() == implication => Some(&proof[1..]),
+            _ => None,
+        }
+    }
+    /// Returns error if the quantifier fails to read stored witnesses.
+    pub fn get_all_quantified(&self, property: &Property) -> Result<QuantifierResult, Error> {
+        self.get_all_quantified_with_context(property, &self.new_context(None))
+    }
+    /// Quantifies in the decision of `context`.
+    pub fn get_all_quantified_with_context(
+        &self,
+        property: &Property,
+        context: &DecisionContext,
+    ) -> Result<QuantifierResult, Error> {
         let decider_id = property.decider;
         if decider_id == DECIDER_LIST[20] {
-            IntegerRangeQuantifier::get_all_quantified(self, &property.inputs)
+            Ok(IntegerRangeQuantifier::get_all_quantified(
+                self,
+                &property.inputs,
+            ))
         } else if decider_id == DECIDER_LIST[21] {
-            NonnegativeIntegerLessThanQuantifier::get_all_quantified(self, &property.inputs)
+            Ok(NonnegativeIntegerLessThanQuantifier::get_all_quantified(
+                self,
+                &property.inputs,
+            ))
         } else if decider_id == DECIDER_LIST[22] {
             BlockRangeQuantifier::get_all_quantified(self, &property.inputs)
         } else if decider_id == DECIDER_LIST[23] {
-            SignedByQuantifier::get_all_quantified(self, &property.inputs)
+            Ok(SignedByQuantifier::get_all_quantified(
+                self,
+                &property.inputs,
+            ))
         } else if decider_id == DECIDER_LIST[24] {
-            HashQuantifier::get_all_quantified(self, &property.inputs)
+            Ok(HashQuantifier::get_all_quantified(self, &property.inputs))
         } else if decider_id == DECIDER_LIST[25] {
-            TxQuantifier::get_all_quantified(self, &property.inputs)
+            Ok(TxQuantifier::get_all_quantified(self, &property.inputs))
         } else if decider_id == DECIDER_LIST[26] {
-            PropertyQuantifier::get_all_quantified(self, &property.inputs)
+            Ok(PropertyQuantifier::get_all_quantified(
+                self,
+                &property.inputs,
+            ))
         } else if decider_id == DECIDER_LIST[27] {
-            StateUpdateQuantifier::get_all_quantified(self, &property.inputs)
+            Ok(StateUpdateQuantifier::get_all_quantified(
+                self,
+                &property.inputs,
+            ))
+        } else if decider_id == DECIDER_LIST[28] {
+            HashedPreimageQuantifier::get_all_quantified(self, &property.inputs)
+        } else if decider_id == DECIDER_LIST[29] {
+            StateObjectQuantifier::get_all_quantified(self, &property.inputs, context)
+        } else if decider_id == DECIDER_LIST[19] {
+            Ok(NullQuantifier::get_all_quantified(self, &property.inputs))
         } else {
             panic!("unknown quantifier")
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{PropertyExecutor, DEFAULT_MAX_PROPERTY_DEPTH};
+    use crate::db::HashPreimageDb;
+    use crate::deciders::preimage_exists_decider::Verifier;
+    use crate::error::ErrorKind;
+    use crate::types::{ImplicationProofElement, Integer, PropertyInput, QuantifierResultItem};
+    use crate::DeciderManager;
+    use bytes::Bytes;
+    use plasma_core::data_structure::Range;
+    use plasma_db::impls::kvs::CoreDbMemoryImpl;
+    use plasma_db::traits::kvs::{BaseDbKey, KeyValueStore};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_reset_cache_and_all() {
+        let preimage = Bytes::from("left");
+        let hash = Verifier::static_hash(&preimage);
+        let property =
+            DeciderManager::preimage_exists_decider(vec![PropertyInput::ConstantH256(hash)]);
+        let mut decider: PropertyExecutor<CoreDbMemoryImpl> = Default::default();
+        assert!(HashPreimageDb::new(decider.get_db())
+            .store_witness(hash, &preimage)
+            .is_ok());
+        decider.set_variable(Bytes::from("v"), QuantifierResultItem::Integer(Integer(1)));
+        decider.reset_cache();
+        assert!(decider.variables.read().unwrap().is_empty());
+        assert!(decider.decide(&property).unwrap().get_outcome());
+        assert!(decider.reset_all().is_ok());
+        assert!(decider
+            .get_db()
+            .iter_all(&BaseDbKey::new(vec![]), Box::new(|_k, _v| true))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_verify_implication_proof() {
+        let preimage = Bytes::from("left");
+        let hash = Verifier::static_hash(&preimage);
+        let property = DeciderManager::not_decider(DeciderManager::not_decider(
+            DeciderManager::preimage_exists_decider(vec![PropertyInput::ConstantH256(hash)]),
+        ));
+        let decider: PropertyExecutor<CoreDbMemoryImpl> = Default::default();
+        let db = HashPreimageDb::new(decider.get_db());
+        assert!(db.store_witness(hash, &preimage).is_ok());
+        let decision = decider.decide(&property).unwrap();
+        let proof = decision.get_implication_proof();
+        assert!(decider.verify_implication_proof(&property, proof));
+        assert!(!decider.verify_implication_proof(&property, &[]));
+        assert!(!decider.verify_implication_proof(&property, &proof[1..]));
+        let mut tampered = proof.clone();
+        tampered[2] = ImplicationProofElement::new(
+            tampered[2].get_implication().clone(),
+            Some(Bytes::from("right")),
+        );
+        assert!(!decider.verify_implication_proof(&property, &tampered));
+    }
+
+    #[test]
+    fn test_verify_implication_proof_of_compound_properties() {
+        let preimage = Bytes::from("left");
+        let hash = Verifier::static_hash(&preimage);
+        let preimage_exists =
+            DeciderManager::preimage_exists_decider(vec![PropertyInput::ConstantH256(hash)]);
+        let not_equal = DeciderManager::not_decider(DeciderManager::equal_decider(vec![
+            PropertyInput::Placeholder(Bytes::from("n")),
+            PropertyInput::ConstantInteger(Integer::new(5)),
+        ]));
+        let for_all = DeciderManager::for_all_such_that_decider(
+            DeciderManager::q_range(vec![PropertyInput::ConstantRange(Range::new(0, 3))]),
+            Bytes::from("n"),
+            not_equal,
+        );
+        let and = DeciderManager::and_decider(preimage_exists.clone(), for_all.clone());
+        let or = DeciderManager::or_decider(for_all.clone(), preimage_exists.clone());
+        let decider: PropertyExecutor<CoreDbMemoryImpl> = Default::default();
+        let db = HashPreimageDb::new(decider.get_db());
+        assert!(db.store_witness(hash, &preimage).is_ok());
+        for property in [and, or, for_all].iter() {
+            let decision = decider.decide(property).unwrap();
+            assert!(decision.get_outcome());
+            assert!(decider.verify_implication_proof(property, decision.get_implication_proof()));
+            // the property itself doesn't prove anything
+            assert!(!decider.verify_implication_proof(
+                property,
+                &[ImplicationProofElement::new(property.clone(), None)]
+            ));
+        }
+        // proof of the other property doesn't prove this one
+        let equal = DeciderManager::equal_decider(vec![
+            PropertyInput::ConstantInteger(Integer::new(1)),
+            PropertyInput::ConstantInteger(Integer::new(1)),
+        ]);
+        let not_equal = DeciderManager::not_decider(equal.clone());
+        let proof = decider
+            .decide(&equal)
+            .unwrap()
+            .get_implication_proof()
+            .clone();
+        assert!(decider.verify_implication_proof(&equal, &proof));
+        assert!(!decider.verify_implication_proof(
+            &not_equal,
+            &[ImplicationProofElement::new(not_equal.clone(), None)]
+        ));
+    }
+
+    #[test]
+    fn test_decide_with_deadline() {
+        let decider: PropertyExecutor<CoreDbMemoryImpl> = Default::default();
+        let property = DeciderManager::equal_decider(vec![
+            PropertyInput::ConstantInteger(Integer::new(1)),
+            PropertyInput::ConstantInteger(Integer::new(1)),
+        ]);
+        let decision = decider
+            .decide_with_deadline(&property, Instant::now() + Duration::from_secs(10))
+            .unwrap();
+        assert!(decision.get_outcome());
+        let result =
+            decider.decide_with_deadline(&property, Instant::now() - Duration::from_millis(1));
+        match result {
+            Err(e) => match e.kind() {
+                ErrorKind::EvaluationTimeout => {}
+                _ => panic!("unexpected error kind"),
+            },
+            Ok(_) => panic!("decision must time out"),
+        }
+    }
+
+    #[test]
+    fn test_nested_timeout_is_not_false() {
+        let decider: PropertyExecutor<CoreDbMemoryImpl> = Default::default();
+        // there exists decider treats errors of nested decisions as false
+        let property = DeciderManager::there_exists_such_that(vec![
+            PropertyInput::ConstantProperty(DeciderManager::q_range(vec![
+                PropertyInput::ConstantRange(Range::new(0, 100_000)),
+            ])),
+            PropertyInput::ConstantBytes(Bytes::from("n")),
+            PropertyInput::ConstantProperty(DeciderManager::equal_decider(vec![
+                PropertyInput::Placeholder(Bytes::from("n")),
+                PropertyInput::ConstantInteger(Integer::new(100_000)),
+            ])),
+        ]);
+        let result =
+            decider.decide_with_deadline(&property, Instant::now() + Duration::from_millis(1));
+        match result {
+            Err(e) => match e.kind() {
+                ErrorKind::EvaluationTimeout => {}
+                _ => panic!("unexpected error kind"),
+            },
+            Ok(_) => panic!("decision must time out"),
+        }
+    }
+
+    #[test]
+    fn test_decide_too_deep_property() {
+        let mut decider: PropertyExecutor<CoreDbMemoryImpl> = Default::default();
+        let mut property = DeciderManager::equal_decider(vec![
+            PropertyInput::ConstantInteger(Integer::new(1)),
+            PropertyInput::ConstantInteger(Integer::new(1)),
+        ]);
+        for _ in 0..DEFAULT_MAX_PROPERTY_DEPTH {
+            property = DeciderManager::not_decider(property);
+        }
+        match decider.decide_with_deadline(&property, Instant::now() + Duration::from_secs(10)) {
+            Err(e) => match e.kind() {
+                ErrorKind::PropertyTooDeep => {}
+                _ => panic!("unexpected error kind"),
+            },
+            Ok(_) => panic!("too deep property must be rejected"),
+        }
+        decider.set_max_property_depth(DEFAULT_MAX_PROPERTY_DEPTH + 1);
+        assert!(decider
+            .decide_with_deadline(&property, Instant::now() + Duration::from_secs(10))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_nested_too_deep_is_not_false() {
+        let decider: PropertyExecutor<CoreDbMemoryImpl> = Default::default();
+        let equal = DeciderManager::equal_decider(vec![
+            PropertyInput::ConstantInteger(Integer::new(1)),
+            PropertyInput::ConstantInteger(Integer::new(1)),
+        ]);
+        let mut too_deep = equal.clone();
+        for _ in 0..DEFAULT_MAX_PROPERTY_DEPTH {
+            too_deep = DeciderManager::not_decider(too_deep);
+        }
+        // or decider decides true if the other side is true
+        let property = DeciderManager::or_decider(too_deep, equal);
+        match decider.decide(&property) {
+            Err(e) => match e.kind() {
+                ErrorKind::PropertyTooDeep => {}
+                _ => panic!("unexpected error kind"),
+            },
+            Ok(_) => panic!("too deep property must be rejected"),
+        }
+    }
+
+    #[test]
+    fn test_concurrent_decisions_have_own_deadlines() {
+        let decider: Arc<PropertyExecutor<CoreDbMemoryImpl>> = Arc::new(Default::default());
+        let property = DeciderManager::not_decider(DeciderManager::equal_decider(vec![
+            PropertyInput::ConstantInteger(Integer::new(1)),
+            PropertyInput::ConstantInteger(Integer::new(2)),
+        ]));
+        let expired = {
+            let decider = decider.clone();
+            let property = property.clone();
+            thread::spawn(move || {
+                for _ in 0..1000 {
+                    let deadline = Instant::now() - Duration::from_millis(1);
+                    assert!(decider.decide_with_deadline(&property, deadline).is_err());
+                }
+            })
+        };
+        for _ in 0..1000 {
+            assert!(decider.decide(&property).unwrap().get_outcome());
+        }
+        expired.join().unwrap();
+    }
+}
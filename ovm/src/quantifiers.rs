@@ -1,15 +1,21 @@
 pub mod block_range_quantifier;
 pub mod hash_quantifier;
+pub mod hashed_preimage_quantifier;
 pub mod integer_quantifiers;
+pub mod null_quantifier;
 pub mod property_quantifier;
 pub mod signed_by_quantifier;
+pub mod state_object_quantifier;
 pub mod state_update_quantifier;
 pub mod tx_quantifier;
 
 pub use self::block_range_quantifier::BlockRangeQuantifier;
 pub use self::hash_quantifier::HashQuantifier;
+pub use self::hashed_preimage_quantifier::HashedPreimageQuantifier;
 pub use self::integer_quantifiers::{IntegerRangeQuantifier, NonnegativeIntegerLessThanQuantifier};
+pub use self::null_quantifier::NullQuantifier;
 pub use self::property_quantifier::PropertyQuantifier;
 pub use self::signed_by_quantifier::SignedByQuantifier;
+pub use self::state_object_quantifier::StateObjectQuantifier;
 pub use self::state_update_quantifier::StateUpdateQuantifier;
 pub use self::tx_quantifier::TxQuantifier;
@@ -1,12 +1,18 @@
 use crate::db::RangeAtBlockRecord;
+use crate::error::{Error, ErrorKind};
 use crate::property_executor::PropertyExecutor;
-use crate::types::{PlasmaDataBlock, PropertyInput, QuantifierResult, QuantifierResultItem};
+use crate::types::{
+    PlasmaDataBlock, PropertyInput, QuantifierResult, QuantifierResultItem, StateUpdate,
+};
 use abi_utils::Decodable;
 use bytes::Bytes;
 use ethereum_types::H256;
+use failure::Fail;
 use merkle_interval_tree::{DoubleLayerTree, DoubleLayerTreeLeaf};
+use plasma_core::data_structure::Range;
 use plasma_db::traits::kvs::KeyValueStore;
 use plasma_db::traits::rangestore::RangeStore;
+use std::convert::TryFrom;
 
 pub struct BlockRangeQuantifier {}
 
@@ -25,10 +31,11 @@ impl BlockRangeQuantifier {
         };
         DoubleLayerTree::verify(&leaf, inclusion_proof.clone(), plasma_data_block.get_root())
     }
+    /// Returns error if a stored record can't be decoded.
     pub fn get_all_quantified<KVS>(
         decider: &PropertyExecutor<KVS>,
         inputs: &[PropertyInput],
-    ) -> QuantifierResult
+    ) -> Result<QuantifierResult, Error>
     where
         KVS: KeyValueStore,
     {
@@ -38,33 +45,102 @@ impl BlockRangeQuantifier {
             .get_range_db()
             .bucket(&Bytes::from("range_at_block"))
             .bucket(&block_number.into())
-            .get(range.get_start(), range.get_end())
-            .unwrap();
+            .get(range.get_start(), range.get_end())?;
         let sum = result
             .iter()
-            .filter_map(|r| r.get_intersection(range.get_start(), range.get_end()))
-            .fold(0, |acc, r| acc + r.get_end() - r.get_start());
+            .map(|r| Range::new(r.get_start(), r.get_end()).clamp(range))
+            .fold(0, |acc, r| acc + r.get_amount());
         let mut full_range_included: bool = sum == (range.get_end() - range.get_start());
-        let plasma_data_blocks: Vec<PlasmaDataBlock> = result
+        let records = result
             .iter()
-            .map(|r| RangeAtBlockRecord::from_abi(r.get_value()).unwrap())
-            .filter_map(move |record| {
-                if record.plasma_data_block.get_is_included() {
-                    Some(record.plasma_data_block.clone())
-                } else {
-                    if !Self::verify_exclusion(&record.plasma_data_block, &record.inclusion_proof) {
-                        full_range_included = false
-                    }
-                    None
-                }
-            })
-            .collect();
-        QuantifierResult::new(
-            plasma_data_blocks
-                .iter()
-                .map(|p| QuantifierResultItem::StateUpdate(p.clone().into()))
-                .collect(),
-            full_range_included,
-        )
+            .map(|r| RangeAtBlockRecord::from_abi(r.get_value()))
+            .collect::<Result<Vec<RangeAtBlockRecord>, _>>()
+            .map_err(|e| Error::from(e.context(ErrorKind::StorageCorruption)))?;
+        let mut items = vec![];
+        for record in records.into_iter() {
+            if record.plasma_data_block.get_is_included() {
+                let state_update = StateUpdate::try_from(record.plasma_data_block)
+                    .map_err(|e| Error::from(e.context(ErrorKind::StorageCorruption)))?;
+                items.push(QuantifierResultItem::StateUpdate(state_update));
+            } else if !Self::verify_exclusion(&record.plasma_data_block, &record.inclusion_proof) {
+                full_range_included = false
+            }
+        }
+        Ok(QuantifierResult::new(items, full_range_included))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlockRangeQuantifier;
+    use crate::db::{RangeAtBlockDb, RangeAtBlockRecordBuilder};
+    use crate::error::ErrorKind;
+    use crate::property_executor::PropertyExecutor;
+    use crate::types::{Integer, Property, PropertyInput, StateUpdate};
+    use abi_utils::Encodable;
+    use bytes::Bytes;
+    use ethereum_types::{Address, H256};
+    use plasma_core::data_structure::range::strategy::arb_range;
+    use plasma_core::data_structure::Range;
+    use plasma_db::impls::kvs::CoreDbMemoryImpl;
+    use plasma_db::traits::rangestore::RangeStore;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_get_all_quantified_with_corrupted_record() {
+        let decider: PropertyExecutor<CoreDbMemoryImpl> = Default::default();
+        assert!(decider
+            .get_range_db()
+            .bucket(&Bytes::from(&b"range_at_block"[..]))
+            .bucket(&Integer::new(1).into())
+            .put(0, 10, b"corrupted")
+            .is_ok());
+        let result = BlockRangeQuantifier::get_all_quantified(
+            &decider,
+            &[
+                PropertyInput::ConstantInteger(Integer::new(1)),
+                PropertyInput::ConstantRange(Range::new(0, 10)),
+            ],
+        );
+        match result {
+            Err(e) => match e.kind() {
+                ErrorKind::StorageCorruption => {}
+                _ => panic!("unexpected error kind"),
+            },
+            Ok(_) => panic!("corrupted record must not be decoded"),
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_get_all_quantified_in_included_range(range in arb_range(1000)) {
+            let decider: PropertyExecutor<CoreDbMemoryImpl> = Default::default();
+            let state_update = StateUpdate::new(
+                Integer::new(1),
+                Address::zero(),
+                Range::new(0, 1000),
+                Property::new(Address::zero(), vec![]),
+            );
+            let record = RangeAtBlockRecordBuilder::new(Integer::new(1), Range::new(0, 1000))
+                .deposit_address(Address::zero())
+                .inclusion_proof(Bytes::new())
+                .root(H256::zero())
+                .data(Bytes::from(state_update.to_abi()))
+                .build()
+                .unwrap();
+            assert!(RangeAtBlockDb::new(decider.get_range_db())
+                .store_witness(record.root, record.inclusion_proof, record.plasma_data_block)
+                .is_ok());
+            let result = BlockRangeQuantifier::get_all_quantified(
+                &decider,
+                &[
+                    PropertyInput::ConstantInteger(Integer::new(1)),
+                    PropertyInput::ConstantRange(range),
+                ],
+            )
+            .unwrap();
+            prop_assert!(result.get_all_results_quantified());
+            prop_assert_eq!(result.get_results().len(), 1);
+        }
     }
 }
@@ -0,0 +1,94 @@
+use crate::db::HashPreimageDb;
+use crate::error::{Error, ErrorKind};
+use crate::property_executor::PropertyExecutor;
+use crate::types::{PropertyInput, QuantifierResult, QuantifierResultItem};
+use crate::utils::static_hash;
+use failure::Fail;
+use plasma_db::traits::kvs::KeyValueStore;
+
+/// Quantifies all known preimages of the hash.
+pub struct HashedPreimageQuantifier {}
+
+impl Default for HashedPreimageQuantifier {
+    fn default() -> Self {
+        Self {}
+    }
+}
+
+impl HashedPreimageQuantifier {
+    /// Returns error if stored preimages can't be read.
+    pub fn get_all_quantified<KVS: KeyValueStore>(
+        decider: &PropertyExecutor<KVS>,
+        inputs: &[PropertyInput],
+    ) -> Result<QuantifierResult, Error> {
+        let hash = decider.get_variable(&inputs[0]).to_h256();
+        let db: HashPreimageDb<KVS> = HashPreimageDb::new(decider.get_db());
+        let preimages = db
+            .get_preimages(hash)
+            .map_err(|e| Error::from(e.context(ErrorKind::StorageCorruption)))?;
+        Ok(QuantifierResult::new(
+            preimages
+                .into_iter()
+                .filter(|preimage| static_hash(preimage) == hash)
+                .map(QuantifierResultItem::Bytes)
+                .collect(),
+            true,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::HashPreimageDb;
+    use crate::error::ErrorKind;
+    use crate::property_executor::PropertyExecutor;
+    use crate::types::PropertyInput;
+    use crate::utils::static_hash;
+    use crate::DeciderManager;
+    use bytes::Bytes;
+    use plasma_db::impls::kvs::CoreDbMemoryImpl;
+    use plasma_db::traits::kvs::{BaseDbKey, KeyValueStore};
+
+    #[test]
+    fn test_get_all_quantified() {
+        let preimage = Bytes::from("secret");
+        let hash = static_hash(&preimage);
+        let decider: PropertyExecutor<CoreDbMemoryImpl> = Default::default();
+        let db = HashPreimageDb::new(decider.get_db());
+        assert!(db.store_witness(hash, &preimage).is_ok());
+        let quantified = decider
+            .get_all_quantified(&DeciderManager::q_hashed_preimage(vec![
+                PropertyInput::ConstantH256(hash),
+            ]))
+            .unwrap();
+        assert_eq!(quantified.get_results().len(), 1);
+        assert_eq!(quantified.get_results()[0].to_bytes(), preimage);
+        let unknown = decider
+            .get_all_quantified(&DeciderManager::q_hashed_preimage(vec![
+                PropertyInput::ConstantH256(static_hash(&Bytes::from("unknown"))),
+            ]))
+            .unwrap();
+        assert!(unknown.get_results().is_empty());
+    }
+
+    #[test]
+    fn test_get_all_quantified_with_corrupted_record() {
+        let hash = static_hash(&Bytes::from("secret"));
+        let decider: PropertyExecutor<CoreDbMemoryImpl> = Default::default();
+        assert!(decider
+            .get_db()
+            .bucket(&BaseDbKey::from(&b"preimage_exists_decider"[..]))
+            .put(&BaseDbKey::from(hash.as_bytes()), b"corrupted")
+            .is_ok());
+        let result = decider.get_all_quantified(&DeciderManager::q_hashed_preimage(vec![
+            PropertyInput::ConstantH256(hash),
+        ]));
+        match result {
+            Err(e) => match e.kind() {
+                ErrorKind::StorageCorruption => {}
+                _ => panic!("unexpected error kind"),
+            },
+            Ok(_) => panic!("corrupted record must not be quantified"),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use crate::property_executor::PropertyExecutor;
+use crate::types::{PropertyInput, QuantifierResult};
+use plasma_db::traits::kvs::KeyValueStore;
+
+/// NullQuantifier quantifies nothing, so properties over it are vacuously true.
+pub struct NullQuantifier {}
+
+impl Default for NullQuantifier {
+    fn default() -> Self {
+        Self {}
+    }
+}
+
+impl NullQuantifier {
+    pub fn get_all_quantified<KVS: KeyValueStore>(
+        _decider: &PropertyExecutor<KVS>,
+        _inputs: &[PropertyInput],
+    ) -> QuantifierResult {
+        QuantifierResult::new(vec![], true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::property_executor::PropertyExecutor;
+    use crate::types::{Integer, PropertyInput};
+    use crate::DeciderManager;
+    use bytes::Bytes;
+    use plasma_db::impls::kvs::CoreDbMemoryImpl;
+
+    #[test]
+    fn test_for_all_over_null_quantifier() {
+        let decider: PropertyExecutor<CoreDbMemoryImpl> = Default::default();
+        let quantified = decider
+            .get_all_quantified(&DeciderManager::q_null(vec![]))
+            .unwrap();
+        assert!(quantified.get_results().is_empty());
+        assert!(quantified.get_all_results_quantified());
+        let property = DeciderManager::for_all_such_that_decider(
+            DeciderManager::q_null(vec![]),
+            Bytes::from("n"),
+            DeciderManager::equal_decider(vec![
+                PropertyInput::ConstantInteger(Integer(1)),
+                PropertyInput::ConstantInteger(Integer(2)),
+            ]),
+        );
+        assert!(decider.decide(&property).unwrap().get_outcome());
+    }
+}
@@ -0,0 +1,94 @@
+use crate::error::Error;
+use crate::property_executor::{DecisionContext, PropertyExecutor};
+use crate::quantifiers::BlockRangeQuantifier;
+use crate::types::{PropertyInput, QuantifierResult};
+use crate::DecideMixin;
+use plasma_db::traits::kvs::KeyValueStore;
+
+/// Quantifies state updates at block `inputs[0]` in range `inputs[1]`
+/// for which property `inputs[3]` is decided true.
+/// Each state update is bound to placeholder `inputs[2]` while deciding the property.
+pub struct StateObjectQuantifier {}
+
+impl Default for StateObjectQuantifier {
+    fn default() -> Self {
+        Self {}
+    }
+}
+
+impl StateObjectQuantifier {
+    pub fn get_all_quantified<KVS>(
+        decider: &PropertyExecutor<KVS>,
+        inputs: &[PropertyInput],
+        context: &DecisionContext,
+    ) -> Result<QuantifierResult, Error>
+    where
+        KVS: KeyValueStore,
+    {
+        let placeholder = decider.get_variable(&inputs[2]).to_bytes();
+        let property = decider.get_variable(&inputs[3]).to_property();
+        Ok(
+            BlockRangeQuantifier::get_all_quantified(decider, &inputs[..2])?.filter(|item| {
+                decider.set_variable(placeholder.clone(), item.clone());
+                property
+                    .decide(decider, context)
+                    .map(|decision| decision.get_outcome())
+                    .unwrap_or(false)
+            }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::RangeAtBlockDb;
+    use crate::property_executor::PropertyExecutor;
+    use crate::types::{Integer, PlasmaDataBlock, Property, PropertyInput, StateUpdate};
+    use crate::DeciderManager;
+    use bytes::Bytes;
+    use ethereum_types::Address;
+    use plasma_core::data_structure::Range;
+    use plasma_db::impls::kvs::CoreDbMemoryImpl;
+
+    #[test]
+    fn test_get_all_quantified() {
+        let decider: PropertyExecutor<CoreDbMemoryImpl> = Default::default();
+        let db = RangeAtBlockDb::new(decider.get_range_db());
+        let state_updates: Vec<StateUpdate> = (0..2)
+            .map(|i| {
+                StateUpdate::new(
+                    Integer::new(1),
+                    Address::zero(),
+                    Range::new(i * 10, (i + 1) * 10),
+                    Property::new(Address::from_low_u64_be(i), vec![]),
+                )
+            })
+            .collect();
+        for state_update in state_updates.iter() {
+            assert!(db
+                .store_witness(
+                    Bytes::new(),
+                    Bytes::new(),
+                    PlasmaDataBlock::from(state_update.clone())
+                )
+                .is_ok());
+        }
+        let quantified = decider
+            .get_all_quantified(&DeciderManager::q_state_object(vec![
+                PropertyInput::ConstantInteger(Integer::new(1)),
+                PropertyInput::ConstantRange(Range::new(0, 20)),
+                PropertyInput::ConstantBytes(Bytes::from("su")),
+                PropertyInput::ConstantProperty(DeciderManager::equal_decider(vec![
+                    PropertyInput::Placeholder(Bytes::from("su")),
+                    PropertyInput::ConstantStateUpdate(state_updates[1].clone()),
+                ])),
+            ]))
+            .unwrap();
+        assert_eq!(quantified.get_results().len(), 1);
+        assert_eq!(
+            quantified.get_results()[0].to_state_update().get_range(),
+            Range::new(10, 20)
+        );
+        assert!(!quantified.get_all_results_quantified());
+    }
+}
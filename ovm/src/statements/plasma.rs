@@ -14,17 +14,15 @@ use plasma_core::data_structure::Range;
 ///      Or(b, Included(p), Excluded(b, p))
 pub fn create_plasma_property(specified_block_number: Integer, range: Range) -> Property {
     DeciderManager::for_all_such_that_decider(
-        DeciderManager::q_less_than(vec![PropertyInput::ConstantInteger(specified_block_number)]),
+        DeciderManager::q_less_than(vec![PropertyInput::from_integer(specified_block_number)]),
         Bytes::from("block"),
         DeciderManager::for_all_such_that_decider(
             DeciderManager::q_block(vec![
-                PropertyInput::Placeholder(Bytes::from("block")),
-                PropertyInput::ConstantRange(range),
+                PropertyInput::placeholder("block"),
+                PropertyInput::from_range(range),
             ]),
             Bytes::from("state_update"),
-            DeciderManager::is_deprecated(vec![PropertyInput::Placeholder(Bytes::from(
-                "state_update",
-            ))]),
+            DeciderManager::is_deprecated(vec![PropertyInput::placeholder("state_update")]),
         ),
     )
 }
@@ -36,7 +34,9 @@ mod tests {
     use crate::db::{RangeAtBlockDb, TransactionDb};
     use crate::deciders::signed_by_decider::Verifier as SignatureVerifier;
     use crate::property_executor::PropertyExecutor;
-    use crate::types::{Integer, PlasmaDataBlock, PropertyInput, StateUpdate};
+    use crate::types::{
+        DoubleLayerTreeLeafExt, Integer, PlasmaDataBlock, PropertyInput, StateUpdate,
+    };
     use crate::DeciderManager;
     use abi_utils::Encodable;
     use bytes::Bytes;
@@ -84,16 +84,17 @@ mod tests {
             if i == 0 {
                 first_state_update_opt = Some(state_update.clone());
             }
-            leaves.push(DoubleLayerTreeLeaf {
-                address: deposit_contract_address,
-                end: i * 30 + 100,
-                data: if inclusion {
-                    Bytes::from(state_update.to_abi())
-                } else {
-                    Bytes::from(H256::zero().as_bytes())
-                },
+            leaves.push(if inclusion {
+                DoubleLayerTreeLeaf::from_state_update(&state_update, deposit_contract_address)
+            } else {
+                DoubleLayerTreeLeaf {
+                    address: deposit_contract_address,
+                    end: i * 30 + 100,
+                    data: Bytes::from(H256::zero().as_bytes()),
+                }
             })
         }
+        let first_state_update = first_state_update_opt.unwrap();
         let tree = DoubleLayerTree::generate(&leaves);
         let root = tree.get_root();
         let inclusion_proof = tree.get_inclusion_proof(deposit_contract_address, 0);
@@ -103,7 +104,11 @@ mod tests {
             root.clone(),
             inclusion,
             block_number,
-            leaves[0].data.clone(),
+            if inclusion {
+                Bytes::from(first_state_update.to_abi())
+            } else {
+                leaves[0].data.clone()
+            },
         );
         assert!(db
             .store_witness(root, inclusion_proof, plasma_data_block.clone())
@@ -112,7 +117,6 @@ mod tests {
         let tx_body =
             TransactionParams::new(Address::zero(), Range::new(0, 100), Bytes::default()).to_abi();
         let signature = SignatureVerifier::sign(&secret_key, &Bytes::from(tx_body));
-        let first_state_update = first_state_update_opt.unwrap();
         tx_db.put_transaction(
             first_state_update.get_block_number().0,
             Transaction::new(
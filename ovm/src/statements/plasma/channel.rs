@@ -85,7 +85,9 @@ mod tests {
     use crate::db::{RangeAtBlockDb, SignedByDb, TransactionDb};
     use crate::deciders::signed_by_decider::Verifier as SignatureVerifier;
     use crate::property_executor::PropertyExecutor;
-    use crate::types::{PlasmaDataBlock, Property, QuantifierResultItem, StateUpdate};
+    use crate::types::{
+        DoubleLayerTreeLeafExt, PlasmaDataBlock, Property, QuantifierResultItem, StateUpdate,
+    };
     use crate::DeciderManager;
     use abi_utils::abi::Encodable;
     use abi_utils::Integer;
@@ -180,21 +182,16 @@ mod tests {
             .store_witness(bob, tx_body.clone(), signature_bob.clone())
             .is_ok());
 
-        let leaf1 = DoubleLayerTreeLeaf {
-            address: deposit_contract_address,
-            end: 100,
-            data: Bytes::from(state_update.to_abi()),
-        };
+        let leaf1 = DoubleLayerTreeLeaf::from_state_update(&state_update, deposit_contract_address);
         let leaf2 = DoubleLayerTreeLeaf {
             address: corresponding_deposit_contract_address,
             end: 100,
             data: Bytes::from(H256::zero().as_bytes()),
         };
-        let leaf3 = DoubleLayerTreeLeaf {
-            address: corresponding_deposit_contract_address,
-            end: 200,
-            data: Bytes::from(corresponding_state_update.to_abi()),
-        };
+        let leaf3 = DoubleLayerTreeLeaf::from_state_update(
+            &corresponding_state_update,
+            corresponding_deposit_contract_address,
+        );
         let tree = DoubleLayerTree::generate(&[leaf1, leaf2, leaf3.clone()]);
         let root = tree.get_root();
         let index = tree.get_index(corresponding_deposit_contract_address, &leaf3.data);
@@ -209,7 +206,7 @@ mod tests {
             root.clone(),
             true,
             block_number,
-            leaf3.data.clone(),
+            Bytes::from(corresponding_state_update.to_abi()),
         );
         assert!(range_at_block_db
             .store_witness(root, inclusion_proof, plasma_data_block.clone())
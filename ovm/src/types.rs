@@ -12,5 +12,5 @@ pub use self::core::{
 pub use self::decision_value::DecisionValue;
 pub use self::plasma_data_block::PlasmaDataBlock;
 pub use self::property_input::PropertyInput;
-pub use self::state_update::StateUpdate;
+pub use self::state_update::{DoubleLayerTreeLeafExt, StateUpdate, StateUpdateBuilder};
 pub use self::state_update_list::StateUpdateList;
@@ -2,8 +2,9 @@ use super::plasma_data_block::PlasmaDataBlock;
 use super::state_update::StateUpdate;
 use crate::db::Message;
 use crate::error::Error;
-use crate::property_executor::PropertyExecutor;
+use crate::property_executor::{DecisionContext, PropertyExecutor};
 use crate::types::PropertyInput;
+use crate::utils::static_hash;
 pub use abi_utils::Integer;
 use abi_utils::{Decodable, Encodable, Error as AbiError, ErrorKind as AbiErrorKind};
 use bytes::Bytes;
@@ -27,6 +28,30 @@ impl Property {
     pub fn new(decider: Address, inputs: Vec<PropertyInput>) -> Self {
         Self { decider, inputs }
     }
+    /// Returns keccak256 hash of ABI encoded property.
+    pub fn hash(&self) -> H256 {
+        static_hash(&Bytes::from(self.to_abi()))
+    }
+    /// Returns the number of direct inputs.
+    pub fn inputs_count(&self) -> usize {
+        self.inputs.len()
+    }
+    /// Returns nesting depth of sub properties including the property of state update.
+    /// A property without sub properties has depth 1.
+    pub fn max_depth(&self) -> usize {
+        1 + self
+            .inputs
+            .iter()
+            .map(|input| match input {
+                PropertyInput::ConstantProperty(property) => property.max_depth(),
+                PropertyInput::ConstantStateUpdate(state_update) => {
+                    state_update.get_property().max_depth()
+                }
+                _ => 0,
+            })
+            .max()
+            .unwrap_or(0)
+    }
 }
 
 impl Encodable for Property {
@@ -75,7 +100,7 @@ impl From<Property> for Token {
 }
 
 /// Implication proof element has the property which is decided by Decider
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ImplicationProofElement {
     implication: Property,
     implication_witness: Option<Bytes>,
@@ -88,22 +113,64 @@ impl ImplicationProofElement {
             implication_witness,
         }
     }
+    pub fn get_implication(&self) -> &Property {
+        &self.implication
+    }
+    pub fn get_implication_witness(&self) -> Option<&Bytes> {
+        self.implication_witness.as_ref()
+    }
 }
 
-impl From<ImplicationProofElement> for Token {
-    fn from(element: ImplicationProofElement) -> Token {
-        Token::Tuple(vec![
-            element.implication.into(),
-            Token::Bytes(match element.implication_witness {
+impl Encodable for ImplicationProofElement {
+    fn to_tuple(&self) -> Vec<Token> {
+        vec![
+            self.implication.clone().into(),
+            Token::Bytes(match &self.implication_witness {
                 Some(v) => v.to_vec(),
                 None => vec![],
             }),
-        ])
+        ]
+    }
+}
+
+/// Empty witness is decoded as None.
+impl Decodable for ImplicationProofElement {
+    type Ok = ImplicationProofElement;
+    fn from_tuple(tuple: &[Token]) -> Result<Self, AbiError> {
+        if tuple.len() != 2 {
+            return Err(AbiError::from(AbiErrorKind::AbiDecode));
+        }
+        let implication = tuple[0].clone().to_tuple();
+        let implication_witness = tuple[1].clone().to_bytes();
+        if let (Some(implication), Some(implication_witness)) = (implication, implication_witness) {
+            Ok(ImplicationProofElement::new(
+                Property::from_tuple(&implication)?,
+                if implication_witness.is_empty() {
+                    None
+                } else {
+                    Some(Bytes::from(implication_witness))
+                },
+            ))
+        } else {
+            Err(AbiError::from(AbiErrorKind::AbiDecode))
+        }
+    }
+    fn get_param_types() -> Vec<ParamType> {
+        vec![
+            ParamType::Tuple(Property::get_param_types()),
+            ParamType::Bytes,
+        ]
+    }
+}
+
+impl From<ImplicationProofElement> for Token {
+    fn from(element: ImplicationProofElement) -> Token {
+        Token::Tuple(element.to_tuple())
     }
 }
 
 /// Decision made by Decider
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Decision {
     outcome: bool,
     implication_proof: Vec<ImplicationProofElement>,
@@ -124,10 +191,59 @@ impl Decision {
     }
 }
 
+impl Encodable for Decision {
+    fn to_tuple(&self) -> Vec<Token> {
+        vec![
+            Token::Bool(self.outcome),
+            Token::Array(
+                self.implication_proof
+                    .iter()
+                    .cloned()
+                    .map(Token::from)
+                    .collect(),
+            ),
+        ]
+    }
+}
+
+impl Decodable for Decision {
+    type Ok = Decision;
+    fn from_tuple(tuple: &[Token]) -> Result<Self, AbiError> {
+        if tuple.len() != 2 {
+            return Err(AbiError::from(AbiErrorKind::AbiDecode));
+        }
+        let outcome = tuple[0].clone().to_bool();
+        let implication_proof = tuple[1].clone().to_array();
+        if let (Some(outcome), Some(implication_proof)) = (outcome, implication_proof) {
+            let implication_proof = implication_proof
+                .into_iter()
+                .map(|element| {
+                    element
+                        .to_tuple()
+                        .ok_or_else(|| AbiError::from(AbiErrorKind::AbiDecode))
+                        .and_then(|tuple| ImplicationProofElement::from_tuple(&tuple))
+                })
+                .collect::<Result<Vec<_>, AbiError>>()?;
+            Ok(Decision::new(outcome, implication_proof))
+        } else {
+            Err(AbiError::from(AbiErrorKind::AbiDecode))
+        }
+    }
+    fn get_param_types() -> Vec<ParamType> {
+        vec![
+            ParamType::Bool,
+            ParamType::Array(Box::new(ParamType::Tuple(
+                ImplicationProofElement::get_param_types(),
+            ))),
+        ]
+    }
+}
+
 pub trait Decider {
     fn decide<T: KeyValueStore>(
         decider: &PropertyExecutor<T>,
         inputs: &[PropertyInput],
+        context: &DecisionContext,
     ) -> Result<Decision, Error>;
 }
 
@@ -211,7 +327,7 @@ impl QuantifierResultItem {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct QuantifierResult {
     results: Vec<QuantifierResultItem>,
     all_results_quantified: bool,
@@ -230,15 +346,39 @@ impl QuantifierResult {
     pub fn get_all_results_quantified(&self) -> bool {
         self.all_results_quantified
     }
+    /// Concatenates results of `other`.
+    /// `all_results_quantified` is true only if both are fully quantified.
+    pub fn merge(mut self, other: QuantifierResult) -> QuantifierResult {
+        self.results.extend(other.results);
+        self.all_results_quantified &= other.all_results_quantified;
+        self
+    }
+    /// Removes items not matching `predicate`.
+    /// `all_results_quantified` becomes false if any item is removed.
+    pub fn filter<F>(mut self, predicate: F) -> QuantifierResult
+    where
+        F: Fn(&QuantifierResultItem) -> bool,
+    {
+        let len = self.results.len();
+        self.results.retain(predicate);
+        if self.results.len() != len {
+            self.all_results_quantified = false;
+        }
+        self
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use super::Property;
+    use super::{
+        Decision, ImplicationProofElement, Integer, Property, QuantifierResult,
+        QuantifierResultItem,
+    };
     use crate::types::PropertyInput;
     use crate::DeciderManager;
     use abi_utils::{Decodable, Encodable};
+    use bytes::Bytes;
     use ethereum_types::H256;
 
     #[test]
@@ -251,4 +391,80 @@ mod tests {
         let decoded = Property::from_abi(&encoded).unwrap();
         assert_eq!(decoded, property);
     }
+
+    #[test]
+    fn test_encode_and_decode_decision() {
+        let preimage_exists = DeciderManager::preimage_exists_decider(vec![
+            PropertyInput::ConstantH256(H256::zero()),
+        ]);
+        let not = DeciderManager::not_decider(preimage_exists.clone());
+        let decision = Decision::new(
+            true,
+            vec![
+                ImplicationProofElement::new(DeciderManager::not_decider(not.clone()), None),
+                ImplicationProofElement::new(not, None),
+                ImplicationProofElement::new(preimage_exists, Some(Bytes::from("preimage"))),
+            ],
+        );
+        let decoded = Decision::from_abi(&decision.to_abi()).unwrap();
+        assert_eq!(decoded, decision);
+        assert!(Decision::from_abi(&[0u8; 10]).is_err());
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let preimage_exists = DeciderManager::preimage_exists_decider(vec![
+            PropertyInput::ConstantH256(H256::zero()),
+        ]);
+        assert_eq!(preimage_exists.inputs_count(), 1);
+        assert_eq!(preimage_exists.max_depth(), 1);
+        let not = DeciderManager::not_decider(preimage_exists.clone());
+        let and = DeciderManager::and_decider(DeciderManager::not_decider(not), preimage_exists);
+        assert_eq!(and.inputs_count(), 2);
+        assert_eq!(and.max_depth(), 4);
+    }
+
+    #[test]
+    fn test_hash() {
+        let property1 =
+            DeciderManager::preimage_exists_decider(vec![
+                PropertyInput::ConstantH256(H256::zero()),
+            ]);
+        let property2 =
+            DeciderManager::preimage_exists_decider(vec![
+                PropertyInput::ConstantH256(H256::zero()),
+            ]);
+        let property3 = DeciderManager::preimage_exists_decider(vec![]);
+        assert_eq!(property1.hash(), property2.hash());
+        assert_ne!(property1.hash(), property3.hash());
+    }
+
+    #[test]
+    fn test_filter_quantifier_result() {
+        let result = || {
+            QuantifierResult::new(
+                (0..4)
+                    .map(|i| QuantifierResultItem::Integer(Integer(i)))
+                    .collect(),
+                true,
+            )
+        };
+        let all = result().filter(|_| true);
+        assert_eq!(all.get_results().len(), 4);
+        assert!(all.get_all_results_quantified());
+        let even = result().filter(|item| item.to_integer().0 % 2 == 0);
+        assert_eq!(even.get_results().len(), 2);
+        assert!(!even.get_all_results_quantified());
+    }
+
+    #[test]
+    fn test_merge_quantifier_result() {
+        let result = QuantifierResult::new(vec![QuantifierResultItem::Integer(Integer(1))], false);
+        let merged = result.clone().merge(QuantifierResult::new(vec![], true));
+        assert_eq!(merged.get_results().len(), 1);
+        assert!(!merged.get_all_results_quantified());
+        let merged = QuantifierResult::new(vec![], true).merge(result.clone().merge(result));
+        assert_eq!(merged.get_results().len(), 2);
+        assert!(!merged.get_all_results_quantified());
+    }
 }
@@ -24,6 +24,15 @@ impl PropertyInput {
     pub fn placeholder(placeholder: &str) -> Self {
         PropertyInput::Placeholder(Bytes::from(placeholder))
     }
+    pub fn from_range(range: Range) -> Self {
+        PropertyInput::ConstantRange(range)
+    }
+    pub fn from_address(address: Address) -> Self {
+        PropertyInput::ConstantAddress(address)
+    }
+    pub fn from_integer(integer: Integer) -> Self {
+        PropertyInput::ConstantInteger(integer)
+    }
     pub fn to_address(&self) -> Address {
         if let PropertyInput::ConstantAddress(address) = self {
             *address
@@ -1,21 +1,22 @@
 use crate::deciders::OwnershipDecider;
+use crate::error::StateUpdateError;
 use crate::property_executor::PropertyExecutor;
 use crate::types::core::{Property, QuantifierResultItem};
-use crate::types::PlasmaDataBlock;
-use crate::DecideMixin;
-use abi_derive::{AbiDecodable, AbiEncodable};
-use abi_utils::{Decodable, Encodable, Integer};
+use crate::types::{PlasmaDataBlock, PropertyInput};
+use abi_utils::{Decodable, Encodable, Error as AbiError, ErrorKind as AbiErrorKind, Integer};
 use bytes::Bytes;
 use ethabi::{ParamType, Token};
 use ethereum_types::Address;
+use merkle_interval_tree::DoubleLayerTreeLeaf;
 use plasma_core::data_structure::error::{
     Error as PlasmaCoreError, ErrorKind as PlasmaCoreErrorKind,
 };
 use plasma_core::data_structure::{Range, Transaction};
 use plasma_db::traits::kvs::KeyValueStore;
+use std::convert::TryFrom;
 use tiny_keccak::Keccak;
 
-#[derive(Clone, Debug, PartialEq, Eq, AbiEncodable, AbiDecodable)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct StateUpdate {
     block_number: Integer,
     deposit_contract_address: Address,
@@ -24,6 +25,9 @@ pub struct StateUpdate {
 }
 
 impl StateUpdate {
+    /// Version of ABI encoding which is put as the first token.
+    pub const CURRENT_VERSION: u8 = 1;
+
     pub fn new(
         block_number: Integer,
         deposit_contract_address: Address,
@@ -58,6 +62,21 @@ impl StateUpdate {
         self.block_number
     }
 
+    /// Returns owner address if the property is ownership state object.
+    pub fn get_owner(&self) -> Option<Address> {
+        if let Some(PropertyInput::ConstantProperty(signed_by)) = self.property.inputs.get(2) {
+            if let Some(PropertyInput::ConstantAddress(address)) = signed_by.inputs.get(0) {
+                return Some(*address);
+            }
+        }
+        None
+    }
+
+    /// Returns CRC32 of ABI encoded state update to detect storage corruption.
+    pub fn checksum(&self) -> u32 {
+        crc32fast::hash(&self.to_abi())
+    }
+
     pub fn get_hash(&self) -> Bytes {
         let mut sha3 = Keccak::new_sha3_256();
         sha3.update(&self.to_abi());
@@ -81,7 +100,7 @@ impl StateUpdate {
             Bytes::from("state_update"),
             QuantifierResultItem::StateUpdate(self.clone()),
         );
-        let decided = property.decide(decider);
+        let decided = decider.decide(&property);
         println!(
             "decide local deprecation claim {:?}. decision = {:?}",
             transaction.get_range(),
@@ -109,8 +128,302 @@ impl StateUpdate {
     }
 }
 
-impl From<PlasmaDataBlock> for StateUpdate {
-    fn from(plasma_data_block: PlasmaDataBlock) -> Self {
-        StateUpdate::from_abi(plasma_data_block.get_data()).unwrap()
+/// Builds StateUpdate after checking that all fields are set and consistent.
+#[derive(Clone, Debug, Default)]
+pub struct StateUpdateBuilder {
+    block_number: Option<Integer>,
+    deposit_contract_address: Option<Address>,
+    range: Option<Range>,
+    property: Option<Property>,
+}
+
+impl StateUpdateBuilder {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn block_number(mut self, block_number: Integer) -> Self {
+        self.block_number = Some(block_number);
+        self
+    }
+
+    pub fn deposit_contract_address(mut self, deposit_contract_address: Address) -> Self {
+        self.deposit_contract_address = Some(deposit_contract_address);
+        self
+    }
+
+    pub fn range(mut self, range: Range) -> Self {
+        self.range = Some(range);
+        self
+    }
+
+    pub fn property(mut self, property: Property) -> Self {
+        self.property = Some(property);
+        self
+    }
+
+    /// Returns error if any field is missing, block number is 0, range is empty
+    /// or property has no decider.
+    pub fn build(self) -> Result<StateUpdate, StateUpdateError> {
+        let block_number = self
+            .block_number
+            .ok_or(StateUpdateError::MissingField("block_number"))?;
+        let deposit_contract_address = self
+            .deposit_contract_address
+            .ok_or(StateUpdateError::MissingField("deposit_contract_address"))?;
+        let range = self.range.ok_or(StateUpdateError::MissingField("range"))?;
+        let property = self
+            .property
+            .ok_or(StateUpdateError::MissingField("property"))?;
+        if block_number.0 == 0 {
+            return Err(StateUpdateError::InvalidBlockNumber);
+        }
+        if range.is_empty() {
+            return Err(StateUpdateError::EmptyRange);
+        }
+        if property.decider == Address::zero() {
+            return Err(StateUpdateError::EmptyProperty);
+        }
+        Ok(StateUpdate::new(
+            block_number,
+            deposit_contract_address,
+            range,
+            property,
+        ))
+    }
+}
+
+impl Encodable for StateUpdate {
+    fn to_tuple(&self) -> Vec<Token> {
+        vec![
+            Token::Uint(StateUpdate::CURRENT_VERSION.into()),
+            Token::Uint(self.block_number.0.into()),
+            Token::Address(self.deposit_contract_address),
+            Token::Tuple(self.range.to_tuple()),
+            Token::Tuple(self.property.to_tuple()),
+        ]
+    }
+}
+
+impl StateUpdate {
+    fn from_tuple_v1(tuple: &[Token]) -> Result<Self, AbiError> {
+        if tuple.len() != 4 {
+            return Err(AbiError::from(AbiErrorKind::AbiDecode));
+        }
+        let block_number = tuple[0].clone().to_uint();
+        let deposit_contract_address = tuple[1].clone().to_address();
+        let range = tuple[2].clone().to_tuple();
+        let property = tuple[3].clone().to_tuple();
+        if let (Some(block_number), Some(deposit_contract_address), Some(range), Some(property)) =
+            (block_number, deposit_contract_address, range, property)
+        {
+            Ok(StateUpdate::new(
+                Integer(block_number.as_u64()),
+                deposit_contract_address,
+                Range::from_tuple(&range)?,
+                Property::from_tuple(&property)?,
+            ))
+        } else {
+            Err(AbiError::from(AbiErrorKind::AbiDecode))
+        }
+    }
+}
+
+impl Decodable for StateUpdate {
+    type Ok = StateUpdate;
+    fn from_tuple(tuple: &[Token]) -> Result<Self, AbiError> {
+        let version = tuple.get(0).and_then(|t| t.clone().to_uint());
+        match version {
+            Some(v) if v == 1.into() => StateUpdate::from_tuple_v1(&tuple[1..]),
+            _ => Err(AbiError::from(AbiErrorKind::AbiDecode)),
+        }
+    }
+    fn get_param_types() -> Vec<ParamType> {
+        vec![
+            ParamType::Uint(8),
+            ParamType::Uint(256),
+            ParamType::Address,
+            ParamType::Tuple(Range::get_param_types()),
+            ParamType::Tuple(Property::get_param_types()),
+        ]
+    }
+}
+
+/// `root` is left empty because it is decided when the block is merkelized.
+impl From<StateUpdate> for PlasmaDataBlock {
+    fn from(state_update: StateUpdate) -> Self {
+        PlasmaDataBlock::new(
+            state_update.get_deposit_contract_address(),
+            state_update.get_range(),
+            Bytes::new(),
+            true,
+            state_update.get_block_number(),
+            Bytes::from(state_update.to_abi()),
+        )
+    }
+}
+
+/// Fails if `plasma_data_block` is of exclusion or its data isn't a state update.
+impl TryFrom<PlasmaDataBlock> for StateUpdate {
+    type Error = AbiError;
+    fn try_from(plasma_data_block: PlasmaDataBlock) -> Result<Self, Self::Error> {
+        if !plasma_data_block.get_is_included() {
+            return Err(AbiError::from(AbiErrorKind::AbiDecode));
+        }
+        StateUpdate::from_abi(plasma_data_block.get_data())
+    }
+}
+
+/// Builds `DoubleLayerTreeLeaf` of `StateUpdate`.
+/// `merkle_interval_tree` can't depend on ovm, so it is provided as extension trait.
+pub trait DoubleLayerTreeLeafExt {
+    fn from_state_update(su: &StateUpdate, deposit_address: Address) -> Self;
+}
+
+impl DoubleLayerTreeLeafExt for DoubleLayerTreeLeaf {
+    /// leaf data is keccak256 hash of ABI encoded state update.
+    fn from_state_update(su: &StateUpdate, deposit_address: Address) -> Self {
+        DoubleLayerTreeLeaf {
+            address: deposit_address,
+            end: su.get_range().get_end(),
+            data: su.get_hash(),
+        }
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl plasma_core::schemars::JsonSchema for StateUpdate {
+    fn schema_name() -> String {
+        "StateUpdate".to_owned()
+    }
+    fn json_schema(
+        gen: &mut plasma_core::schemars::gen::SchemaGenerator,
+    ) -> plasma_core::schemars::schema::Schema {
+        use plasma_core::data_structure::schema::{address_schema, hex_schema, object_schema};
+        object_schema(vec![
+            ("block_number", gen.subschema_for::<u64>()),
+            ("deposit_contract_address", address_schema()),
+            ("range", gen.subschema_for::<Range>()),
+            ("property", hex_schema("hex-encoded ABI of property", None)),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DeciderManager;
+
+    #[test]
+    fn test_state_update_builder() {
+        let builder = StateUpdateBuilder::new()
+            .block_number(Integer::new(1))
+            .deposit_contract_address(Address::zero())
+            .range(Range::new(0, 100))
+            .property(DeciderManager::preimage_exists_decider(vec![]));
+        assert_eq!(
+            builder.clone().build(),
+            Ok(StateUpdate::new(
+                Integer::new(1),
+                Address::zero(),
+                Range::new(0, 100),
+                DeciderManager::preimage_exists_decider(vec![]),
+            ))
+        );
+        assert_eq!(
+            builder.clone().block_number(Integer::new(0)).build(),
+            Err(StateUpdateError::InvalidBlockNumber)
+        );
+        assert_eq!(
+            builder.clone().range(Range::new(10, 10)).build(),
+            Err(StateUpdateError::EmptyRange)
+        );
+        assert_eq!(
+            builder
+                .property(Property::new(Address::zero(), vec![]))
+                .build(),
+            Err(StateUpdateError::EmptyProperty)
+        );
+        assert_eq!(
+            StateUpdateBuilder::new().build(),
+            Err(StateUpdateError::MissingField("block_number"))
+        );
+    }
+
+    #[test]
+    fn test_encode_and_decode_with_version() {
+        let state_update = StateUpdate::new(
+            Integer::new(1),
+            Address::zero(),
+            Range::new(0, 100),
+            DeciderManager::preimage_exists_decider(vec![]),
+        );
+        let tuple = state_update.to_tuple();
+        assert_eq!(
+            tuple[0].clone().to_uint().unwrap(),
+            StateUpdate::CURRENT_VERSION.into()
+        );
+        let decoded = StateUpdate::from_abi(&state_update.to_abi()).unwrap();
+        assert_eq!(decoded, state_update);
+    }
+
+    #[test]
+    fn test_decode_unknown_version() {
+        let state_update = StateUpdate::new(
+            Integer::new(1),
+            Address::zero(),
+            Range::new(0, 100),
+            DeciderManager::preimage_exists_decider(vec![]),
+        );
+        let mut tuple = state_update.to_tuple();
+        tuple[0] = Token::Uint(2.into());
+        assert!(StateUpdate::from_tuple(&tuple).is_err());
+    }
+
+    #[test]
+    fn test_convert_with_plasma_data_block() {
+        let state_update = StateUpdate::new(
+            Integer::new(3),
+            Address::from_low_u64_be(1),
+            Range::new(0, 100),
+            DeciderManager::preimage_exists_decider(vec![]),
+        );
+        let plasma_data_block: PlasmaDataBlock = state_update.clone().into();
+        assert_eq!(plasma_data_block.get_block_number(), Integer::new(3));
+        assert_eq!(plasma_data_block.get_updated_range(), Range::new(0, 100));
+        assert_eq!(
+            plasma_data_block.get_deposit_contract_address(),
+            Address::from_low_u64_be(1)
+        );
+        assert_eq!(
+            StateUpdate::try_from(plasma_data_block).unwrap(),
+            state_update
+        );
+
+        let excluded = PlasmaDataBlock::new(
+            Address::zero(),
+            Range::new(0, 100),
+            Bytes::new(),
+            false,
+            Integer::new(3),
+            Bytes::new(),
+        );
+        assert!(StateUpdate::try_from(excluded).is_err());
+    }
+
+    #[test]
+    fn test_double_layer_tree_leaf_from_state_update() {
+        let deposit_address = Address::from_low_u64_be(1);
+        let state_update = StateUpdate::new(
+            Integer::new(1),
+            deposit_address,
+            Range::new(0, 100),
+            DeciderManager::preimage_exists_decider(vec![]),
+        );
+        let leaf = DoubleLayerTreeLeaf::from_state_update(&state_update, deposit_address);
+        assert_eq!(leaf.address, deposit_address);
+        assert_eq!(leaf.end, 100);
+        assert_eq!(leaf.data, state_update.get_hash());
+        assert_eq!(leaf.data.len(), 32);
     }
 }
@@ -1,15 +1,51 @@
 use super::StateUpdate;
 use abi_utils::{Decodable, Encodable, Error as AbiError};
 use ethabi::{ParamType, Token};
+use plasma_core::data_structure::Range;
+use std::collections::BTreeMap;
 
 #[derive(Clone, Debug)]
 pub struct StateUpdateList {
-    pub state_updates: Vec<StateUpdate>,
+    state_updates: Vec<StateUpdate>,
+    /// Indexes of `state_updates` by range start. It's built in `new`.
+    index: BTreeMap<u64, Vec<usize>>,
+    /// The largest width of ranges, which bounds how far before a query a range can start.
+    max_width: u64,
 }
 
 impl StateUpdateList {
     pub fn new(state_updates: Vec<StateUpdate>) -> Self {
-        Self { state_updates }
+        let mut index: BTreeMap<u64, Vec<usize>> = BTreeMap::new();
+        let mut max_width = 0;
+        for (i, state_update) in state_updates.iter().enumerate() {
+            let range = state_update.get_range();
+            index
+                .entry(range.get_start())
+                .or_insert_with(Vec::new)
+                .push(i);
+            max_width = max_width.max(range.get_amount());
+        }
+        Self {
+            state_updates,
+            index,
+            max_width,
+        }
+    }
+    pub fn get_state_updates(&self) -> &[StateUpdate] {
+        &self.state_updates
+    }
+    /// Returns state updates overlapping with `range` sorted by range start.
+    pub fn get_for_range(&self, range: Range) -> Vec<&StateUpdate> {
+        if range.is_empty() {
+            return vec![];
+        }
+        let lower = range.get_start().saturating_sub(self.max_width);
+        self.index
+            .range(lower..range.get_end())
+            .flat_map(|(_, indexes)| indexes.iter())
+            .map(|i| &self.state_updates[*i])
+            .filter(|s| s.get_range().intersection(&range).is_some())
+            .collect()
     }
 }
 
@@ -69,8 +105,35 @@ mod tests {
         let encoded = state_update_list.to_abi();
         let decoded = StateUpdateList::from_abi(&encoded).unwrap();
         assert_eq!(
-            decoded.state_updates.len(),
-            state_update_list.state_updates.len()
+            decoded.get_state_updates().len(),
+            state_update_list.get_state_updates().len()
+        );
+    }
+
+    #[test]
+    fn test_get_for_range() {
+        let state_updates: Vec<StateUpdate> = [(50, 60), (0, 10), (10, 40), (100, 200)]
+            .iter()
+            .map(|(start, end)| {
+                StateUpdate::new(
+                    Integer(1),
+                    Address::zero(),
+                    Range::new(*start, *end),
+                    DeciderManager::preimage_exists_decider(vec![]),
+                )
+            })
+            .collect();
+        let list = StateUpdateList::new(state_updates);
+        let ranges: Vec<Range> = list
+            .get_for_range(Range::new(5, 55))
+            .iter()
+            .map(|s| s.get_range())
+            .collect();
+        assert_eq!(
+            ranges,
+            vec![Range::new(0, 10), Range::new(10, 40), Range::new(50, 60)]
         );
+        assert_eq!(list.get_for_range(Range::new(150, 160)).len(), 1);
+        assert!(list.get_for_range(Range::new(60, 100)).is_empty());
     }
 }
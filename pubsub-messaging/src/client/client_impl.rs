@@ -1,19 +1,156 @@
-use super::{Error, Handler, Message, Result};
+use super::{ChallengeSigner, Error, Handler, Message, Result, SendError, AUTH_NONCE_LENGTH};
 use bincode::{deserialize, serialize};
+use futures::task::{self, Task};
+use futures::{Async, Future, Poll};
 use std::marker::{Send, Sync};
 use std::sync::mpsc::channel;
 use std::sync::mpsc::Sender as ThreadOut;
-use std::sync::Arc;
+use std::sync::{Arc, Condvar, Mutex};
 use std::thread::{spawn, JoinHandle};
+use std::time::{Duration, Instant};
 use ws::{
     connect as ws_connect, CloseCode, Error as WsError, Handler as WsHandler, Handshake,
     Message as WsMessage, Result as WsResult, Sender,
 };
 
+/// Counts messages received from the server so that senders can wait for the response.
+#[derive(Default)]
+struct Responses {
+    /// Number of received messages and whether the connection is closed.
+    state: Mutex<(u64, bool)>,
+    received: Condvar,
+    /// Tasks of `ResponseFuture` waiting for the next message.
+    tasks: Mutex<Vec<Task>>,
+}
+
+impl Responses {
+    fn notify(&self, closed: bool) {
+        {
+            let mut state = self.state.lock().unwrap();
+            if closed {
+                state.1 = true;
+            } else {
+                state.0 += 1;
+            }
+        }
+        self.received.notify_all();
+        for task in self.tasks.lock().unwrap().drain(..) {
+            task.notify();
+        }
+    }
+
+    fn count(&self) -> u64 {
+        self.state.lock().unwrap().0
+    }
+
+    /// Returns Ok(true) if a message is received after `count` messages.
+    fn check(&self, count: u64) -> std::result::Result<bool, SendError> {
+        let state = self.state.lock().unwrap();
+        if state.0 > count {
+            Ok(true)
+        } else if state.1 {
+            Err(SendError::Ws)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Blocks until a message is received after `count` messages or `timeout` elapses.
+    fn wait(&self, count: u64, timeout: Duration) -> std::result::Result<(), SendError> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if state.0 > count {
+                return Ok(());
+            }
+            if state.1 {
+                return Err(SendError::Ws);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(SendError::Timeout);
+            }
+            state = self.received.wait_timeout(state, deadline - now).unwrap().0;
+        }
+    }
+}
+
+/// Resolves when a message is received after `count` messages.
+struct ResponseFuture {
+    responses: Arc<Responses>,
+    count: u64,
+}
+
+impl Future for ResponseFuture {
+    type Item = ();
+    type Error = SendError;
+
+    fn poll(&mut self) -> Poll<(), SendError> {
+        if self.responses.check(self.count)? {
+            return Ok(Async::Ready(()));
+        }
+        self.responses.tasks.lock().unwrap().push(task::current());
+        // the message may be received before the task is registered
+        if self.responses.check(self.count)? {
+            return Ok(Async::Ready(()));
+        }
+        Ok(Async::NotReady)
+    }
+}
+
 struct Inner<T: Handler> {
     handler: T,
     ws: Sender,
+    responses: Arc<Responses>,
     tx: ThreadOut<Sender>,
+    signer: Option<Arc<dyn ChallengeSigner + Send + Sync>>,
+    /// Identity of the server expected to send the challenge.
+    server_id: String,
+    /// True after the challenge is answered or any other message is received.
+    handshake_done: bool,
+}
+
+/// Time to wait for the challenge of the server which requires authentication.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+impl<T> Inner<T>
+where
+    T: Handler,
+{
+    /// Answers only the first challenge received before any other message
+    /// so that the server can't use the client as a signing oracle.
+    /// Connection is closed if the nonce is malformed.
+    fn answer_challenge(
+        &mut self,
+        signer: Arc<dyn ChallengeSigner + Send + Sync>,
+        message: Message,
+    ) -> WsResult<()> {
+        if self.handshake_done {
+            return Ok(());
+        }
+        let result = if message.message.len() != AUTH_NONCE_LENGTH {
+            self.ws.close(CloseCode::Policy)
+        } else {
+            let mut nonce = [0u8; AUTH_NONCE_LENGTH];
+            nonce.copy_from_slice(&message.message);
+            let signature = signer.sign_challenge(&self.server_id, &nonce);
+            serialize(&Message::auth_response(signature))
+                .map_err(WsError::from)
+                .and_then(|response| self.ws.send(WsMessage::Binary(response)))
+        };
+        self.finish_handshake();
+        result
+    }
+    /// Hands the connection to `connect_with_auth` once the response to the challenge is sent,
+    /// so that messages of the caller don't reach the server before it.
+    fn finish_handshake(&mut self) {
+        if !self.handshake_done {
+            self.handshake_done = true;
+            if self.signer.is_some() {
+                let _ = self.tx.send(self.ws.clone());
+            }
+        }
+    }
 }
 
 impl<T> WsHandler for Inner<T>
@@ -21,13 +158,16 @@ where
     T: Handler,
 {
     fn on_open(&mut self, _: Handshake) -> WsResult<()> {
-        // TODO: handle error
-        let _ = self.tx.send(self.ws.clone());
+        if self.signer.is_none() {
+            // TODO: handle error
+            let _ = self.tx.send(self.ws.clone());
+        }
         self.handler.handle_open(self.ws.clone());
         Ok(())
     }
 
     fn on_close(&mut self, _code: CloseCode, _reason: &str) {
+        self.responses.notify(true);
         self.handler.handle_close();
     }
 
@@ -39,7 +179,12 @@ where
 
         match res {
             Ok(message) => {
+                if let (true, Some(signer)) = (message.is_auth_challenge(), self.signer.clone()) {
+                    return self.answer_challenge(signer, message);
+                }
+                self.finish_handshake();
                 self.handler.handle_message(message, self.ws.clone());
+                self.responses.notify(false);
                 Ok(())
             }
             Err(e) => Err(WsError::from(e)),
@@ -53,6 +198,7 @@ where
 pub struct Client {
     pub sender: Sender,
     pub handle: Arc<JoinHandle<()>>,
+    responses: Arc<Responses>,
 }
 
 impl Client {
@@ -61,6 +207,32 @@ impl Client {
         // TODO: error handling
         let _ = self.sender.send(ws_msg);
     }
+
+    /// Sends `msg` and waits for the response, which is any message received after sending.
+    /// Returns `SendError::Timeout` if the server doesn't respond within `timeout`.
+    pub fn send_with_timeout(
+        &self,
+        msg: Message,
+        timeout: Duration,
+    ) -> std::result::Result<(), SendError> {
+        let count = self.send_message(msg)?;
+        self.responses.wait(count, timeout)
+    }
+
+    /// Sends `msg` without blocking. The future resolves when the response is received.
+    pub fn send_async(&self, msg: Message) -> impl Future<Item = (), Error = SendError> {
+        let responses = self.responses.clone();
+        futures::future::result(self.send_message(msg))
+            .and_then(move |count| ResponseFuture { responses, count })
+    }
+
+    /// Queues `msg` to the connection and returns the number of messages received before it.
+    fn send_message(&self, msg: Message) -> std::result::Result<u64, SendError> {
+        let ws_msg = WsMessage::Binary(serialize(&msg).map_err(|_| SendError::InvalidMessage)?);
+        let count = self.responses.count();
+        self.sender.send(ws_msg).map_err(|_| SendError::Ws)?;
+        Ok(count)
+    }
 }
 
 /// create connection to given host returning Client.
@@ -83,24 +255,207 @@ impl Client {
 pub fn connect<T: Handler + Clone + Send + Sync + 'static>(
     host: String,
     handler: T,
+) -> Result<Client> {
+    connect_inner(host, String::new(), handler, None)
+}
+
+/// create connection to the server which requires authentication.
+/// The nonce sent by server is signed by `signer` with `server_id` and sent back
+/// before this returns. Returns error if the server doesn't send the nonce in time.
+pub fn connect_with_auth<T, S>(
+    host: String,
+    server_id: String,
+    handler: T,
+    signer: S,
+) -> Result<Client>
+where
+    T: Handler + Clone + Send + Sync + 'static,
+    S: ChallengeSigner + Send + Sync + 'static,
+{
+    connect_inner(host, server_id, handler, Some(Arc::new(signer)))
+}
+
+fn connect_inner<T: Handler + Clone + Send + Sync + 'static>(
+    host: String,
+    server_id: String,
+    handler: T,
+    signer: Option<Arc<dyn ChallengeSigner + Send + Sync>>,
 ) -> Result<Client> {
     let (tx, rx) = channel();
+    let requires_auth = signer.is_some();
+    let responses = Arc::new(Responses::default());
+    let inner_responses = responses.clone();
     let t = spawn(move || {
         let url: &str = &format!("ws://{}", host);
         ws_connect(url, |out| Inner {
             handler: handler.clone(),
             ws: out,
+            responses: inner_responses.clone(),
             tx: tx.clone(),
+            signer: signer.clone(),
+            server_id: server_id.clone(),
+            handshake_done: false,
         })
         .unwrap();
     });
 
-    if let Ok(sender) = rx.recv() {
+    let sender = if requires_auth {
+        rx.recv_timeout(HANDSHAKE_TIMEOUT).ok()
+    } else {
+        rx.recv().ok()
+    };
+    if let Some(sender) = sender {
         Ok(Client {
             sender,
             handle: Arc::new(t),
+            responses,
         })
     } else {
         Err(Error::Thread)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{auth_message, spawn_server, spawn_server_with_auth, Authenticator, ServerHandler};
+    use std::thread::sleep;
+
+    #[derive(Clone)]
+    struct NopClientHandler;
+
+    impl Handler for NopClientHandler {
+        fn handle_message(&self, _msg: Message, _sender: Sender) {}
+    }
+
+    /// Echoes messages back if `echo` is true, otherwise never responds.
+    #[derive(Clone)]
+    struct EchoServerHandler {
+        echo: bool,
+    }
+
+    impl ServerHandler for EchoServerHandler {
+        fn handle_message(&mut self, msg: Message, sender: Sender) {
+            if self.echo {
+                let _ = sender.send(WsMessage::Binary(serialize(&msg).unwrap()));
+            }
+        }
+    }
+
+    /// Uses the message itself as signature.
+    struct PlainSigner;
+
+    impl ChallengeSigner for PlainSigner {
+        fn sign_challenge(&self, server_id: &str, nonce: &[u8; AUTH_NONCE_LENGTH]) -> Vec<u8> {
+            auth_message(server_id, nonce)
+        }
+    }
+
+    struct PlainAuthenticator;
+
+    impl Authenticator for PlainAuthenticator {
+        fn authenticate(
+            &self,
+            server_id: &str,
+            nonce: &[u8; AUTH_NONCE_LENGTH],
+            signature: &[u8],
+        ) -> Option<String> {
+            if signature == &auth_message(server_id, nonce)[..] {
+                Some("client".to_string())
+            } else {
+                None
+            }
+        }
+    }
+
+    fn create_message() -> Message {
+        Message::new("server".to_string(), b"message".to_vec())
+    }
+
+    #[test]
+    fn test_send_with_timeout() {
+        let _server = spawn_server(
+            "127.0.0.1:18451".to_owned(),
+            EchoServerHandler { echo: true },
+        )
+        .unwrap();
+        sleep(Duration::from_millis(100));
+        let client = connect("127.0.0.1:18451".to_owned(), NopClientHandler).unwrap();
+        assert_eq!(
+            client.send_with_timeout(create_message(), Duration::from_secs(5)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_send_with_timeout_without_response() {
+        let _server = spawn_server(
+            "127.0.0.1:18452".to_owned(),
+            EchoServerHandler { echo: false },
+        )
+        .unwrap();
+        sleep(Duration::from_millis(100));
+        let client = connect("127.0.0.1:18452".to_owned(), NopClientHandler).unwrap();
+        let started_at = Instant::now();
+        assert_eq!(
+            client.send_with_timeout(create_message(), Duration::from_millis(200)),
+            Err(SendError::Timeout)
+        );
+        assert!(started_at.elapsed() >= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_send_async() {
+        let _server = spawn_server(
+            "127.0.0.1:18453".to_owned(),
+            EchoServerHandler { echo: true },
+        )
+        .unwrap();
+        sleep(Duration::from_millis(100));
+        let client = connect("127.0.0.1:18453".to_owned(), NopClientHandler).unwrap();
+        assert_eq!(client.send_async(create_message()).wait(), Ok(()));
+    }
+
+    #[test]
+    fn test_send_right_after_authentication() {
+        let _server = spawn_server_with_auth(
+            "127.0.0.1:18454".to_owned(),
+            "server".to_owned(),
+            EchoServerHandler { echo: true },
+            PlainAuthenticator,
+        )
+        .unwrap();
+        sleep(Duration::from_millis(100));
+        let client = connect_with_auth(
+            "127.0.0.1:18454".to_owned(),
+            "server".to_owned(),
+            NopClientHandler,
+            PlainSigner,
+        )
+        .unwrap();
+        assert_eq!(
+            client.send_with_timeout(create_message(), Duration::from_secs(5)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_wait_for_response() {
+        let responses = Responses::default();
+        assert_eq!(
+            responses.wait(0, Duration::from_millis(10)),
+            Err(SendError::Timeout)
+        );
+        responses.notify(false);
+        assert_eq!(responses.wait(0, Duration::from_millis(10)), Ok(()));
+        assert_eq!(
+            responses.wait(1, Duration::from_millis(10)),
+            Err(SendError::Timeout)
+        );
+        responses.notify(true);
+        assert_eq!(
+            responses.wait(1, Duration::from_millis(10)),
+            Err(SendError::Ws)
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use super::message::Message;
+use super::message::{Message, AUTH_NONCE_LENGTH};
 use ws::Sender;
 
 pub trait Handler {
@@ -6,3 +6,9 @@ pub trait Handler {
     fn handle_open(&self, _sender: Sender) {}
     fn handle_close(&self) {}
 }
+
+/// Signs the nonce sent by server which requires authentication.
+pub trait ChallengeSigner {
+    /// Must sign `auth_message(server_id, nonce)`, never the nonce itself.
+    fn sign_challenge(&self, server_id: &str, nonce: &[u8; AUTH_NONCE_LENGTH]) -> Vec<u8>;
+}
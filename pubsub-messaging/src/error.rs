@@ -10,4 +10,14 @@ pub enum Error {
     Thread,
 }
 
+#[derive(Debug, Fail, PartialEq)]
+pub enum SendError {
+    #[fail(display = "Send timed out")]
+    Timeout,
+    #[fail(display = "WebSocket fail")]
+    Ws,
+    #[fail(display = "Invalid Message")]
+    InvalidMessage,
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
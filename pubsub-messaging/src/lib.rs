@@ -1,6 +1,8 @@
 #[macro_use]
 extern crate failure;
 extern crate bincode;
+extern crate futures;
+extern crate rand;
 extern crate serde;
 extern crate ws;
 
@@ -9,10 +11,10 @@ pub mod error;
 pub mod message;
 pub mod server;
 
-pub use client::client_impl::{connect, Client};
-pub use client::handler::Handler as ClientHandler;
-pub use error::{Error, Result};
-pub use message::Message;
-pub use server::handler::Handler as ServerHandler;
-pub use server::server_impl::{spawn_server, Server};
+pub use client::client_impl::{connect, connect_with_auth, Client};
+pub use client::handler::{ChallengeSigner, Handler as ClientHandler};
+pub use error::{Error, Result, SendError};
+pub use message::{auth_message, Message, AUTH_NONCE_LENGTH};
+pub use server::handler::{Authenticator, Handler as ServerHandler};
+pub use server::server_impl::{spawn_server, spawn_server_with_auth, Server};
 pub use ws::{CloseCode, Message as WsMessage, Sender};
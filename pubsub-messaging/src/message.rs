@@ -1,5 +1,22 @@
 use serde::{Deserialize, Serialize};
 
+/// `to` of the nonce sent by server to a connecting client.
+pub const AUTH_CHALLENGE: &str = "auth_challenge";
+/// `to` of the signature of the nonce sent back by client.
+pub const AUTH_RESPONSE: &str = "auth_response";
+/// Length of the nonce sent by server.
+pub const AUTH_NONCE_LENGTH: usize = 32;
+/// Prefix of the signed authentication message so that the signature can't be used elsewhere.
+pub const AUTH_DOMAIN: &[u8] = b"plasma-pubsub-auth:";
+
+/// Returns `AUTH_DOMAIN || server_id || nonce` which client signs to authenticate.
+pub fn auth_message(server_id: &str, nonce: &[u8; AUTH_NONCE_LENGTH]) -> Vec<u8> {
+    let mut message = AUTH_DOMAIN.to_vec();
+    message.extend_from_slice(server_id.as_bytes());
+    message.extend_from_slice(nonce);
+    message
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Message {
     pub to: String,
@@ -10,4 +27,16 @@ impl Message {
     pub fn new(to: String, message: Vec<u8>) -> Self {
         Message { to, message }
     }
+    pub fn auth_challenge(nonce: Vec<u8>) -> Self {
+        Message::new(AUTH_CHALLENGE.to_owned(), nonce)
+    }
+    pub fn auth_response(signature: Vec<u8>) -> Self {
+        Message::new(AUTH_RESPONSE.to_owned(), signature)
+    }
+    pub fn is_auth_challenge(&self) -> bool {
+        self.to == AUTH_CHALLENGE
+    }
+    pub fn is_auth_response(&self) -> bool {
+        self.to == AUTH_RESPONSE
+    }
 }
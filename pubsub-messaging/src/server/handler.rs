@@ -1,4 +1,4 @@
-use super::message::Message;
+use super::message::{Message, AUTH_NONCE_LENGTH};
 use ws::Sender;
 
 /// Trait to implement server event handlers
@@ -7,4 +7,18 @@ pub trait Handler: Clone {
     fn handle_message(&mut self, msg: Message, sender: Sender);
     fn handle_open(&mut self, _sender: Sender) {}
     fn handle_close(&mut self) {}
+    /// Called when the client is authenticated as `identity`, before `handle_open`.
+    fn handle_authenticated(&mut self, _identity: String, _sender: Sender) {}
+}
+
+/// Verifies the signature of nonce sent by client.
+pub trait Authenticator {
+    /// Returns identity of the client, e.g. recovered address, or None if it isn't allowed.
+    /// The signature is of `auth_message(server_id, nonce)`.
+    fn authenticate(
+        &self,
+        server_id: &str,
+        nonce: &[u8; AUTH_NONCE_LENGTH],
+        signature: &[u8],
+    ) -> Option<String>;
 }
@@ -1,7 +1,9 @@
-use super::{Error, Handler, Message, Result};
+use super::{Authenticator, Error, Handler, Message, Result, AUTH_NONCE_LENGTH};
 use bincode::{deserialize, serialize};
+use rand::{thread_rng, Rng};
 use std::marker::{Send, Sync};
 use std::sync::mpsc::channel;
+use std::sync::Arc;
 use std::thread::{spawn, JoinHandle};
 use ws::{
     CloseCode, Error as WsError, Handler as WsHandler, Handshake, Message as WsMessage,
@@ -12,6 +14,34 @@ use ws::{
 struct Inner<T: Handler> {
     handler: T,
     ws: Sender,
+    authenticator: Option<Arc<dyn Authenticator + Send + Sync>>,
+    /// Identity of this server which is signed together with nonce.
+    server_id: String,
+    /// Nonce sent to the client. None after the client is authenticated.
+    nonce: Option<[u8; AUTH_NONCE_LENGTH]>,
+}
+
+impl<T> Inner<T>
+where
+    T: Handler,
+{
+    /// Verifies response to the challenge. Connection is closed if it is invalid.
+    fn authenticate(&mut self, message: Message) -> WsResult<()> {
+        let identity = match (&self.authenticator, &self.nonce) {
+            (Some(authenticator), Some(nonce)) if message.is_auth_response() => {
+                authenticator.authenticate(&self.server_id, nonce, &message.message)
+            }
+            _ => None,
+        };
+        if let Some(identity) = identity {
+            self.nonce = None;
+            self.handler.handle_authenticated(identity, self.ws.clone());
+            self.handler.handle_open(self.ws.clone());
+            Ok(())
+        } else {
+            self.ws.close(CloseCode::Policy)
+        }
+    }
 }
 
 impl<T> WsHandler for Inner<T>
@@ -19,6 +49,13 @@ where
     T: Handler,
 {
     fn on_open(&mut self, _: Handshake) -> WsResult<()> {
+        if self.authenticator.is_some() {
+            let nonce = thread_rng().gen::<[u8; AUTH_NONCE_LENGTH]>();
+            self.nonce = Some(nonce);
+            let challenge =
+                serialize(&Message::auth_challenge(nonce.to_vec())).map_err(WsError::from)?;
+            return self.ws.send(WsMessage::Binary(challenge));
+        }
         self.handler.handle_open(self.ws.clone());
         Ok(())
     }
@@ -35,6 +72,9 @@ where
 
         match res {
             Ok(message) => {
+                if self.nonce.is_some() {
+                    return self.authenticate(message);
+                }
                 self.handler.handle_message(message, self.ws.clone());
                 Ok(())
             }
@@ -81,11 +121,39 @@ impl Server {
 pub fn spawn_server<T: Handler + Clone + Send + Sync + 'static>(
     host: String,
     handler: T,
+) -> Result<Server> {
+    spawn_inner(host, String::new(), handler, None)
+}
+
+/// spawn server which sends random nonce to each connecting client
+/// and accepts messages only after `authenticator` verifies the signed nonce.
+/// Clients must sign `server_id` with the nonce, so it should be unique to this server.
+pub fn spawn_server_with_auth<T, A>(
+    host: String,
+    server_id: String,
+    handler: T,
+    authenticator: A,
+) -> Result<Server>
+where
+    T: Handler + Clone + Send + Sync + 'static,
+    A: Authenticator + Send + Sync + 'static,
+{
+    spawn_inner(host, server_id, handler, Some(Arc::new(authenticator)))
+}
+
+fn spawn_inner<T: Handler + Clone + Send + Sync + 'static>(
+    host: String,
+    server_id: String,
+    handler: T,
+    authenticator: Option<Arc<dyn Authenticator + Send + Sync>>,
 ) -> Result<Server> {
     let (tx, rx) = channel();
     let ws = WebSocket::new(move |out: Sender| Inner {
         handler: handler.clone(),
         ws: out,
+        authenticator: authenticator.clone(),
+        server_id: server_id.clone(),
+        nonce: None,
     })
     .unwrap();
 